@@ -1,48 +1,274 @@
+use std::collections::HashMap;
+
+use super::cedict;
+use super::ids;
 use super::trie::Trie;
+use super::unihan;
+use super::validate::Diagnostics;
 
 const CHAR_DATA: &str = include_str!("../data/chars.tsv");
 const WORD_DATA: &str = include_str!("../data/words.tsv");
 const FREQ_DATA: &str = include_str!("../data/freq.txt");
 const LETTERED_DATA: &str = include_str!("../data/lettered.tsv");
+const NAMES_DATA: &str = include_str!("../data/names.tsv");
+const CLASSIFIERS_DATA: &str = include_str!("../data/classifiers.tsv");
+const PHONETICS_DATA: &str = include_str!("../data/phonetics.tsv");
+
+/// Optional Unihan readings file. It is not bundled (it is several MB and
+/// mostly irrelevant fields), so it is read from disk only when present.
+const UNIHAN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/Unihan_Readings.txt");
+
+/// Optional Unihan radical-stroke file, read the same way, for the
+/// `strokes` option.
+const UNIHAN_IRG_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/Unihan_IRGSources.txt");
+
+/// Optional ideographic description sequences (CHISE or cjkvi-ids
+/// format), for `decompose`.
+const IDS_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/ids.txt");
+
+/// Under the `small-freq` feature, frequencies below this are dropped.
+/// Keeps roughly the 22,000 most frequent of the 266,000 freq.txt entries.
+const SMALL_FREQ_MIN: i64 = 1000;
+
+/// Cargo features are not visible to the build script as `cfg`, only as
+/// `CARGO_FEATURE_<NAME>` environment variables.
+fn feature_enabled(name: &str) -> bool {
+    std::env::var_os(format!("CARGO_FEATURE_{}", name)).is_some()
+}
+
+/// Optional folder of CEDICT-format dictionaries (e.g. CC-Canto).
+const CEDICT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/data/cedict");
+
+/// Read every file in `CEDICT_DIR`, sorted by file name so that the load
+/// order (and hence reading priority) does not depend on the filesystem.
+fn read_cedict_files() -> Vec<String> {
+    let Ok(dir) = std::fs::read_dir(CEDICT_DIR) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = dir.filter_map(|e| e.ok()).map(|e| e.path()).collect();
+    paths.sort();
+    paths
+        .iter()
+        .filter_map(|p| std::fs::read_to_string(p).ok())
+        .collect()
+}
+
+/// Weights seen so far, per entry, for the "add up to over 100%" warning.
+#[derive(Default)]
+struct WeightTotals(HashMap<String, u32>);
+
+impl WeightTotals {
+    /// Parse the optional weight (third field) of a `key, reading[, weight]`
+    /// line, e.g. "5%". Returns Ok(None) if there is none and Err(()) if it
+    /// is malformed, recording the error in `diagnostics`; warns once when
+    /// the weights given for `key` add up to over 100%.
+    fn parse(
+        &mut self,
+        diagnostics: &mut Diagnostics,
+        file: &'static str,
+        line: usize,
+        parts: &[&str],
+    ) -> Result<Option<u32>, ()> {
+        let Some(field) = parts.get(2) else {
+            return Ok(None);
+        };
+        let weight = diagnostics.weight(file, line, field).ok_or(())?;
+        let total = self.0.entry(parts[0].to_string()).or_insert(0);
+        *total += weight;
+        if *total > 100 && *total - weight <= 100 {
+            diagnostics.warning(
+                file,
+                line,
+                format!("weights of {} add up to over 100%", parts[0]),
+            );
+        }
+        Ok(Some(weight))
+    }
+}
+
+/// Build the trie from the data files, recording malformed or suspicious
+/// lines in `diagnostics`. Lines with errors are skipped.
+pub fn build_trie(diagnostics: &mut Diagnostics) -> Trie {
+    let chars_only = feature_enabled("CHARS_ONLY");
+    let no_lettered = chars_only || feature_enabled("NO_LETTERED");
+    let small_freq = feature_enabled("SMALL_FREQ");
 
-pub fn build_trie() -> Trie {
     let mut trie = Trie::new();
 
-    for line in CHAR_DATA.lines() {
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            if let Some(ch) = parts[0].chars().next() {
-                // parse "5%" → 5, missing → 100 (highest priority)
-                let weight = parts
-                    .get(2)
-                    .map(|s| s.replace('%', "").trim().parse::<u32>().unwrap_or(0))
-                    .unwrap_or(100);
-                trie.insert_char(ch, parts[1], weight);
+    let mut totals = WeightTotals::default();
+    for (i, line) in CHAR_DATA.lines().enumerate() {
+        let Some(parts) = diagnostics.fields("chars.tsv", i + 1, line, 2, 3) else {
+            continue;
+        };
+        let mut chars = parts[0].chars();
+        let (Some(ch), None) = (chars.next(), chars.next()) else {
+            diagnostics.error(
+                "chars.tsv",
+                i + 1,
+                format!("not a single character: {:?}", parts[0]),
+            );
+            continue;
+        };
+        if !diagnostics.reading("chars.tsv", i + 1, parts[1]) {
+            continue;
+        }
+        // parse "5%" → 5, missing → 100 (highest priority)
+        let Ok(weight) = totals.parse(diagnostics, "chars.tsv", i + 1, &parts) else {
+            continue;
+        };
+        trie.insert_char(ch, parts[1], weight.unwrap_or(100));
+    }
+
+    // words.tsv also carries every multi-character CJK entry, so chars-only
+    // builds stop here for CJK data (Unihan still backfills single chars).
+    if !chars_only {
+        for (i, line) in WORD_DATA.lines().enumerate() {
+            let Some(parts) = diagnostics.fields("words.tsv", i + 1, line, 2, 3) else {
+                continue;
+            };
+            if !diagnostics.reading("words.tsv", i + 1, parts[1]) {
+                continue;
+            }
+            if let Ok(weight) = totals.parse(diagnostics, "words.tsv", i + 1, &parts) {
+                trie.insert_word(parts[0], parts[1], weight);
+            }
+        }
+    }
+
+    // Proper nouns: places and stations, flagged as names at runtime.
+    if !chars_only {
+        for (i, line) in NAMES_DATA.lines().enumerate() {
+            let Some(parts) = diagnostics.fields("names.tsv", i + 1, line, 2, 2) else {
+                continue;
+            };
+            if diagnostics.reading("names.tsv", i + 1, parts[1]) {
+                trie.insert_name(parts[0], parts[1]);
+            }
+        }
+    }
+
+    // Classifiers of nouns: metadata for the `classifiers` option, not
+    // entries of the trie.
+    if !chars_only {
+        for (i, line) in CLASSIFIERS_DATA.lines().enumerate() {
+            let Some(parts) = diagnostics.fields("classifiers.tsv", i + 1, line, 2, 2) else {
+                continue;
+            };
+            let classifiers: Vec<String> = parts[1].split(' ').map(str::to_string).collect();
+            if classifiers.iter().any(String::is_empty) {
+                let message = format!("bad classifier list {:?}", parts[1]);
+                diagnostics.error("classifiers.tsv", i + 1, message);
+                continue;
+            }
+            trie.classifiers.push((parts[0].to_string(), classifiers));
+        }
+    }
+
+    // CEDICT-format dictionaries (CC-Canto etc.) dropped into data/cedict/.
+    // Loaded after words.tsv so that bundled readings keep priority.
+    let cedict_files = if chars_only {
+        Vec::new()
+    } else {
+        read_cedict_files()
+    };
+    for text in cedict_files {
+        for entry in text.lines().filter_map(cedict::parse_line) {
+            trie.insert_entry(entry.traditional, entry.jyutping);
+            if entry.simplified != entry.traditional {
+                trie.insert_entry(entry.simplified, entry.jyutping);
+            }
+        }
+    }
+
+    // Unihan kCantonese is the lowest-priority tier: it only backfills
+    // characters that chars.tsv has no reading for at all.
+    if let Ok(unihan) = std::fs::read_to_string(UNIHAN_PATH) {
+        for (ch, readings) in unihan.lines().filter_map(unihan::parse_cantonese_line) {
+            if trie.has_char_reading(ch) {
+                continue;
+            }
+            for reading in readings {
+                trie.insert_char(ch, reading, 0);
             }
         }
     }
 
-    for line in WORD_DATA.lines() {
-        let Some((left, right)) = line.split_once('\t') else {
+    // Phonetic components and the reading of their series (睘 waan4, for
+    // 環 and 還), for guessing the readings of unknown characters.
+    for (i, line) in PHONETICS_DATA.lines().enumerate() {
+        let Some(parts) = diagnostics.fields("phonetics.tsv", i + 1, line, 2, 2) else {
             continue;
         };
-        trie.insert_word(left, right);
+        let mut chars = parts[0].chars();
+        let (Some(component), None) = (chars.next(), chars.next()) else {
+            let message = format!("not a single character: {:?}", parts[0]);
+            diagnostics.error("phonetics.tsv", i + 1, message);
+            continue;
+        };
+        if diagnostics.reading("phonetics.tsv", i + 1, parts[1]) {
+            trie.phonetics.push((component, parts[1].to_string()));
+        }
+    }
+
+    // Radicals and stroke counts, kept only for characters with a reading
+    // so that the table stays small.
+    if let Ok(unihan) = std::fs::read_to_string(UNIHAN_IRG_PATH) {
+        let mut table = HashMap::new();
+        unihan::load(&mut table, &unihan);
+        let mut table: Vec<_> = table
+            .into_iter()
+            .filter(|&(ch, _)| trie.has_char_reading(ch))
+            .collect();
+        table.sort_unstable();
+        trie.radical_strokes = table;
+    }
+    if let Ok(text) = std::fs::read_to_string(IDS_PATH) {
+        let mut table = HashMap::new();
+        ids::load(&mut table, &text);
+        // characters without a reading keep theirs for guess_readings
+        ids::retain_useful(
+            &mut table,
+            |ch| trie.has_char_reading(ch),
+            |ch| trie.phonetics.iter().any(|&(c, _)| c == ch),
+        );
+        let mut table: Vec<_> = table.into_iter().collect();
+        table.sort_unstable();
+        trie.ids = table;
     }
 
-    for line in FREQ_DATA.lines() {
-        let parts: Vec<&str> = line.split('\t').collect();
-        if parts.len() >= 2 {
-            if let Ok(freq) = parts[1].parse::<i64>() {
+    // Frequencies only break ties between segmentations, which never differ
+    // in chars-only builds (every token is one character).
+    if !chars_only {
+        for (i, line) in FREQ_DATA.lines().enumerate() {
+            let Some(parts) = diagnostics.fields("freq.txt", i + 1, line, 2, 2) else {
+                continue;
+            };
+            let Ok(freq) = parts[1].parse::<i64>() else {
+                diagnostics.error(
+                    "freq.txt",
+                    i + 1,
+                    format!("not a whole number: {:?}", parts[1]),
+                );
+                continue;
+            };
+            if !small_freq || freq >= SMALL_FREQ_MIN {
                 trie.insert_freq(parts[0], freq);
             }
         }
     }
 
-    for line in LETTERED_DATA.lines() {
-        let Some((left, right)) = line.split_once('\t') else {
-            continue;
-        };
-        trie.insert_lettered(left, right);
+    if !no_lettered {
+        for (i, line) in LETTERED_DATA.lines().enumerate() {
+            let Some(parts) = diagnostics.fields("lettered.tsv", i + 1, line, 2, 3) else {
+                continue;
+            };
+            if !diagnostics.reading("lettered.tsv", i + 1, parts[1]) {
+                continue;
+            }
+            if let Ok(weight) = totals.parse(diagnostics, "lettered.tsv", i + 1, &parts) {
+                trie.insert_lettered(parts[0], parts[1], weight);
+            }
+        }
     }
 
     trie
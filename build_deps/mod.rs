@@ -1,14 +1,33 @@
 pub mod build_trie;
+#[path = "../src/cedict.rs"]
+#[allow(dead_code)] // glosses are only used at runtime
+pub mod cedict;
+#[path = "../src/ids.rs"]
+pub mod ids;
+#[path = "../src/inventory.rs"]
+#[allow(dead_code)] // syllabification is only used at runtime
+pub mod inventory;
 pub mod trie;
+#[path = "../src/unihan.rs"]
+pub mod unihan;
+pub mod validate;
 
 pub fn build_trie_data() -> Result<(), Box<dyn std::error::Error>> {
-    let trie = build_trie::build_trie();
+    let mut diagnostics = validate::Diagnostics::default();
+    let trie = build_trie::build_trie(&mut diagnostics);
+
+    let errors: Vec<String> = diagnostics.errors().map(|p| p.to_string()).collect();
+    if !errors.is_empty() {
+        return Err(format!("invalid data:\n{}", errors.join("\n")).into());
+    }
+
     let bytes = postcard::to_stdvec(&trie)?;
     let compressed = zstd::encode_all(bytes.as_slice(), 20)?;
 
     let out_dir = std::env::var("OUT_DIR")?;
-    let dest_path = std::path::Path::new(&out_dir).join("trie.dat");
+    let out_dir = std::path::Path::new(&out_dir);
 
-    std::fs::write(dest_path, compressed)?;
+    std::fs::write(out_dir.join("trie.dat"), compressed)?;
+    std::fs::write(out_dir.join("diagnostics.tsv"), diagnostics.to_tsv())?;
     Ok(())
 }
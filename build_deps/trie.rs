@@ -1,12 +1,16 @@
-use std::collections::HashMap;
 use serde::Serialize;
+use serde::ser::{SerializeStruct, Serializer};
+use std::collections::HashMap;
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct TrieNode {
-    pub children: HashMap<char, TrieNode>,
+    /// Child node indices in `Trie::nodes`, by char.
+    pub children: HashMap<char, u32>,
     pub readings: Vec<String>,
-    #[serde(skip_serializing)]
-    pub char_weights: Vec<u32>, // parallel to readings, for sorting by weight
+    pub weights: Vec<u32>, // parallel to readings, for sorting by weight
+    /// Whether `weights` are written to trie.dat: always for characters,
+    /// for words only if a data line gives one a weight.
+    pub weighted: bool,
     pub freq: i64,
 }
 
@@ -15,84 +19,220 @@ impl TrieNode {
         TrieNode {
             children: HashMap::new(),
             readings: Vec::new(),
-            char_weights: Vec::new(),
+            weights: Vec::new(),
+            weighted: false,
             freq: 0,
         }
     }
 }
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Debug, Clone)]
 pub struct Trie {
-    pub root: TrieNode,
+    /// Every node, in one arena: `nodes[0]` is the root, and children
+    /// link by index.
+    pub nodes: Vec<TrieNode>,
+    /// Length in chars of the longest entry under each first character,
+    /// which bounds how far the segmenter walks from a start position.
+    pub max_len: HashMap<char, usize>,
+    /// Entries from the names tier (names.tsv), in insertion order.
+    pub names: Vec<String>,
+    /// Classifiers of nouns (classifiers.tsv), in file order.
+    pub classifiers: Vec<(String, Vec<String>)>,
+    /// Radical and stroke count of characters (Unihan), by char.
+    pub radical_strokes: Vec<(char, (u8, u8))>,
+    /// Ideographic description sequences of characters, by char.
+    pub ids: Vec<(char, String)>,
+    /// Series readings of phonetic components (phonetics.tsv), by char.
+    pub phonetics: Vec<(char, String)>,
 }
 
 impl Trie {
     pub fn new() -> Self {
         Trie {
-            root: TrieNode::new(),
+            nodes: vec![TrieNode::new()],
+            max_len: HashMap::new(),
+            names: Vec::new(),
+            classifiers: Vec::new(),
+            radical_strokes: Vec::new(),
+            ids: Vec::new(),
+            phonetics: Vec::new(),
         }
     }
 
-    /// Insert a single CJK character with a weighted reading.
-    /// Higher weight = more common pronunciation = inserted earlier in readings[].
-    /// Entries with no percentage in chars.tsv get weight=100 (highest priority).
-    pub fn insert_char(&mut self, ch: char, reading: &str, weight: u32) {
-        let node = self.root.children.entry(ch).or_insert_with(TrieNode::new);
+    /// Node for `word`, created (with its prefixes) if missing.
+    fn node_mut(&mut self, word: &str) -> &mut TrieNode {
+        let mut id = 0;
+        for ch in word.chars() {
+            id = match self.nodes[id].children.get(&ch) {
+                Some(&child) => child as usize,
+                None => {
+                    let child = self.nodes.len();
+                    self.nodes.push(TrieNode::new());
+                    self.nodes[id].children.insert(ch, child as u32);
+                    child
+                }
+            };
+        }
+        &mut self.nodes[id]
+    }
+
+    /// Index of the node for `word`, if it is in the trie.
+    fn find(&self, word: &str) -> Option<usize> {
+        let mut id = 0;
+        for ch in word.chars() {
+            id = *self.nodes[id].children.get(&ch)? as usize;
+        }
+        Some(id)
+    }
+
+    /// Record `word` in `max_len`.
+    fn note_len(&mut self, word: &str) {
+        if let Some(first) = word.chars().next() {
+            let len = self.max_len.entry(first).or_insert(0);
+            *len = (*len).max(word.chars().count());
+        }
+    }
+
+    /// Add a reading to `word`'s node in its place by weight: higher weight
+    /// = more common pronunciation = earlier in readings[], and equal
+    /// weights in code point order of the reading. The order depends only on
+    /// the entries, not on the order of the lines in the data files.
+    fn insert_weighted(&mut self, word: &str, reading: &str, weight: u32) -> &mut TrieNode {
+        self.note_len(word);
+        let node = self.node_mut(word);
         let r = reading.to_string();
         if !node.readings.contains(&r) {
             let pos = node
-                .char_weights
+                .weights
                 .iter()
-                .position(|&w| w < weight)
+                .zip(&node.readings)
+                .position(|(&w, other)| w < weight || (w == weight && *other > r))
                 .unwrap_or(node.readings.len());
             node.readings.insert(pos, r);
-            node.char_weights.insert(pos, weight);
+            node.weights.insert(pos, weight);
         }
+        node
+    }
+
+    /// Insert a single CJK character with a weighted reading.
+    /// Entries with no percentage in chars.tsv get weight=100 (highest priority).
+    pub fn insert_char(&mut self, ch: char, reading: &str, weight: u32) {
+        self.insert_weighted(&ch.to_string(), reading, weight)
+            .weighted = true;
+    }
+
+    /// True if `ch` already carries at least one reading.
+    pub fn has_char_reading(&self, ch: char) -> bool {
+        self.find(&ch.to_string())
+            .is_some_and(|id| !self.nodes[id].readings.is_empty())
     }
 
-    /// Insert a multi-character CJK word (words.tsv).
+    /// Insert a multi-character CJK word (words.tsv), ranked by weight like
+    /// a character; a reading without one gets weight=100.
     /// Skips single-character entries — use insert_char for those.
-    pub fn insert_word(&mut self, word: &str, reading: &str) {
+    pub fn insert_word(&mut self, word: &str, reading: &str, weight: Option<u32>) {
         if word.chars().count() < 2 {
             return;
         }
-        let mut node = &mut self.root;
-        for ch in word.chars() {
-            node = node.children.entry(ch).or_insert_with(TrieNode::new);
-        }
-        let r = reading.to_string();
-        if !node.readings.contains(&r) {
-            node.readings.push(r);
-        }
+        self.insert_lettered(word, reading, weight);
     }
 
     /// Insert a word frequency for use as a DP tiebreaker.
     /// Only updates nodes already in the trie (from insert_char/insert_word).
     pub fn insert_freq(&mut self, word: &str, freq: i64) {
-        let mut node = &mut self.root;
-        for ch in word.chars() {
-            match node.children.get_mut(&ch) {
-                None => return,
-                Some(child) => node = child,
+        if let Some(id) = self.find(word) {
+            self.nodes[id].freq = freq;
+        }
+    }
+
+    /// Insert an entry from an external dictionary (e.g. CEDICT), with the
+    /// lowest weight so that it ranks after every bundled reading. Single
+    /// characters go through insert_char.
+    pub fn insert_entry(&mut self, word: &str, reading: &str) {
+        let mut chars = word.chars();
+        match (chars.next(), chars.next()) {
+            (Some(ch), None) => self.insert_char(ch, reading, 0),
+            _ if !word.is_empty() => {
+                self.insert_weighted(word, reading, 0);
             }
+            _ => {}
         }
-        node.freq = freq;
     }
 
-    /// Insert an entry from the lettered dict (lettered.tsv).
-    /// Unlike insert_word, allows single-character entries (%, D, K, ...)
-    /// and mixed Latin+CJK entries (AB膠, chok-cheat, Hap唔Happy呀).
-    pub fn insert_lettered(&mut self, word: &str, reading: &str) {
+    /// Insert an entry from the lettered dict (lettered.tsv), weighted like
+    /// insert_word. Unlike insert_word, allows single-character entries
+    /// (%, D, K, ...) and mixed Latin+CJK entries (AB膠, chok-cheat,
+    /// Hap唔Happy呀).
+    pub fn insert_lettered(&mut self, word: &str, reading: &str, weight: Option<u32>) {
         if word.is_empty() {
             return;
         }
-        let mut node = &mut self.root;
-        for ch in word.chars() {
-            node = node.children.entry(ch).or_insert_with(TrieNode::new);
+        let node = self.insert_weighted(word, reading, weight.unwrap_or(100));
+        node.weighted |= weight.is_some();
+    }
+
+    /// Insert a proper noun from the names tier (names.tsv) like a word,
+    /// and record it as a name.
+    pub fn insert_name(&mut self, word: &str, reading: &str) {
+        if word.is_empty() {
+            return;
         }
-        let r = reading.to_string();
-        if !node.readings.contains(&r) {
-            node.readings.push(r);
+        self.insert_lettered(word, reading, None);
+        if !self.names.iter().any(|name| name == word) {
+            self.names.push(word.to_string());
+        }
+    }
+}
+
+/// A node as written to trie.dat: its children by char only (see
+/// `Trie::serialize`).
+#[derive(Serialize)]
+struct StoredNode<'a> {
+    children: Vec<char>,
+    readings: &'a [String],
+    /// `weights` if the node is `weighted`; they never exceed 100.
+    weights: Vec<u8>,
+    freq: i64,
+}
+
+/// The nodes are written breadth first, each node's children in char order,
+/// so the children of a node are the nodes right after those of the nodes
+/// before it. The links then need no indices, and trie.dat compresses
+/// better than with them.
+impl Serialize for Trie {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut order = vec![0];
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        let mut i = 0;
+        while let Some(&id) = order.get(i) {
+            let node = &self.nodes[id];
+            let mut children: Vec<(char, u32)> = node
+                .children
+                .iter()
+                .map(|(&ch, &child)| (ch, child))
+                .collect();
+            children.sort_unstable();
+            order.extend(children.iter().map(|&(_, child)| child as usize));
+            nodes.push(StoredNode {
+                children: children.into_iter().map(|(ch, _)| ch).collect(),
+                readings: &node.readings,
+                weights: if node.weighted {
+                    node.weights.iter().map(|&w| w as u8).collect()
+                } else {
+                    Vec::new()
+                },
+                freq: node.freq,
+            });
+            i += 1;
         }
+        let mut state = serializer.serialize_struct("Trie", 7)?;
+        state.serialize_field("nodes", &nodes)?;
+        state.serialize_field("max_len", &self.max_len)?;
+        state.serialize_field("names", &self.names)?;
+        state.serialize_field("classifiers", &self.classifiers)?;
+        state.serialize_field("radical_strokes", &self.radical_strokes)?;
+        state.serialize_field("ids", &self.ids)?;
+        state.serialize_field("phonetics", &self.phonetics)?;
+        state.end()
     }
 }
@@ -0,0 +1,161 @@
+use std::fmt;
+
+use super::inventory::{SyllableError, syllable_error};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    /// The line cannot be read as intended; the build fails.
+    Error,
+    /// The line is used, but may be a mistake, e.g. a syllable outside the
+    /// LSHK inventory (colloquial "pet6", English "feel" in lettered.tsv).
+    Warning,
+}
+
+/// One problem with one line of a data file.
+#[derive(Debug, Clone)]
+pub struct Problem {
+    pub file: &'static str,
+    /// 1-based.
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Severity {
+    fn name(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+/// e.g. `data/chars.tsv:12: error: weight "5" is not a percentage like "5%"`
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "data/{}:{}: {}: {}",
+            self.file,
+            self.line,
+            self.severity.name(),
+            self.message
+        )
+    }
+}
+
+/// Problems found while reading the data files.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    pub problems: Vec<Problem>,
+}
+
+impl Diagnostics {
+    pub fn error(&mut self, file: &'static str, line: usize, message: String) {
+        self.problems.push(Problem {
+            file,
+            line,
+            severity: Severity::Error,
+            message,
+        });
+    }
+
+    pub fn warning(&mut self, file: &'static str, line: usize, message: String) {
+        self.problems.push(Problem {
+            file,
+            line,
+            severity: Severity::Warning,
+            message,
+        });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Problem> {
+        self.problems
+            .iter()
+            .filter(|p| p.severity == Severity::Error)
+    }
+
+    /// Split a `key<TAB>reading[<TAB>...]` line into at least `min` and at
+    /// most `max` fields, recording an error otherwise.
+    pub fn fields<'a>(
+        &mut self,
+        file: &'static str,
+        line: usize,
+        text: &'a str,
+        min: usize,
+        max: usize,
+    ) -> Option<Vec<&'a str>> {
+        let fields: Vec<&str> = text.split('\t').collect();
+        if (min..=max).contains(&fields.len()) {
+            return Some(fields);
+        }
+        let expected = if min == max {
+            format!("{}", min)
+        } else {
+            format!("{} to {}", min, max)
+        };
+        self.error(
+            file,
+            line,
+            format!(
+                "expected {} tab-separated fields, found {}: {:?}",
+                expected,
+                fields.len(),
+                text
+            ),
+        );
+        None
+    }
+
+    /// Check a space-separated Jyutping reading: an empty one is an error,
+    /// syllables without a tone or that are not legal (see
+    /// `syllable_error`) are warnings. Returns false for an empty reading.
+    pub fn reading(&mut self, file: &'static str, line: usize, reading: &str) -> bool {
+        if reading.trim().is_empty() {
+            self.error(file, line, "empty reading".to_string());
+            return false;
+        }
+        for syllable in reading.split(' ') {
+            let toned = syllable.ends_with(|c| ('1'..='6').contains(&c));
+            let message = match syllable_error(syllable) {
+                None if toned => continue,
+                None | Some(SyllableError::Spelling) => "not a toned Jyutping syllable",
+                Some(error) => error.message(),
+            };
+            self.warning(file, line, format!("{}: {:?}", message, syllable));
+        }
+        true
+    }
+
+    /// Parse a chars.tsv weight such as "5%", recording an error (and
+    /// returning None) for anything else.
+    pub fn weight(&mut self, file: &'static str, line: usize, weight: &str) -> Option<u32> {
+        let parsed = weight
+            .trim()
+            .strip_suffix('%')
+            .and_then(|n| n.parse::<u32>().ok())
+            .filter(|&n| n <= 100);
+        if parsed.is_none() {
+            self.error(
+                file,
+                line,
+                format!("weight {:?} is not a percentage like \"5%\"", weight),
+            );
+        }
+        parsed
+    }
+
+    /// Tab-separated `file, line, severity, message` lines, for
+    /// `data_diagnostics` in lib.rs.
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::new();
+        for p in &self.problems {
+            let severity = p.severity.name();
+            out.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                p.file, p.line, severity, p.message
+            ));
+        }
+        out
+    }
+}
@@ -0,0 +1,201 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::token::Token;
+use crate::utils::{is_alpha_char, is_cjk};
+
+/// A CJK character or alpha run that received no reading.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Unknown {
+    pub text: String,
+    /// "cjk" or "alpha"
+    pub kind: &'static str,
+    pub count: usize,
+    /// Character offsets of every occurrence.
+    pub positions: Vec<usize>,
+}
+
+/// Collect the unread CJK characters and alpha runs in `tokens`, most
+/// frequent first (ties in order of first appearance). Whitespace,
+/// punctuation and symbols without a reading are not reported.
+pub fn coverage(tokens: &[Token]) -> Vec<Unknown> {
+    let mut unknowns: Vec<Unknown> = Vec::new();
+    let mut offset = 0;
+    for token in tokens {
+        let start = offset;
+        offset += token.word.chars().count();
+        if token.reading.is_some() || token.category.is_some() {
+            continue;
+        }
+        let kind = match token.word.chars().next() {
+            Some(c) if is_cjk(c) => "cjk",
+            Some(c) if is_alpha_char(c) => "alpha",
+            _ => continue,
+        };
+        match unknowns.iter_mut().find(|u| u.text == token.word) {
+            Some(u) => {
+                u.count += 1;
+                u.positions.push(start);
+            }
+            None => unknowns.push(Unknown {
+                text: token.word.clone(),
+                kind,
+                count: 1,
+                positions: vec![start],
+            }),
+        }
+    }
+    // stable sort keeps first-appearance order among equal counts
+    unknowns.sort_by_key(|u| std::cmp::Reverse(u.count));
+    unknowns
+}
+
+/// One distinct word in a text, for vocabulary lists.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct VocabEntry {
+    pub word: String,
+    pub jyutping: String,
+    pub yale: Option<Vec<String>>,
+    /// Corpus frequency from freq.txt (0 if unlisted).
+    pub freq: i64,
+    /// Occurrences in the text.
+    pub count: usize,
+}
+
+/// The distinct words in `tokens` that carry a reading, sorted by in-text
+/// count, then by corpus frequency (both descending), then by first
+/// appearance. A word read differently in different places is listed once
+/// per reading.
+pub fn vocabulary(tokens: &[Token], freq: impl Fn(&str) -> i64) -> Vec<VocabEntry> {
+    let mut entries: Vec<VocabEntry> = Vec::new();
+    for token in tokens {
+        let Some(reading) = &token.reading else {
+            continue;
+        };
+        match entries
+            .iter_mut()
+            .find(|e| e.word == token.word && &e.jyutping == reading)
+        {
+            Some(e) => e.count += 1,
+            None => entries.push(VocabEntry {
+                word: token.word.clone(),
+                jyutping: reading.clone(),
+                yale: token.yale.clone(),
+                freq: freq(&token.word),
+                count: 1,
+            }),
+        }
+    }
+    entries.sort_by_key(|e| (std::cmp::Reverse(e.count), std::cmp::Reverse(e.freq)));
+    entries
+}
+
+/// Corpus frequencies counted from `tokens`, in the form of freq.txt: each
+/// word with a reading and its number of occurrences, most frequent first,
+/// then by word.
+pub fn frequencies(tokens: &[Token]) -> Vec<(String, i64)> {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for token in tokens.iter().filter(|t| t.reading.is_some()) {
+        *counts.entry(&token.word).or_default() += 1;
+    }
+    let mut table: Vec<(String, i64)> = counts
+        .into_iter()
+        .map(|(word, count)| (word.to_string(), count))
+        .collect();
+    table.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    table
+}
+
+/// Parse a frequency table (`word<TAB>count` lines, as in freq.txt);
+/// malformed lines are skipped.
+pub fn parse_frequencies(text: &str) -> Vec<(String, i64)> {
+    text.lines()
+        .filter_map(|line| {
+            let (word, count) = line.split_once('\t')?;
+            Some((word.to_string(), count.trim().parse().ok()?))
+        })
+        .filter(|(word, _)| !word.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(word: &str, reading: Option<&str>) -> Token {
+        Token {
+            word: word.to_string(),
+            reading: reading.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_coverage() {
+        let tokens = [
+            token("abc", None),
+            token(" ", None),
+            token("𪛖", None),
+            token("好", Some("hou2")),
+            token("𪛖", None),
+            token("！", None),
+        ];
+        assert_eq!(
+            coverage(&tokens),
+            [
+                Unknown {
+                    text: "𪛖".into(),
+                    kind: "cjk",
+                    count: 2,
+                    positions: vec![4, 6],
+                },
+                Unknown {
+                    text: "abc".into(),
+                    kind: "alpha",
+                    count: 1,
+                    positions: vec![0],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let tokens = [
+            token("學生", Some("hok6 saang1")),
+            token("佢", Some("keoi5")),
+            token("，", None),
+            token("好", Some("hou2")),
+            token("學生", Some("hok6 saang1")),
+        ];
+        let freq = |w: &str| if w == "好" { 500 } else { 100 };
+        let vocab: Vec<_> = vocabulary(&tokens, freq)
+            .into_iter()
+            .map(|e| (e.word, e.freq, e.count))
+            .collect();
+        assert_eq!(
+            vocab,
+            [
+                ("學生".to_string(), 100, 2),
+                ("好".to_string(), 500, 1),
+                ("佢".to_string(), 100, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_frequencies() {
+        let tokens = [
+            token("佢", Some("keoi5")),
+            token("學生", Some("hok6 saang1")),
+            token("。", None),
+            token("學生", Some("hok6 saang1")),
+            token("好", Some("hou2")),
+        ];
+        let table = frequencies(&tokens);
+        let expected = [("學生", 2), ("佢", 1), ("好", 1)].map(|(w, c)| (w.to_string(), c));
+        assert_eq!(table, expected);
+        let tsv = "學生\t2\n佢\t1\nbad line\n好\tx\n";
+        assert_eq!(parse_frequencies(tsv), expected[..2]);
+    }
+}
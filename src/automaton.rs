@@ -0,0 +1,63 @@
+use aho_corasick::{AhoCorasick, MatchKind};
+
+use crate::trie::{Trie, TrieNode};
+use crate::utils::fold_ascii;
+use crate::variants;
+
+/// The form of `ch` that entries and text are matched in: folded to ASCII
+/// (see `fold_ascii`), and the canonical form of a variant character.
+fn fold(ch: char) -> char {
+    variants::canonical(fold_ascii(ch))
+}
+
+/// Aho-Corasick automaton over every dictionary entry, for finding all
+/// entries in a text in one pass (the `aho-corasick` feature). Entries and
+/// text are both folded (see `fold`), so a hit is only a candidate: the
+/// trie walk decides whether it matches, as without the feature (see
+/// `Trie::scan`).
+pub struct Automaton {
+    ac: AhoCorasick,
+}
+
+impl Automaton {
+    /// Build over the entries in `trie`; None if the automaton would be
+    /// too large.
+    pub fn new(trie: &Trie) -> Option<Self> {
+        fn collect(trie: &Trie, node: &TrieNode, word: &mut String, words: &mut Vec<String>) {
+            if !node.readings.is_empty() {
+                words.push(word.clone());
+            }
+            for (ch, child) in trie.children(node) {
+                word.push(fold(ch));
+                collect(trie, child, word, words);
+                word.pop();
+            }
+        }
+        let mut words = Vec::new();
+        collect(trie, trie.root(), &mut String::new(), &mut words);
+        words.sort_unstable();
+        words.dedup();
+        let ac = AhoCorasick::builder()
+            .match_kind(MatchKind::Standard)
+            .build(&words)
+            .ok()?;
+        Some(Automaton { ac })
+    }
+
+    /// Every `(start, end)` char span of `chars` that may be an entry.
+    pub fn find(&self, chars: &[char]) -> Vec<(usize, usize)> {
+        let mut text = String::with_capacity(chars.len() * 3);
+        // char index of each byte offset that starts a char, and the end
+        let mut index = Vec::with_capacity(chars.len() * 3 + 1);
+        for (i, &ch) in chars.iter().enumerate() {
+            let ch = fold(ch);
+            index.extend(std::iter::repeat_n(i, ch.len_utf8()));
+            text.push(ch);
+        }
+        index.push(chars.len());
+        self.ac
+            .find_overlapping_iter(&text)
+            .map(|m| (index[m.start()], index[m.end()]))
+            .collect()
+    }
+}
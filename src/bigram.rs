@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+/// How often each word follows another, for `Scoring::Bigram`.
+#[derive(Default)]
+pub struct Bigrams {
+    followers: HashMap<String, Followers>,
+}
+
+/// The words seen after one word, with their counts.
+#[derive(Default)]
+struct Followers {
+    counts: HashMap<String, i64>,
+    total: i64,
+}
+
+impl Bigrams {
+    /// Read `word1<TAB>word2<TAB>count` lines; malformed lines and counts
+    /// below 1 are skipped, and repeated pairs add up.
+    pub fn parse(text: &str) -> Self {
+        let mut bigrams = Bigrams::default();
+        for line in text.lines() {
+            let mut fields = line.split('\t');
+            let (Some(first), Some(second), Some(count)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let Ok(count) = count.trim().parse::<i64>() else {
+                continue;
+            };
+            if first.is_empty() || second.is_empty() || count < 1 {
+                continue;
+            }
+            let followers = bigrams.followers.entry(first.to_string()).or_default();
+            *followers.counts.entry(second.to_string()).or_default() += count;
+            followers.total += count;
+        }
+        bigrams
+    }
+
+    /// Number of distinct word pairs.
+    pub fn len(&self) -> usize {
+        self.followers.values().map(|f| f.counts.len()).sum()
+    }
+
+    /// ln P(`word` | `prev`), interpolating the pair count with `unigram`,
+    /// the word's own probability, by Witten-Bell smoothing:
+    /// (count + types × unigram) / (total + types), where total and types
+    /// are the count and number of distinct words seen after `prev`. A word
+    /// never seen before anything, or the first word, gets `unigram`.
+    pub fn log_prob(&self, prev: &str, word: &str, unigram: f64) -> f64 {
+        let Some(followers) = self.followers.get(prev) else {
+            return unigram.ln();
+        };
+        let count = followers.counts.get(word).copied().unwrap_or(0) as f64;
+        let types = followers.counts.len() as f64;
+        ((count + types * unigram) / (followers.total as f64 + types)).ln()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_prob() {
+        let bigrams =
+            Bigrams::parse("我哋\t一齊\t3\n我哋\t去\t1\n我哋\t去\t1\nbad line\n一\t齊\t0");
+        assert_eq!(bigrams.len(), 2);
+        // 5 pairs after 我哋, 2 kinds: (3 + 2 × 0.1) / (5 + 2)
+        let p = bigrams.log_prob("我哋", "一齊", 0.1).exp();
+        assert!((p - 3.2 / 7.0).abs() < 1e-12);
+        // unseen pairs keep a share of the unigram probability
+        let p = bigrams.log_prob("我哋", "一", 0.1).exp();
+        assert!((p - 0.2 / 7.0).abs() < 1e-12);
+        assert_eq!(bigrams.log_prob("一", "齊", 0.1), 0.1f64.ln());
+        assert_eq!(bigrams.log_prob("", "我哋", 0.1), 0.1f64.ln());
+    }
+}
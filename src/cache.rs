@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+/// Small least-recently-used cache for annotation results, keyed by the
+/// input fragment. Typst re-evaluates show rules often, so the same short
+/// strings come back again and again.
+///
+/// Entries carry the tick of their last use; eviction scans for the oldest
+/// one. That is O(capacity), but only happens on insert into a full cache,
+/// and capacities are small.
+pub struct LruCache<V> {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (u64, V)>,
+}
+
+impl<V: Clone> LruCache<V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, key: &str) -> Option<V> {
+        self.tick += 1;
+        let (used, value) = self.entries.get_mut(key)?;
+        *used = self.tick;
+        Some(value.clone())
+    }
+
+    pub fn insert(&mut self, key: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (used, _))| *used)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, (self.tick, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_eviction() {
+        let mut cache = LruCache::new(2);
+        cache.insert("a".into(), 1);
+        cache.insert("b".into(), 2);
+        assert_eq!(cache.get("a"), Some(1)); // "b" is now least recently used
+        cache.insert("c".into(), 3);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("c"), Some(3));
+
+        cache.clear();
+        assert_eq!(cache.get("a"), None);
+
+        let mut disabled = LruCache::new(0);
+        disabled.insert("a".into(), 1);
+        assert_eq!(disabled.get("a"), None);
+    }
+}
@@ -0,0 +1,59 @@
+use serde::Serialize;
+
+use crate::options::Options;
+use crate::romanize::ROMANIZERS;
+
+/// Version of the JSON output shapes. It goes up when a field is renamed or
+/// removed, or changes type, in any output; new fields leave it unchanged.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A dictionary added with `load_dictionary`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct LoadedDictionary {
+    /// "tsv", "cedict", "emoji", "names", "unihan" or "ids".
+    pub format: String,
+    pub entries: usize,
+}
+
+/// What this build of the plugin offers, for `get_capabilities`.
+#[derive(Serialize, Debug)]
+pub struct Capabilities {
+    pub schema_version: u32,
+    /// The crate version, e.g. "0.3.4".
+    pub version: &'static str,
+    /// Cargo features built in, e.g. "chars-only".
+    pub features: Vec<&'static str>,
+    /// Names accepted by the `romanization` option and `romanize`.
+    pub romanizers: Vec<&'static str>,
+    /// Dictionaries added with `load_dictionary`, in load order.
+    pub dictionaries: Vec<LoadedDictionary>,
+    /// Number of word pairs loaded with `load_bigrams`.
+    pub bigrams: usize,
+    /// Every option `annotate_with` takes, with its default value.
+    pub options: Options,
+}
+
+impl Capabilities {
+    pub fn new(dictionaries: Vec<LoadedDictionary>, bigrams: usize) -> Self {
+        let features = [
+            ("no-lettered", cfg!(feature = "no-lettered")),
+            ("chars-only", cfg!(feature = "chars-only")),
+            ("small-freq", cfg!(feature = "small-freq")),
+            ("aho-corasick", cfg!(feature = "aho-corasick")),
+            ("parallel", cfg!(feature = "parallel")),
+        ];
+        Capabilities {
+            schema_version: SCHEMA_VERSION,
+            version: env!("CARGO_PKG_VERSION"),
+            features: features
+                .into_iter()
+                .filter(|&(_, on)| on)
+                .map(|(name, _)| name)
+                .collect(),
+            romanizers: ROMANIZERS.iter().map(|r| r.name()).collect(),
+            dictionaries,
+            bigrams,
+            options: Options::default(),
+        }
+    }
+}
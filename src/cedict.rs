@@ -0,0 +1,82 @@
+/// One entry in CC-CEDICT line format, as used by CC-Canto and the
+/// CC-CEDICT Cantonese readings file:
+///
+/// `traditional simplified [pinyin] {jyutping} /gloss/`
+///
+/// Entries without a `{jyutping}` block carry no Cantonese reading and are
+/// skipped by `parse_line`.
+pub struct CedictEntry<'a> {
+    pub traditional: &'a str,
+    pub simplified: &'a str,
+    pub jyutping: &'a str,
+    /// The `/gloss/` definitions, if any, still slash-separated.
+    pub gloss: Option<&'a str>,
+}
+
+/// Parse a single CEDICT line. Comments (`#`), blank lines and lines with no
+/// `{jyutping}` block right after the `[pinyin]` return None.
+///
+/// e.g. "學生 学生 [xue2 sheng5] {hok6 saang1} /student/"
+///      → traditional="學生", simplified="学生", jyutping="hok6 saang1"
+pub fn parse_line(line: &str) -> Option<CedictEntry<'_>> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let mut heads = line.split_whitespace();
+    let traditional = heads.next()?;
+    let simplified = heads.next()?;
+
+    // the Jyutping block follows the pinyin; braces in the gloss are text
+    let rest = line[line.find(']')? + 1..].trim_start();
+    let rest = rest.strip_prefix('{')?;
+    let close = rest.find('}')?;
+    let jyutping = rest[..close].trim();
+    if jyutping.is_empty() {
+        return None;
+    }
+    let gloss = rest[close + 1..].trim().trim_matches('/').trim();
+
+    Some(CedictEntry {
+        traditional,
+        simplified,
+        jyutping,
+        gloss: (!gloss.is_empty()).then_some(gloss),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        let e = parse_line("學生 学生 [xue2 sheng5] {hok6 saang1} /student/").unwrap();
+        assert_eq!(e.traditional, "學生");
+        assert_eq!(e.simplified, "学生");
+        assert_eq!(e.jyutping, "hok6 saang1");
+        assert_eq!(e.gloss, Some("student"));
+
+        // no gloss (CC-CEDICT Cantonese readings file)
+        let e = parse_line("一 一 [yi1] {jat1}").unwrap();
+        assert_eq!(e.traditional, "一");
+        assert_eq!(e.jyutping, "jat1");
+        assert_eq!(e.gloss, None);
+
+        let e = parse_line("好 好 [hao3] {hou3} /to be fond of/to have a tendency to/").unwrap();
+        assert_eq!(e.gloss, Some("to be fond of/to have a tendency to"));
+
+        // comments, blanks and entries without jyutping are skipped
+        assert!(parse_line("# CC-Canto").is_none());
+        assert!(parse_line("   ").is_none());
+        assert!(parse_line("中國 中国 [Zhong1 guo2] /China/").is_none());
+        assert!(parse_line("中國 中国 [Zhong1 guo2] {} /China/").is_none());
+
+        // a brace in the gloss is not the Jyutping block
+        let e = parse_line("括號 括号 [kuo4 hao4] {kut3 hou6} /brackets { }/").unwrap();
+        assert_eq!(e.jyutping, "kut3 hou6");
+        assert_eq!(e.gloss, Some("brackets { }"));
+        assert!(parse_line("括號 括号 [kuo4 hao4] /curly brackets {kut3}/").is_none());
+    }
+}
@@ -0,0 +1,190 @@
+use serde::Serialize;
+use std::sync::LazyLock;
+
+use crate::distance::distance;
+use crate::jyutping::{FINALS, INITIALS, normalize_syllable, syllabify, syllable_error, tone};
+use crate::utils::is_alpha_char;
+
+/// Most suggestions given for one syllable.
+const SUGGESTIONS: usize = 5;
+
+/// Every legal syllable with a tone digit: the suggestion candidates.
+static SYLLABLES: LazyLock<Vec<String>> = LazyLock::new(|| {
+    let mut all = Vec::new();
+    for initial in std::iter::once("").chain(INITIALS) {
+        for fin in FINALS {
+            for tone in 1..=6 {
+                let s = format!("{}{}{}", initial, fin, tone);
+                if syllable_error(&s).is_none() {
+                    all.push(s);
+                }
+            }
+        }
+    }
+    all
+});
+
+/// One syllable of a checked text.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Checked {
+    /// The syllable as typed.
+    pub text: String,
+    /// Byte offsets of the syllable in the input.
+    pub start: usize,
+    pub end: usize,
+    /// Legal Jyutping with a tone digit.
+    pub valid: bool,
+    /// Nearest legal syllables, closest first; left out when `valid`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+/// Check typed Jyutping syllable by syllable. Words are split into
+/// syllables where possible ("nei5hou2" → nei5, hou2); a syllable that is
+/// not legal, or has no tone, is flagged with the nearest legal syllables
+/// by `distance`, a fuzzy-spelling fix (see `normalize`) first. Runs of
+/// digits or punctuation are skipped.
+pub fn check(text: &str) -> Vec<Checked> {
+    let mut out = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, ch)) = chars.next() {
+        if !is_alpha_char(ch) {
+            continue;
+        }
+        let mut end = start + ch.len_utf8();
+        while let Some(&(i, c)) = chars.peek()
+            && is_alpha_char(c)
+        {
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let word = &text[start..end];
+        if !word.chars().any(char::is_alphabetic) {
+            continue;
+        }
+
+        let lower = word.to_ascii_lowercase();
+        let pieces = syllabify(&lower).unwrap_or_else(|| vec![lower.as_str()]);
+        let mut offset = start;
+        for piece in pieces {
+            let valid = tone(piece) > 0 && syllable_error(piece).is_none();
+            out.push(Checked {
+                text: text[offset..offset + piece.len()].to_string(),
+                start: offset,
+                end: offset + piece.len(),
+                valid,
+                suggestions: if valid { Vec::new() } else { suggest(piece) },
+            });
+            offset += piece.len();
+        }
+    }
+    out
+}
+
+/// A syllable `validate` rejects.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Invalid {
+    /// 1-based line number.
+    pub line: usize,
+    pub syllable: String,
+    /// What is wrong with it, e.g. "checked syllable with tone 4 or 5".
+    pub error: &'static str,
+}
+
+/// Validate the Jyutping of a user dictionary before loading it: `text`
+/// has one reading per line, or `word<TAB>reading` lines as for
+/// `load_dictionary`, whose second field is checked. Every syllable must
+/// be lower case, have a tone, and be legal (see `syllable_error`): no
+/// final outside the inventory ("om"), no gw or kw before a front rounded
+/// final ("gweoi"), no checked syllable in tone 4 or 5 ("sik4").
+pub fn validate(text: &str) -> Vec<Invalid> {
+    let mut out = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let reading = match line.split_once('\t') {
+            Some((_, rest)) => rest.split('\t').next().unwrap_or_default(),
+            None => line,
+        };
+        for syllable in reading.split_whitespace() {
+            let error = match syllable_error(syllable) {
+                Some(e) => e.message(),
+                None if tone(syllable) == 0 => "no tone",
+                None => continue,
+            };
+            out.push(Invalid {
+                line: i + 1,
+                syllable: syllable.to_string(),
+                error,
+            });
+        }
+    }
+    out
+}
+
+fn suggest(syllable: &str) -> Vec<String> {
+    let fuzzy = normalize_syllable(syllable).filter(|s| tone(s) > 0);
+    let mut ranked: Vec<(f64, &str)> = SYLLABLES
+        .iter()
+        .filter(|s| Some(*s) != fuzzy.as_ref())
+        .map(|s| (distance(syllable, s), s.as_str()))
+        .collect();
+    ranked.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(b.1)));
+    fuzzy
+        .into_iter()
+        .chain(ranked.into_iter().map(|(_, s)| s.to_string()))
+        .take(SUGGESTIONS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jyutping::is_valid_syllable;
+
+    #[test]
+    fn test_check() {
+        let checked = check("Nei5hou2, ngo5 hai6 hok6saan!");
+        let flagged: Vec<_> = checked.iter().filter(|c| !c.valid).collect();
+        assert_eq!(checked.len(), 6);
+        assert_eq!(checked[0].text, "Nei5");
+        assert_eq!((checked[1].start, checked[1].end), (4, 8));
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].text, "saan");
+        assert_eq!(flagged[0].suggestions[0], "saan1");
+
+        // a fuzzy spelling fix comes first, then the nearest syllables
+        let yuet = &check("yuet6")[0];
+        assert!(!yuet.valid);
+        assert_eq!(yuet.suggestions[0], "jyut6");
+        assert_eq!(yuet.suggestions.len(), SUGGESTIONS);
+
+        assert!(check("2024 ...").is_empty());
+
+        // Yale is flagged whole, not cut inside a diacritic; CJK is skipped
+        let yale = check("néih hóu 你好");
+        assert_eq!(yale.len(), 2);
+        assert_eq!((yale[0].text.as_str(), yale[0].valid), ("néih", false));
+        assert_eq!((yale[1].start, yale[1].end), (6, 10));
+    }
+
+    #[test]
+    fn test_validate() {
+        let invalid = validate("碟\tdip2\n\n人\tjom4\n居\tgweoi1\t5%\nsik4 sik6\n好\thou\n");
+        let found: Vec<_> = invalid
+            .iter()
+            .map(|i| (i.line, i.syllable.as_str(), i.error))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (3, "jom4", "not an initial plus a final"),
+                (4, "gweoi1", "gw or kw before a front rounded final"),
+                (5, "sik4", "checked syllable with tone 4 or 5"),
+                (6, "hou", "no tone"),
+            ]
+        );
+        assert!(!is_valid_syllable("kwyun4"));
+        // spelled right, so still parsed and romanized
+        assert!(is_valid_syllable("sik4"));
+        assert!(is_valid_syllable("gwaa1") && is_valid_syllable("kwong3"));
+    }
+}
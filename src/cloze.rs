@@ -0,0 +1,91 @@
+use crate::token::Token;
+
+/// SplitMix64: a tiny, well-mixed PRNG, so that a given seed picks the same
+/// tokens on every platform and in every build.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `0..n` (n > 0); the modulo bias is negligible here.
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Cloze mode: mark `ratio` (0–1, rounded to the nearest token) of the tokens
+/// that have a reading as `hidden: true` and withhold their readings.
+/// Which tokens are picked depends only on `seed` and the token sequence.
+pub fn hide(tokens: &mut [Token], ratio: f64, seed: u64) {
+    let mut candidates: Vec<usize> = (0..tokens.len())
+        .filter(|&i| tokens[i].reading.is_some())
+        .collect();
+    let count = (candidates.len() as f64 * ratio.clamp(0.0, 1.0)).round() as usize;
+
+    // partial Fisher–Yates: the first `count` slots end up a uniform sample
+    let mut rng = SplitMix64(seed);
+    for i in 0..count {
+        let j = i + rng.below(candidates.len() - i);
+        candidates.swap(i, j);
+    }
+    for &i in &candidates[..count] {
+        let t = &mut tokens[i];
+        t.reading = None;
+        t.yale = None;
+        t.jyutping_syllables = None;
+        t.readings = None;
+        t.hidden = Some(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens() -> Vec<Token> {
+        ["今日", "我", "要", "上堂", "。"]
+            .iter()
+            .map(|w| Token {
+                word: w.to_string(),
+                reading: (*w != "。").then(|| "x".to_string()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn hidden(tokens: &[Token]) -> Vec<&str> {
+        tokens
+            .iter()
+            .filter(|t| t.hidden == Some(true))
+            .map(|t| t.word.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_hide() {
+        let mut a = tokens();
+        hide(&mut a, 0.5, 7);
+        let picked = hidden(&a);
+        assert_eq!(picked.len(), 2);
+        assert!(!picked.contains(&"。"));
+        assert!(a.iter().all(|t| t.hidden.is_none() || t.reading.is_none()));
+
+        // same seed, same picks
+        let mut b = tokens();
+        hide(&mut b, 0.5, 7);
+        assert_eq!(hidden(&b), picked);
+
+        let mut all = tokens();
+        hide(&mut all, 1.0, 0);
+        assert_eq!(hidden(&all), ["今日", "我", "要", "上堂"]);
+        let mut none = tokens();
+        hide(&mut none, 0.0, 0);
+        assert!(hidden(&none).is_empty());
+    }
+}
@@ -0,0 +1,156 @@
+use serde::Serialize;
+use unicode_normalization::UnicodeNormalization;
+
+use crate::jyutping::is_valid_syllable;
+
+/// A romanization system `detect` can recognize.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum System {
+    Jyutping,
+    Yale,
+    CantonesePinyin,
+    SidneyLau,
+}
+
+const SYSTEMS: [System; 4] = [
+    System::Jyutping,
+    System::Yale,
+    System::CantonesePinyin,
+    System::SidneyLau,
+];
+
+/// Result of `detect`: the best guess (None if nothing looked like
+/// romanized Cantonese) and the evidence score of every system.
+#[derive(Serialize, Debug)]
+pub struct Detection {
+    pub system: Option<System>,
+    pub scores: Scores,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct Scores {
+    pub jyutping: u32,
+    pub yale: u32,
+    pub cantonese_pinyin: u32,
+    pub sidney_lau: u32,
+}
+
+impl Scores {
+    fn get(&self, system: System) -> u32 {
+        match system {
+            System::Jyutping => self.jyutping,
+            System::Yale => self.yale,
+            System::CantonesePinyin => self.cantonese_pinyin,
+            System::SidneyLau => self.sidney_lau,
+        }
+    }
+}
+
+/// Score one syllable. Spellings only one system uses weigh most: Yale tone
+/// diacritics, Cantonese Pinyin's entering tones 7–9 and `dz`/`ts`, Sidney
+/// Lau's `°`, `oo` and `ue`, Jyutping's `z`/`c`/`eo`/`oe`.
+fn score(syllable: &str, scores: &mut Scores) {
+    let decomposed: String = syllable.nfd().collect();
+    if decomposed
+        .chars()
+        .any(|c| matches!(c, '\u{0300}' | '\u{0301}' | '\u{0304}'))
+    {
+        scores.yale += 3;
+        return;
+    }
+    if syllable.contains('°') {
+        scores.sidney_lau += 3;
+        return;
+    }
+
+    let body = syllable.trim_end_matches(|c: char| c.is_ascii_digit());
+    let tone = &syllable[body.len()..];
+    if matches!(tone, "7" | "8" | "9") {
+        scores.cantonese_pinyin += 3;
+    }
+
+    if is_valid_syllable(syllable) {
+        scores.jyutping += 2;
+        if body.starts_with(['z', 'c']) || body.contains("eo") || body.contains("oe") {
+            scores.jyutping += 2;
+        }
+    }
+    if body.starts_with("dz") || body.starts_with("ts") {
+        scores.cantonese_pinyin += 3;
+    }
+    // Cantonese Pinyin writes Jyutping yu as y: jy, sy, dzyn
+    if body.chars().nth(1) == Some('y') && !body.starts_with("ng") {
+        scores.cantonese_pinyin += 1;
+    }
+    if body.contains("oey") {
+        scores.cantonese_pinyin += 2;
+    }
+    if body.contains("oo") || body.contains("ue") {
+        scores.sidney_lau += 2;
+    }
+    if body.ends_with("oh") {
+        scores.sidney_lau += 1;
+    }
+    // shared by Yale and Sidney Lau: ch, j and y initials, eu
+    if body.starts_with("ch") || body.starts_with('j') || body.starts_with('y') {
+        scores.yale += 1;
+        scores.sidney_lau += 1;
+    }
+    if body.contains("eu") {
+        scores.yale += 1;
+        scores.sidney_lau += 1;
+    }
+    // Yale marks the low register with h after the vowel
+    let vowel_h = body
+        .as_bytes()
+        .windows(2)
+        .any(|w| b"aeiou".contains(&w[0]) && w[1] == b'h');
+    if vowel_h {
+        scores.yale += 2;
+    }
+}
+
+/// Guess which romanization `text` is written in. Scores are summed over
+/// the whitespace-separated syllables; ties go to the more common system
+/// (Jyutping, then Yale, Cantonese Pinyin, Sidney Lau).
+pub fn detect(text: &str) -> Detection {
+    let mut scores = Scores::default();
+    for syllable in text.split_whitespace() {
+        score(&syllable.to_lowercase(), &mut scores);
+    }
+    let best = SYSTEMS
+        .into_iter()
+        .rev()
+        .max_by_key(|&s| scores.get(s))
+        .filter(|&s| scores.get(s) > 0);
+    Detection {
+        system: best,
+        scores,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let system = |text| detect(text).system;
+        assert_eq!(system("gwong2 dung1 waa2"), Some(System::Jyutping));
+        assert_eq!(system("zoeng1 ceoi4"), Some(System::Jyutping));
+        assert_eq!(system("gwóng dūng wá"), Some(System::Yale));
+        assert_eq!(system("haih yauh"), Some(System::Yale));
+        assert_eq!(
+            system("sik9 faan6 dzung1 tsoey4"),
+            Some(System::CantonesePinyin)
+        );
+        assert_eq!(system("ngoh5 foo1 sue1"), Some(System::SidneyLau));
+        assert_eq!(system("hello world"), None);
+        assert_eq!(system(""), None);
+        // text that is not romanized at all, or is only partly
+        assert_eq!(system("你好"), None);
+        assert_eq!(system("😀"), None);
+        assert_eq!(system("你好 nei5 hou2 😀"), Some(System::Jyutping));
+    }
+}
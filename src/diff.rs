@@ -0,0 +1,116 @@
+use serde::Serialize;
+
+use crate::token::Token;
+
+/// A token as `diff` reports it.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Word {
+    pub word: String,
+    pub jyutping: Option<String>,
+}
+
+/// A stretch of text annotated differently by two runs.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Change {
+    /// Character offsets in the text.
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub before: Vec<Word>,
+    pub after: Vec<Word>,
+}
+
+/// Compare two annotations of the same text. The text is cut wherever both
+/// have a token boundary; each piece whose words or readings differ is one
+/// change, with the tokens of either run that cover it.
+pub fn diff(before: &[Token], after: &[Token]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut start = 0;
+    while i < before.len() || j < after.len() {
+        // take tokens from whichever run is behind until both end together
+        let (first_i, first_j) = (i, j);
+        let (mut end_a, mut end_b) = (start, start);
+        loop {
+            if end_a <= end_b && i < before.len() {
+                end_a += before[i].word.chars().count();
+                i += 1;
+            } else if j < after.len() {
+                end_b += after[j].word.chars().count();
+                j += 1;
+            } else {
+                break;
+            }
+            if end_a == end_b {
+                break;
+            }
+        }
+        let (a, b) = (&before[first_i..i], &after[first_j..j]);
+        let same = a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(x, y)| x.word == y.word && x.reading == y.reading);
+        if !same {
+            changes.push(Change {
+                start,
+                end: end_a.max(end_b),
+                text: a.iter().map(|t| t.word.as_str()).collect(),
+                before: a.iter().map(word).collect(),
+                after: b.iter().map(word).collect(),
+            });
+        }
+        start = end_a.max(end_b);
+    }
+    changes
+}
+
+fn word(token: &Token) -> Word {
+    Word {
+        word: token.word.clone(),
+        jyutping: token.reading.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(spec: &[(&str, Option<&str>)]) -> Vec<Token> {
+        spec.iter()
+            .map(|&(word, reading)| Token {
+                word: word.to_string(),
+                reading: reading.map(str::to_string),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_diff() {
+        let before = tokens(&[
+            ("好", Some("hou2")),
+            ("學生", Some("hok6 saang1")),
+            ("，", None),
+            ("行", Some("haang4")),
+            ("！", None),
+        ]);
+        let after = tokens(&[
+            ("好學", Some("hou3 hok6")),
+            ("生", Some("saang1")),
+            ("，", None),
+            ("行", Some("hang4")),
+            ("！", None),
+        ]);
+        let changes = diff(&before, &after);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].start, changes[0].end), (0, 3));
+        assert_eq!(changes[0].text, "好學生");
+        assert_eq!(changes[0].before.len(), 2);
+        assert_eq!(changes[0].after[0].word, "好學");
+        assert_eq!((changes[1].start, changes[1].end), (4, 5));
+        assert_eq!(changes[1].after[0].jyutping.as_deref(), Some("hang4"));
+
+        assert!(diff(&before, &before).is_empty());
+        assert!(diff(&[], &[]).is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+/// Jyutping letters that spell one phoneme together, matched before single
+/// letters.
+const DIGRAPHS: [&str; 7] = ["ng", "gw", "kw", "aa", "oe", "eo", "yu"];
+
+/// Cost of adding, dropping or changing a tone digit; segments cost 1.
+const TONE_COST: f64 = 0.5;
+
+/// Split a lowercase Jyutping spelling into phoneme units: "gwaang2" →
+/// ["gw", "aa", "ng", "2"]. Tone digits are units of their own.
+fn phonemes(s: &str) -> Vec<&str> {
+    let mut units = Vec::new();
+    let mut rest = s;
+    while let Some(ch) = rest.chars().next() {
+        let len = DIGRAPHS
+            .iter()
+            .find(|d| rest.starts_with(*d))
+            .map_or(ch.len_utf8(), |d| d.len());
+        units.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    units
+}
+
+fn is_tone(unit: &str) -> bool {
+    matches!(unit, "1" | "2" | "3" | "4" | "5" | "6")
+}
+
+fn indel(unit: &str) -> f64 {
+    if is_tone(unit) { TONE_COST } else { 1.0 }
+}
+
+fn substitution(a: &str, b: &str) -> f64 {
+    match (a == b, is_tone(a) && is_tone(b)) {
+        (true, _) => 0.0,
+        (false, true) => TONE_COST,
+        (false, false) => 1.0,
+    }
+}
+
+/// Weighted phoneme edit distance between two Jyutping spellings: each
+/// inserted, deleted or replaced phoneme costs 1, a tone only 0.5. So "si1"
+/// is 0.5 from "si2" and 1 from "sik1"; "gwong" is 1 from "gong".
+pub fn distance(a: &str, b: &str) -> f64 {
+    edit_distance(
+        &phonemes(a),
+        &phonemes(b),
+        |u| indel(u),
+        |x, y| substitution(x, y),
+    )
+}
+
+/// Distance between two space-separated Jyutping readings: an edit
+/// distance over syllables, where replacing a syllable costs its `distance`
+/// and adding or dropping one costs as much as spelling it. "nei5 hou2" is
+/// 0.5 from "nei5 hou3" and 3.5 (h, o, u and a tone) from "nei5".
+pub fn word_distance(a: &str, b: &str) -> f64 {
+    let a: Vec<&str> = a.split_whitespace().collect();
+    let b: Vec<&str> = b.split_whitespace().collect();
+    edit_distance(&a, &b, |s| distance(s, ""), |x, y| distance(x, y))
+}
+
+/// Weighted Levenshtein distance between two sequences.
+fn edit_distance<T>(
+    a: &[T],
+    b: &[T],
+    indel: impl Fn(&T) -> f64,
+    substitution: impl Fn(&T, &T) -> f64,
+) -> f64 {
+    // prev[j] = distance between the a items seen so far and b[..j]
+    let mut prev: Vec<f64> = std::iter::once(0.0)
+        .chain(b.iter().scan(0.0, |acc, u| {
+            *acc += indel(u);
+            Some(*acc)
+        }))
+        .collect();
+    for x in a {
+        let mut row = vec![prev[0] + indel(x)];
+        for (j, y) in b.iter().enumerate() {
+            let cost = (prev[j] + substitution(x, y))
+                .min(prev[j + 1] + indel(x))
+                .min(row[j] + indel(y));
+            row.push(cost);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(phonemes("gwaang2"), ["gw", "aa", "ng", "2"]);
+        assert_eq!(distance("si1", "si1"), 0.0);
+        assert_eq!(distance("si1", "si2"), 0.5);
+        assert_eq!(distance("si", "si2"), 0.5);
+        assert_eq!(distance("si1", "sik1"), 1.0);
+        assert_eq!(distance("gwong2", "gong2"), 1.0);
+        assert_eq!(distance("hou2", "hau3"), 1.5);
+        assert_eq!(distance("", "aa3"), 1.5);
+    }
+
+    #[test]
+    fn test_word_distance() {
+        assert_eq!(word_distance("nei5 hou2", "nei5 hou2"), 0.0);
+        assert_eq!(word_distance("nei5 hou2", "nei5 hou3"), 0.5);
+        assert_eq!(word_distance("nei5 hou2", "nei5"), 3.5);
+        assert_eq!(word_distance("gwong2 dung1", "gong2 dung1 waa2"), 3.5);
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+
+use crate::token::Token;
+
+/// Characters of context re-segmented on each side of an edit. A word that
+/// straddles the edit can only be re-split within reach of the longest
+/// entries in practice, so tokens further away are kept as they are.
+const CONTEXT_CHARS: usize = 8;
+
+/// An edit in character (not byte) offsets into the current document text:
+/// the characters in `start..end` are replaced by `text`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Edit {
+    pub start: usize,
+    pub end: usize,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// What the host has to change in its own token list after an edit:
+/// remove `removed` tokens starting at index `first`, and insert `tokens`
+/// in their place.
+#[derive(Serialize, Debug)]
+pub struct Patch {
+    pub first: usize,
+    pub removed: usize,
+    pub tokens: Vec<Token>,
+}
+
+/// A registered document: its current text and tokens, kept in sync so that
+/// each edit only re-segments the region around it.
+pub struct Document {
+    chars: Vec<char>,
+    tokens: Vec<Token>,
+}
+
+impl Document {
+    pub fn new(text: &str, segment: impl Fn(&str) -> Vec<Token>) -> Self {
+        Document {
+            chars: text.chars().collect(),
+            tokens: segment(text),
+        }
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Apply `edit` and re-segment the affected tokens plus `CONTEXT_CHARS`
+    /// of context on each side. A reversed range or an offset past the end
+    /// of the text is an error, and leaves the document as it was.
+    pub fn apply(
+        &mut self,
+        edit: &Edit,
+        segment: impl Fn(&str) -> Vec<Token>,
+    ) -> Result<Patch, String> {
+        let n = self.chars.len();
+        let (start, end) = (edit.start, edit.end);
+        if start > end {
+            return Err(format!("edit start {} is after its end {}", start, end));
+        }
+        if end > n {
+            return Err(format!(
+                "edit end {} is past the end of the text ({} characters)",
+                end, n
+            ));
+        }
+
+        // char offset where each token starts
+        let mut starts = Vec::with_capacity(self.tokens.len());
+        let mut offset = 0;
+        for token in &self.tokens {
+            starts.push(offset);
+            offset += token.word.chars().count();
+        }
+
+        // first token touching the left context, first token past the right one
+        let lo = start.saturating_sub(CONTEXT_CHARS);
+        let hi = end + CONTEXT_CHARS;
+        let first = starts.iter().rposition(|&s| s <= lo).unwrap_or(0);
+        let last = starts
+            .iter()
+            .position(|&s| s >= hi)
+            .unwrap_or(self.tokens.len());
+        let window_start = starts.get(first).copied().unwrap_or(0);
+        let window_end = starts.get(last).copied().unwrap_or(n);
+
+        let new_text: Vec<char> = edit.text.chars().collect();
+        let window: String = self.chars[window_start..start]
+            .iter()
+            .chain(&new_text)
+            .chain(&self.chars[end..window_end])
+            .collect();
+        let tokens = segment(&window);
+
+        self.chars.splice(start..end, new_text);
+        self.tokens.splice(first..last, tokens.iter().cloned());
+
+        Ok(Patch {
+            first,
+            removed: last - first,
+            tokens,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // stand-in segmenter: one token per space-terminated word
+    fn by_word(text: &str) -> Vec<Token> {
+        text.split_inclusive(' ')
+            .map(|w| Token {
+                word: w.to_string(),
+                reading: None,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_apply_edit() {
+        let text = "aa bb cc dd ee ff gg hh ii jj ";
+        let mut doc = Document::new(text, by_word);
+        assert_eq!(doc.tokens().len(), 10);
+
+        // replace "ff" (chars 15..17) with "xyz"
+        let patch = doc
+            .apply(
+                &Edit {
+                    start: 15,
+                    end: 17,
+                    text: "xyz".into(),
+                },
+                by_word,
+            )
+            .unwrap();
+        // tokens outside the 8-char context are untouched
+        assert_eq!(patch.first, 2);
+        assert_eq!(patch.removed, 7);
+
+        let edited = "aa bb cc dd ee xyz gg hh ii jj ";
+        let words: Vec<_> = doc.tokens().iter().map(|t| t.word.as_str()).collect();
+        let expected: Vec<_> = by_word(edited).into_iter().map(|t| t.word).collect();
+        assert_eq!(words, expected);
+
+        // an insertion at the end appends
+        let n = edited.chars().count();
+        let patch = doc
+            .apply(
+                &Edit {
+                    start: n,
+                    end: n,
+                    text: "kk ".into(),
+                },
+                by_word,
+            )
+            .unwrap();
+        assert_eq!(patch.first + patch.removed, 10);
+        assert_eq!(doc.tokens().last().unwrap().word, "kk ");
+
+        // reversed and out-of-range edits are rejected, changing nothing
+        for (start, end) in [(5, 2), (0, 99), (99, 99)] {
+            let edit = Edit {
+                start,
+                end,
+                text: "zz ".into(),
+            };
+            assert!(doc.apply(&edit, by_word).is_err());
+        }
+        assert_eq!(doc.tokens().len(), 11);
+    }
+}
@@ -0,0 +1,118 @@
+use crate::trie::{Trie, TrieNode};
+use crate::utils::is_cjk;
+
+/// The entries `dump_dictionary` lists. The trie does not record which
+/// file an entry came from, so the tiers go by the entry's text, as the
+/// data files split them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tier {
+    /// Every entry of the trie (not emoji).
+    All,
+    /// Single CJK characters, as in chars.tsv.
+    Chars,
+    /// Words of two or more CJK characters, as in words.tsv.
+    Words,
+    /// Entries with anything else in them, Latin letters, digits or
+    /// symbols, as in lettered.tsv.
+    Lettered,
+    /// Proper nouns (names.tsv and `load_dictionary(b"names", ...)`).
+    Names,
+    /// Emoji readings (`load_dictionary(b"emoji", ...)`).
+    Emoji,
+}
+
+impl Tier {
+    /// The tier called `name`: "all", "chars", "words", "lettered", "names"
+    /// or "emoji".
+    pub fn parse(name: &str) -> Option<Tier> {
+        Some(match name {
+            "all" => Tier::All,
+            "chars" => Tier::Chars,
+            "words" => Tier::Words,
+            "lettered" => Tier::Lettered,
+            "names" => Tier::Names,
+            "emoji" => Tier::Emoji,
+            _ => return None,
+        })
+    }
+
+    fn contains(self, trie: &Trie, word: &str) -> bool {
+        let cjk = word.chars().all(is_cjk);
+        match self {
+            Tier::All => true,
+            Tier::Chars => cjk && word.chars().count() == 1,
+            Tier::Words => cjk && word.chars().count() > 1,
+            Tier::Lettered => !cjk,
+            Tier::Names => trie.names.contains(word),
+            Tier::Emoji => false,
+        }
+    }
+}
+
+/// The entries of `tier` as they stand, runtime loads included, in the
+/// format of the data files: a `word<TAB>jyutping` line per reading, most
+/// common first, with the reading's weight as a third field (`3%`) where
+/// the entry has weights other than 100. Words are in code point order;
+/// emoji in the order of their text.
+pub fn dump(trie: &Trie, tier: Tier) -> String {
+    fn collect(trie: &Trie, node: &TrieNode, word: &mut String, tier: Tier, out: &mut String) {
+        if !node.readings.is_empty() && tier.contains(trie, word) {
+            for (i, reading) in node.readings().enumerate() {
+                out.push_str(word);
+                out.push('\t');
+                out.push_str(&reading);
+                match node.weights.get(i) {
+                    Some(&weight) if weight != 100 => out.push_str(&format!("\t{}%", weight)),
+                    _ => {}
+                }
+                out.push('\n');
+            }
+        }
+        for (ch, child) in trie.children(node) {
+            word.push(ch);
+            collect(trie, child, word, tier, out);
+            word.pop();
+        }
+    }
+    let mut out = String::new();
+    if tier == Tier::Emoji {
+        for (emoji, reading) in trie.emoji.entries() {
+            out.push_str(&format!("{}\t{}\n", emoji, reading));
+        }
+    } else {
+        collect(trie, trie.root(), &mut String::new(), tier, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump() {
+        let mut trie = Trie::default();
+        for (word, reading) in [
+            ("行", "haang4"),
+            ("行", "hang4"),
+            ("銀行", "ngan4 hong4"),
+            ("OK", "ou1 kei1"),
+        ] {
+            trie.insert(word, reading);
+        }
+        trie.insert_name("沙田", "saa1 tin4");
+        trie.emoji.load("😂\thaa1 haa1\n");
+        let dump = |tier| dump(&trie, tier);
+        assert_eq!(
+            dump(Tier::All),
+            "OK\tou1 kei1\n沙田\tsaa1 tin4\n行\thaang4\n行\thang4\n銀行\tngan4 hong4\n"
+        );
+        assert_eq!(dump(Tier::Chars), "行\thaang4\n行\thang4\n");
+        assert_eq!(dump(Tier::Words), "沙田\tsaa1 tin4\n銀行\tngan4 hong4\n");
+        assert_eq!(dump(Tier::Lettered), "OK\tou1 kei1\n");
+        assert_eq!(dump(Tier::Names), "沙田\tsaa1 tin4\n");
+        assert_eq!(dump(Tier::Emoji), "😂\thaa1 haa1\n");
+        assert_eq!(Tier::parse("words"), Some(Tier::Words));
+        assert_eq!(Tier::parse("Words"), None);
+    }
+}
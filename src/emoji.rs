@@ -0,0 +1,113 @@
+use std::collections::HashMap;
+
+/// True if the grapheme cluster `cluster` is an emoji: it starts with a
+/// pictograph, or is a symbol or keycap in emoji presentation (`©️`, `1️⃣`).
+/// ZWJ sequences, skin-tone modifiers and flags are one cluster already (see
+/// `Trie::segment`), so they are one emoji here.
+pub fn is_emoji(cluster: &[char]) -> bool {
+    let Some(&first) = cluster.first() else {
+        return false;
+    };
+    let pictograph = matches!(first,
+        '\u{1F000}'..='\u{1FAFF}'   // pictographs, emoticons, flags, ...
+        | '\u{2600}'..='\u{27BF}'   // miscellaneous symbols, dingbats
+        | '\u{2B00}'..='\u{2BFF}'   // arrows and shapes (⭐, ⬛)
+        | '\u{231A}'..='\u{23FF}'   // technical symbols (⌚, ⏰)
+    );
+    pictograph
+        || cluster
+            .iter()
+            .any(|&c| matches!(c, '\u{FE0F}' | '\u{20E3}'))
+}
+
+/// `cluster` without variation selectors and skin-tone modifiers, so that
+/// 👍🏽 and 👍️ find the reading of 👍.
+fn base(cluster: &[char]) -> String {
+    cluster
+        .iter()
+        .filter(|&&c| !matches!(c, '\u{FE0E}' | '\u{FE0F}' | '\u{1F3FB}'..='\u{1F3FF}'))
+        .collect()
+}
+
+/// Readings for emoji loaded with `load_dictionary(b"emoji", ...)`
+/// (lib.rs): a tier below the dictionary, used only for emoji tokens the
+/// trie has no reading for.
+#[derive(Default)]
+pub struct EmojiReadings {
+    readings: HashMap<String, String>,
+}
+
+impl EmojiReadings {
+    /// Add `emoji<TAB>jyutping` lines, e.g. "😂\thaa1 haa1"; lines that are
+    /// not an emoji and a reading are skipped, and later lines win. Returns
+    /// the number added.
+    pub fn load(&mut self, text: &str) -> usize {
+        let mut count = 0;
+        for line in text.lines() {
+            let Some((emoji, reading)) = line.split_once('\t') else {
+                continue;
+            };
+            let emoji: Vec<char> = emoji.trim().chars().collect();
+            let reading = reading.trim();
+            if !is_emoji(&emoji) || reading.is_empty() {
+                continue;
+            }
+            self.readings.insert(base(&emoji), reading.to_string());
+            count += 1;
+        }
+        count
+    }
+
+    /// Every emoji and its reading, in the order of the emoji's text.
+    pub fn entries(&self) -> Vec<(&str, &str)> {
+        let mut entries: Vec<(&str, &str)> = self
+            .readings
+            .iter()
+            .map(|(emoji, reading)| (emoji.as_str(), reading.as_str()))
+            .collect();
+        entries.sort_unstable();
+        entries
+    }
+
+    /// The reading of the emoji `cluster`, ignoring skin tone and
+    /// presentation.
+    pub fn get(&self, cluster: &[char]) -> Option<&str> {
+        if self.readings.is_empty() {
+            return None;
+        }
+        self.readings.get(&base(cluster)).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    #[test]
+    fn test_is_emoji() {
+        for emoji in ["😂", "👍🏽", "👨‍👩‍👧", "🇭🇰", "❤️", "1️⃣", "⭐"]
+        {
+            assert!(is_emoji(&chars(emoji)), "{}", emoji);
+        }
+        for other in ["好", "a", "1", "%", "，", "©"] {
+            assert!(!is_emoji(&chars(other)), "{}", other);
+        }
+    }
+
+    #[test]
+    fn test_emoji_readings() {
+        let mut readings = EmojiReadings::default();
+        assert_eq!(
+            readings.load("😂\thaa1 haa1\n👍\tzan1 hai6 hou2\n好\thou2\nbad\n"),
+            2
+        );
+        assert_eq!(readings.get(&chars("😂")), Some("haa1 haa1"));
+        assert_eq!(readings.get(&chars("👍🏽")), Some("zan1 hai6 hou2"));
+        assert_eq!(readings.get(&chars("👍️")), Some("zan1 hai6 hou2"));
+        assert_eq!(readings.get(&chars("🙏")), None);
+    }
+}
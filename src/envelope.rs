@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::capabilities::SCHEMA_VERSION;
+
+/// Why a `*_checked` call failed.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// The text is not valid UTF-8.
+    InvalidUtf8,
+    /// The options are not a valid JSON object, or an option has a value
+    /// of the wrong type.
+    InvalidOptions,
+    /// Part of the input is not Jyutping.
+    InvalidJyutping,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Error {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl Error {
+    pub fn new(code: ErrorCode, message: impl ToString) -> Self {
+        Error {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// The input as text, or an `InvalidUtf8` error.
+pub fn utf8(input: &[u8]) -> Result<&str, Error> {
+    std::str::from_utf8(input).map_err(|e| Error::new(ErrorCode::InvalidUtf8, e))
+}
+
+/// Wrap a result, whose value is already serialized as JSON, in the
+/// envelope the `*_checked` functions return: `{"schema_version": 1, "ok":
+/// value, "error": null}` or `{..., "ok": null, "error": {"code": ...,
+/// "message": ...}}`.
+pub fn envelope(result: Result<String, Error>) -> Vec<u8> {
+    let (ok, error) = match result {
+        Ok(json) => (json, "null".to_string()),
+        Err(e) => (
+            "null".to_string(),
+            serde_json::to_string(&e).unwrap_or_else(|_| "null".to_string()),
+        ),
+    };
+    format!(
+        "{{\"schema_version\":{},\"ok\":{},\"error\":{}}}",
+        SCHEMA_VERSION, ok, error
+    )
+    .into_bytes()
+}
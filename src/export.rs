@@ -0,0 +1,150 @@
+use crate::sentence::sentence_spans;
+use crate::token::Token;
+
+/// One flashcard: a distinct word of the text with its readings, a gloss
+/// when a dictionary with glosses is loaded, and the first sentence of the
+/// text that uses it.
+#[derive(Debug, PartialEq)]
+pub struct Flashcard {
+    pub word: String,
+    pub jyutping: String,
+    pub yale: Option<Vec<String>>,
+    pub gloss: Option<String>,
+    pub example: String,
+}
+
+/// One card per distinct word (and reading) in `tokens`, in order of first
+/// appearance. `tokens` must be the segmentation of `text`.
+pub fn flashcards(
+    text: &str,
+    tokens: &[Token],
+    gloss: impl Fn(&str) -> Option<String>,
+) -> Vec<Flashcard> {
+    let spans = sentence_spans(text);
+    let mut cards: Vec<Flashcard> = Vec::new();
+    let mut offset = 0;
+    for token in tokens {
+        let start = offset;
+        offset += token.word.len();
+        let Some(reading) = &token.reading else {
+            continue;
+        };
+        if cards
+            .iter()
+            .any(|c| c.word == token.word && &c.jyutping == reading)
+        {
+            continue;
+        }
+        let example = spans
+            .iter()
+            .find(|s| s.contains(&start))
+            .map_or("", |s| text[s.clone()].trim());
+        cards.push(Flashcard {
+            word: token.word.clone(),
+            jyutping: reading.clone(),
+            yale: token.yale.clone(),
+            gloss: gloss(&token.word),
+            example: example.to_string(),
+        });
+    }
+    cards
+}
+
+/// Tab-separated rows for Anki's text import, one card per line:
+/// word, Jyutping, Yale, gloss, example. Missing fields are left empty.
+pub fn to_tsv(cards: &[Flashcard]) -> String {
+    let mut out = String::new();
+    for card in cards {
+        let yale = card.yale.as_ref().map(|y| y.join(" ")).unwrap_or_default();
+        push_row(
+            &mut out,
+            &[
+                &card.word,
+                &card.jyutping,
+                &yale,
+                card.gloss.as_deref().unwrap_or(""),
+                &card.example,
+            ],
+        );
+    }
+    out
+}
+
+/// Pleco's flashcard text import: an optional `// category` line, then
+/// `headword<TAB>pronunciation<TAB>definition` per card. The definition is
+/// the gloss, or the example sentence when there is no gloss, so that every
+/// card has a back side.
+pub fn to_pleco(cards: &[Flashcard], category: Option<&str>) -> String {
+    let mut out = String::new();
+    if let Some(category) = category {
+        out.push_str("// ");
+        out.push_str(category);
+        out.push('\n');
+    }
+    for card in cards {
+        let definition = card.gloss.as_deref().unwrap_or(&card.example);
+        push_row(&mut out, &[&card.word, &card.jyutping, definition]);
+    }
+    out
+}
+
+fn push_row(out: &mut String, fields: &[&str]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push('\t');
+        }
+        // a tab or line break inside a field would shift the columns
+        out.extend(field.chars().map(|c| match c {
+            '\t' | '\n' | '\r' => ' ',
+            c => c,
+        }));
+    }
+    out.push('\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(word: &str, reading: Option<&str>) -> Token {
+        Token {
+            word: word.to_string(),
+            reading: reading.map(String::from),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_flashcards() {
+        let text = "我係學生。\n佢都係學生！";
+        let tokens = [
+            token("我", Some("ngo5")),
+            token("係", Some("hai6")),
+            token("學生", Some("hok6 saang1")),
+            token("。", None),
+            token("\n", None),
+            token("佢", Some("keoi5")),
+            token("都", Some("dou1")),
+            token("係", Some("hai6")),
+            token("學生", Some("hok6 saang1")),
+            token("！", None),
+        ];
+        let gloss = |w: &str| (w == "學生").then(|| "student".to_string());
+        let cards = flashcards(text, &tokens, gloss);
+
+        let words: Vec<_> = cards.iter().map(|c| c.word.as_str()).collect();
+        assert_eq!(words, ["我", "係", "學生", "佢", "都"]);
+        assert_eq!(cards[2].gloss.as_deref(), Some("student"));
+        assert_eq!(cards[2].example, "我係學生。");
+        assert_eq!(cards[3].example, "佢都係學生！");
+
+        assert_eq!(
+            to_tsv(&cards[2..3]),
+            "學生\thok6 saang1\t\tstudent\t我係學生。\n"
+        );
+        assert_eq!(
+            to_pleco(&cards[1..3], Some("Lesson 1")),
+            "// Lesson 1\n係\thai6\t我係學生。\n學生\thok6 saang1\tstudent\n"
+        );
+    }
+}
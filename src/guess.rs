@@ -0,0 +1,107 @@
+use crate::ids::components;
+use crate::token::Token;
+use crate::trie::Trie;
+use crate::utils::is_cjk;
+
+/// A reading for `ch` from its phonetic component: the series reading in
+/// phonetics.tsv of any of its components (so that in ⿱相心 the phonetic
+/// 相 beats 心), else the reading of its likeliest phonetic component.
+/// Needs the character's decomposition (`Trie::ids`).
+pub fn guess(trie: &Trie, ch: char) -> Option<String> {
+    let components: Vec<char> = components(trie.ids.get(&ch)?)
+        .into_iter()
+        .filter(|&c| c != ch)
+        .collect();
+    components
+        .iter()
+        .find_map(|c| trie.phonetics.get(c).cloned())
+        .or_else(|| {
+            components.iter().find_map(|&c| {
+                trie.child(trie.root(), c)
+                    .and_then(|node| node.readings().next())
+            })
+        })
+}
+
+/// Guess readings for CJK characters the dictionary has no reading for
+/// (the `guess_readings` option), marking them `guessed: true`: better a
+/// flagged guess than a blank ruby over a rare character.
+pub fn guess_readings(trie: &Trie, tokens: &mut [Token]) {
+    for token in tokens.iter_mut().filter(|t| t.reading.is_none()) {
+        let mut chars = token.word.chars();
+        let (Some(ch), None) = (chars.next(), chars.next()) else {
+            continue;
+        };
+        if !is_cjk(ch) {
+            continue;
+        }
+        if let Some(reading) = guess(trie, ch) {
+            token.reading = Some(reading);
+            token.guessed = Some(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_guess() {
+        let mut trie = Trie::default();
+        trie.insert("睘", "king4");
+        trie.insert("青", "cing1");
+        trie.insert("心", "sam1");
+        trie.phonetics.insert('睘', "waan4".into());
+        trie.phonetics.insert('相', "soeng1".into());
+        for (ch, ids) in [
+            ('\u{E000}', "⿰王睘"),
+            ('\u{E001}', "⿰氵青"),
+            ('\u{E002}', "⿱相心"),
+            ('\u{E003}', "⿰氵⿱木目"),
+            ('\u{E004}', "\u{E004}"),
+        ] {
+            trie.ids.insert(ch, ids.into());
+        }
+        assert_eq!(guess(&trie, '\u{E000}').as_deref(), Some("waan4"));
+        assert_eq!(guess(&trie, '\u{E001}').as_deref(), Some("cing1"));
+        assert_eq!(guess(&trie, '\u{E002}').as_deref(), Some("soeng1"));
+        assert_eq!(guess(&trie, '\u{E003}'), None);
+        assert_eq!(guess(&trie, '\u{E004}'), None);
+        assert_eq!(guess(&trie, '\u{E005}'), None);
+    }
+
+    #[test]
+    fn test_guess_readings_bundled() {
+        // the table as build.rs bundles it: only characters without a
+        // reading are guessed, so their decompositions have to be kept
+        let mut trie = Trie::default();
+        trie.insert("羅", "lo4");
+        trie.insert("好", "hou2");
+        let mut table = HashMap::new();
+        crate::ids::load(&mut table, "U+3469\t㑩\t⿰亻羅\nU+4E8D\t亍\t⿱二亅\n");
+        crate::ids::retain_useful(
+            &mut table,
+            |c| {
+                trie.child(trie.root(), c)
+                    .is_some_and(|n| n.readings().next().is_some())
+            },
+            |c| trie.phonetics.contains_key(&c),
+        );
+        trie.ids = table;
+
+        let mut tokens: Vec<Token> = "㑩好"
+            .chars()
+            .map(|c| Token {
+                word: c.to_string(),
+                reading: None,
+                ..Default::default()
+            })
+            .collect();
+        guess_readings(&trie, &mut tokens);
+        assert_eq!(tokens[0].reading.as_deref(), Some("lo4"));
+        assert_eq!(tokens[0].guessed, Some(true));
+        assert!(!trie.ids.contains_key(&'亍'));
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+/// Parse an IDS line in the format of the CHISE and cjkvi-ids files, e.g.
+/// "U+597D\t好\t⿰女子" or "U+5379\t卹\t⿰血卩[GTV]\t⿰⿱丿皿卩[J]": the
+/// character and its first ideographic description sequence, without the
+/// `[GTJKV]` source tags and `^...$(...)` markers. Comments and malformed
+/// lines give None.
+pub fn parse_line(line: &str) -> Option<(char, String)> {
+    let mut parts = line.split('\t');
+    let code = parts.next()?.strip_prefix("U+")?;
+    let ch = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+    let mut chars = parts.next()?.chars();
+    if chars.next() != Some(ch) || chars.next().is_some() {
+        return None;
+    }
+    let ids = parts.next()?.trim();
+    let ids = ids.split_once('[').map_or(ids, |(ids, _)| ids);
+    let ids = ids.split_once("$(").map_or(ids, |(ids, _)| ids);
+    let ids = ids.trim_start_matches('^').trim_end_matches('$');
+    (!ids.is_empty()).then(|| (ch, ids.to_string()))
+}
+
+/// Number of components an ideographic description character takes:
+/// ⿲ and ⿳ three, ⿾ and ⿿ one, the others two; 0 for anything else.
+fn arity(c: char) -> usize {
+    match c {
+        '\u{2FF2}' | '\u{2FF3}' => 3,
+        '\u{2FFE}' | '\u{2FFF}' => 1,
+        '\u{2FF0}'..='\u{2FFD}' | '\u{31EF}' => 2,
+        _ => 0,
+    }
+}
+
+/// End of the description starting at `ids[i]`.
+fn skip(ids: &[char], i: usize) -> Option<usize> {
+    let c = *ids.get(i)?;
+    (0..arity(c)).try_fold(i + 1, |j, _| skip(ids, j))
+}
+
+/// The components of `ids` at its top level that are single characters,
+/// the phonetic first: it is usually the last one, on the right (⿰氵青),
+/// at the bottom (⿱艹化) or inside (⿺辶袁).
+pub fn components(ids: &str) -> Vec<char> {
+    let ids: Vec<char> = ids.chars().collect();
+    let Some(&first) = ids.first() else {
+        return Vec::new();
+    };
+    let mut parts = Vec::new();
+    let mut i = 1;
+    for _ in 0..arity(first) {
+        let Some(end) = skip(&ids, i) else {
+            return Vec::new();
+        };
+        if let [c] = ids[i..end] {
+            parts.push(c);
+        }
+        i = end;
+    }
+    parts.reverse();
+    parts
+}
+
+/// Add the decompositions in IDS `text` to `table`; later lines win.
+/// Returns the number added.
+pub fn load(table: &mut HashMap<char, String>, text: &str) -> usize {
+    let mut count = 0;
+    for (ch, ids) in text.lines().filter_map(parse_line) {
+        table.insert(ch, ids);
+        count += 1;
+    }
+    count
+}
+
+/// Drop the decompositions of `table` that are of no use, to keep the
+/// bundled table small: those of characters with a reading are kept for
+/// `decompose`, and those of characters without one only if a component
+/// has a reading or a phonetic series to guess from.
+#[allow(dead_code)] // only the build script bundles a table
+pub fn retain_useful(
+    table: &mut HashMap<char, String>,
+    has_reading: impl Fn(char) -> bool,
+    is_phonetic: impl Fn(char) -> bool,
+) {
+    table.retain(|&ch, ids| {
+        has_reading(ch)
+            || components(ids)
+                .into_iter()
+                .any(|c| c != ch && (has_reading(c) || is_phonetic(c)))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("U+597D\t好\t⿰女子"),
+            Some(('好', "⿰女子".into()))
+        );
+        assert_eq!(
+            parse_line("U+5379\t卹\t⿰血卩[GTV]\t⿰⿱丿皿卩[J]"),
+            Some(('卹', "⿰血卩".into()))
+        );
+        assert_eq!(
+            parse_line("U+4E00\t一\t^一$(GHJKTV)"),
+            Some(('一', "一".into()))
+        );
+        assert_eq!(parse_line("# comment"), None);
+        assert_eq!(parse_line("U+597D\t女\t⿰女子"), None);
+        assert_eq!(parse_line("U+597D\t好\t"), None);
+    }
+
+    #[test]
+    fn test_retain_useful() {
+        let mut table = HashMap::new();
+        load(
+            &mut table,
+            "U+597D\t好\t⿰女子\nU+3469\t㑩\t⿰亻羅\nU+6E05\t清\t⿰氵青\nU+4E8D\t亍\t⿱二亅\n",
+        );
+        retain_useful(&mut table, |c| "好女子羅".contains(c), |c| c == '青');
+        let mut kept: Vec<char> = table.into_keys().collect();
+        kept.sort_unstable();
+        // 亍 has no reading and neither has any of its components
+        assert_eq!(kept, ['㑩', '好', '清']);
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use crate::jyutping::is_valid_syllable;
+use crate::utils::is_cjk;
+
+/// Length of the inline reading at the start of `rest`, and the reading:
+/// Jyutping in braces, e.g. "{hon3}". Braces around anything else (Typst
+/// code, LaTeX, a set) are not readings.
+fn reading(rest: &str) -> Option<(usize, &str)> {
+    let end = rest.find('}')?;
+    let inner = rest[1..end].trim();
+    let valid = !inner.is_empty() && inner.split_whitespace().all(is_valid_syllable);
+    valid.then_some((end + 1, inner))
+}
+
+/// Strip the inline readings from `text` (the `inline_readings` option): a
+/// CJK character followed by Jyutping in braces, 漢{hon3}字{zi6}, loses the
+/// braces and is read as written there. Returns the text without them and
+/// the readings by character offset in that text, as for `readings_at`.
+pub fn strip(text: &str) -> (String, BTreeMap<usize, String>) {
+    let mut out = String::with_capacity(text.len());
+    let mut readings = BTreeMap::new();
+    let mut offset = 0;
+    let mut prev: Option<char> = None;
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        let inline = match ch {
+            '{' if prev.is_some_and(is_cjk) => reading(&text[i..]),
+            _ => None,
+        };
+        if let Some((len, reading)) = inline {
+            readings.insert(offset - 1, reading.to_string());
+            prev = None;
+            i += len;
+            continue;
+        }
+        out.push(ch);
+        offset += 1;
+        prev = Some(ch);
+        i += ch.len_utf8();
+    }
+    (out, readings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip() {
+        let (text, readings) = strip("漢{hon3}字{ zi6 }，行{hang4}{haang4}");
+        assert_eq!(text, "漢字，行{haang4}");
+        assert_eq!(
+            readings.into_iter().collect::<Vec<_>>(),
+            [(0, "hon3".into()), (1, "zi6".into()), (3, "hang4".into())]
+        );
+        // only Jyutping after a character is a reading
+        for text in ["a{hon3}", "#let x = 字{1}", "字{x}", "字{hon3", "{hon3}"] {
+            let (stripped, readings) = strip(text);
+            assert_eq!(stripped, text);
+            assert!(readings.is_empty());
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use serde::Deserializer;
+use serde::de::{SeqAccess, Visitor};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{LazyLock, RwLock};
+
+/// Marks the end of one reading and the start of the next in an encoded
+/// list (see `encode`).
+const SEPARATOR: u32 = u32::MAX;
+
+/// Distinct syllables, each stored once and referred to by index: the trie
+/// holds some 140,000 readings but only a few thousand distinct syllables,
+/// with "si6" or "gei1" shared by dozens of characters and every word that
+/// contains them. The strings live for the rest of the program, so
+/// `resolve` can hand them out without holding the lock.
+#[derive(Default)]
+struct Interner {
+    strings: Vec<&'static str>,
+    index: HashMap<&'static str, u32>,
+}
+
+static POOL: LazyLock<RwLock<Interner>> = LazyLock::new(|| RwLock::new(Interner::default()));
+
+/// Index of `s` in the pool, adding it if it is new.
+fn intern(s: &str) -> u32 {
+    if let Some(&id) = POOL.read().unwrap_or_else(|e| e.into_inner()).index.get(s) {
+        return id;
+    }
+    let mut pool = POOL.write().unwrap_or_else(|e| e.into_inner());
+    if let Some(&id) = pool.index.get(s) {
+        return id;
+    }
+    let s: &'static str = Box::leak(s.into());
+    let id = pool.strings.len() as u32;
+    pool.strings.push(s);
+    pool.index.insert(s, id);
+    id
+}
+
+/// The string interned as `id`.
+fn resolve(id: u32) -> &'static str {
+    POOL.read().unwrap_or_else(|e| e.into_inner()).strings[id as usize]
+}
+
+/// Append `reading` to an encoded list of readings: its space-separated
+/// syllables as pool indices, after a separator if the list is not empty.
+pub fn encode(reading: &str, ids: &mut Vec<u32>) {
+    if !ids.is_empty() {
+        ids.push(SEPARATOR);
+    }
+    ids.extend(reading.split(' ').map(intern));
+}
+
+/// The readings in an encoded list, in order.
+pub fn decode(ids: &[u32]) -> impl Iterator<Item = String> + '_ {
+    ids.split(|&id| id == SEPARATOR)
+        .filter(move |_| !ids.is_empty())
+        .map(|reading| {
+            let syllables: Vec<&str> = reading.iter().map(|&id| resolve(id)).collect();
+            syllables.join(" ")
+        })
+}
+
+/// Deserialize a list of readings (as build.rs writes them) straight into
+/// their encoded form, without allocating the strings.
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u32>, D::Error> {
+    struct Ids;
+    impl<'de> Visitor<'de> for Ids {
+        type Value = Vec<u32>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a list of strings")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<u32>, A::Error> {
+            let mut ids = Vec::new();
+            while let Some(reading) = seq.next_element::<&str>()? {
+                encode(reading, &mut ids);
+            }
+            ids.shrink_to_fit();
+            Ok(ids)
+        }
+    }
+    deserializer.deserialize_seq(Ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode() {
+        let mut ids = Vec::new();
+        assert_eq!(decode(&ids).count(), 0);
+        encode("hoeng1 gong2", &mut ids);
+        encode("gong2", &mut ids);
+        assert_eq!(ids.len(), 4);
+        assert_eq!(ids[1], ids[3]);
+        assert_eq!(decode(&ids).collect::<Vec<_>>(), ["hoeng1 gong2", "gong2"]);
+    }
+}
@@ -0,0 +1,113 @@
+//! The Jyutping syllable inventory. It depends on nothing else in the crate,
+//! so that build.rs can check the data files against it too.
+
+/// Jyutping initials, two-letter ones first.
+pub const INITIALS: [&str; 19] = [
+    "gw", "kw", "ng", "b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "w", "z", "c", "s",
+    "j",
+];
+
+/// Jyutping finals (LSHK scheme), including the syllabic nasals m and ng.
+pub const FINALS: [&str; 58] = [
+    "aa", "aai", "aau", "aam", "aan", "aang", "aap", "aat", "aak", //
+    "a", "ai", "au", "am", "an", "ang", "ap", "at", "ak", //
+    "e", "ei", "eu", "em", "eng", "ep", "ek", //
+    "i", "iu", "im", "in", "ing", "ip", "it", "ik", //
+    "o", "oi", "ou", "on", "ong", "ot", "ok", //
+    "u", "ui", "un", "ung", "ut", "uk", //
+    "oe", "oeng", "oet", "oek", //
+    "eoi", "eon", "eot", //
+    "yu", "yun", "yut", //
+    "m", "ng",
+];
+
+/// Byte lengths of every legal syllable (initial + final + optional tone
+/// digit) that starts at `s[0]`. A tone digit right after a final is always
+/// taken, since no syllable can start with one.
+pub fn syllable_lengths(s: &str) -> Vec<usize> {
+    let mut lengths = Vec::new();
+    for initial in std::iter::once("").chain(INITIALS) {
+        let Some(rest) = s.strip_prefix(initial) else {
+            continue;
+        };
+        for fin in FINALS {
+            // syllabic nasals only stand alone or after h (hm, hng); the yu
+            // finals always take an initial (jyu, not yu)
+            let syllabic = fin == "m" || fin == "ng";
+            if !rest.starts_with(fin)
+                || (syllabic && !matches!(initial, "" | "h"))
+                || (fin.starts_with('y') && initial.is_empty())
+                || labialized_rounded(initial, fin)
+            {
+                continue;
+            }
+            let mut len = initial.len() + fin.len();
+            if matches!(s.as_bytes().get(len), Some(b'1'..=b'6')) {
+                len += 1;
+            }
+            lengths.push(len);
+        }
+    }
+    lengths
+}
+
+/// True for gw or kw before a front rounded final (yu, oe, eo), a
+/// combination Cantonese does not have: 居 is geoi1, 決 kyut3.
+fn labialized_rounded(initial: &str, fin: &str) -> bool {
+    matches!(initial, "gw" | "kw") && ["yu", "oe", "eo"].iter().any(|r| fin.starts_with(r))
+}
+
+/// Why a syllable is not legal Jyutping; see `syllable_error`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyllableError {
+    /// Not an initial plus a final of the inventory ("om", "xa", "yu").
+    Spelling,
+    /// gw or kw before a front rounded final ("gweoi", "kwyun").
+    Labialized,
+    /// A checked final (-p, -t, -k) with tone 4 or 5 ("sik4"): checked
+    /// syllables take tones 1, 3 and 6, and 2 as a changed tone (碟 dip2).
+    CheckedTone,
+}
+
+impl SyllableError {
+    /// A description for validation reports.
+    pub fn message(self) -> &'static str {
+        match self {
+            SyllableError::Spelling => "not an initial plus a final",
+            SyllableError::Labialized => "gw or kw before a front rounded final",
+            SyllableError::CheckedTone => "checked syllable with tone 4 or 5",
+        }
+    }
+}
+
+/// What is wrong with `syllable`, an initial plus a final with an optional
+/// tone; None if it is legal.
+pub fn syllable_error(syllable: &str) -> Option<SyllableError> {
+    let (body, tone) = match syllable.strip_suffix(|c| ('1'..='6').contains(&c)) {
+        Some(body) => (body, syllable.as_bytes().last().copied()),
+        None => (syllable, None),
+    };
+    if body.is_empty() || !syllable_lengths(syllable).contains(&syllable.len()) {
+        let labialized = ["gw", "kw"].iter().any(|initial| {
+            body.strip_prefix(initial)
+                .is_some_and(|fin| FINALS.contains(&fin) && labialized_rounded(initial, fin))
+        });
+        return Some(if labialized {
+            SyllableError::Labialized
+        } else {
+            SyllableError::Spelling
+        });
+    }
+    let checked = body.ends_with(['p', 't', 'k']);
+    (checked && matches!(tone, Some(b'4' | b'5'))).then_some(SyllableError::CheckedTone)
+}
+
+/// True if `syllable` is an initial plus a final, with an optional tone.
+/// A checked syllable in tone 4 or 5 is still spelled right, and is read
+/// in the dictionary (卜卜 buk4 buk4); only `syllable_error` rejects it.
+pub fn is_valid_syllable(syllable: &str) -> bool {
+    !matches!(
+        syllable_error(syllable),
+        Some(SyllableError::Spelling | SyllableError::Labialized)
+    )
+}
@@ -0,0 +1,330 @@
+use serde::Serialize;
+
+use crate::inventory::syllable_lengths;
+pub use crate::inventory::{FINALS, INITIALS, is_valid_syllable, syllable_error};
+use crate::romanize::{Case, map_syllables};
+
+/// Tone number of a Jyutping syllable: 1–6 as written, or 0 when the
+/// syllable carries no (valid) tone digit. This mapping is stable:
+///
+/// | Tone | Contour          | e.g.    |
+/// | ---- | ---------------- | ------- |
+/// | 1    | high level       | 詩 si1  |
+/// | 2    | high rising      | 史 si2  |
+/// | 3    | mid level        | 試 si3  |
+/// | 4    | low falling      | 時 si4  |
+/// | 5    | low rising       | 市 si5  |
+/// | 6    | low level        | 事 si6  |
+///
+/// Checked syllables (ending in -p/-t/-k) keep their written 1, 3 or 6.
+pub fn tone(syllable: &str) -> u8 {
+    match syllable.as_bytes().last() {
+        Some(&d @ b'1'..=b'6') => d - b'0',
+        _ => 0,
+    }
+}
+
+/// Tone numbers of every syllable in a space-separated Jyutping reading,
+/// e.g. "gwong2 dung1 waa2" → [2, 1, 2].
+pub fn tones(reading: &str) -> Vec<u8> {
+    reading.split_whitespace().map(tone).collect()
+}
+
+/// Split unspaced Jyutping, with or without tone digits, into syllables:
+/// "gwong2dung1waa2" → ["gwong2", "dung1", "waa2"]. Takes the split with
+/// the fewest syllables; among equally short splits, the one with the
+/// longest syllables first ("gwongdungwaa" → "gwong dung waa", not
+/// "gwong dun gwaa"). None if `s` is not a sequence of legal lowercase
+/// syllables, which are all ASCII.
+pub fn syllabify(s: &str) -> Option<Vec<&str>> {
+    if !s.is_ascii() {
+        return None;
+    }
+    // best[i] = (syllables, length of the first one) for the suffix s[i..]
+    let mut best: Vec<Option<(usize, usize)>> = vec![None; s.len() + 1];
+    best[s.len()] = Some((0, 0));
+    for i in (0..s.len()).rev() {
+        for len in syllable_lengths(&s[i..]) {
+            let Some((count, _)) = best[i + len] else {
+                continue;
+            };
+            let better = best[i].is_none_or(|(c, l)| count + 1 < c || (count + 1 == c && len > l));
+            if better {
+                best[i] = Some((count + 1, len));
+            }
+        }
+    }
+
+    let mut syllables = Vec::new();
+    let mut start = 0;
+    while start < s.len() {
+        let (_, len) = best[start]?;
+        syllables.push(&s[start..start + len]);
+        start += len;
+    }
+    Some(syllables)
+}
+
+/// A Jyutping syllable split into onset, rime and tone. Converters from
+/// other romanizations parse into this model, so every system round-trips
+/// through one canonical form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Syllable {
+    /// One of `INITIALS`, or "" for none.
+    pub onset: &'static str,
+    /// One of `FINALS`.
+    pub rime: &'static str,
+    /// 1–6, or 0 if unmarked.
+    pub tone: u8,
+}
+
+impl Syllable {
+    /// Parse one lowercase Jyutping syllable, e.g. "gwong2"; the tone digit
+    /// is optional.
+    pub fn parse(s: &str) -> Option<Syllable> {
+        if !is_valid_syllable(s) {
+            return None;
+        }
+        let tone = tone(s);
+        let body = if tone > 0 { &s[..s.len() - 1] } else { s };
+        INITIALS
+            .iter()
+            .chain(std::iter::once(&""))
+            .find_map(|&onset| {
+                let rest = body.strip_prefix(onset)?;
+                let rime = FINALS.iter().find(|&&f| f == rest)?;
+                Some(Syllable { onset, rime, tone })
+            })
+    }
+
+    /// Build a syllable from the onset and rime spelled in another system,
+    /// e.g. Cantonese Pinyin "dz" + "oey": the onset is the longest prefix of
+    /// `body` found in `initials` (else a Jyutping initial, else none), and
+    /// the rest is mapped through `finals` (else kept as is).
+    pub fn respell(
+        body: &str,
+        tone: u8,
+        initials: &[(&str, &str)],
+        finals: &[(&str, &str)],
+    ) -> Option<Syllable> {
+        let (onset, rest) = initials
+            .iter()
+            .find_map(|&(from, to)| body.strip_prefix(from).map(|rest| (to, rest)))
+            .or_else(|| {
+                INITIALS
+                    .iter()
+                    .find_map(|&i| body.strip_prefix(i).map(|rest| (i, rest)))
+            })
+            .unwrap_or(("", body));
+        let rime = finals
+            .iter()
+            .find(|&&(from, _)| from == rest)
+            .map_or(rest, |&(_, to)| to);
+        let tone = if tone > 0 {
+            tone.to_string()
+        } else {
+            String::new()
+        };
+        Syllable::parse(&format!("{}{}{}", onset, rime, tone))
+    }
+}
+
+impl std::fmt::Display for Syllable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.onset, self.rime)?;
+        if self.tone > 0 {
+            write!(f, "{}", self.tone)?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert syllables of another romanization to Jyutping, parsing each
+/// lowercased syllable with `parse` and keeping its capitalization.
+/// Spaces and punctuation between syllables are copied. Fails on the first
+/// syllable `parse` does not recognize.
+pub fn to_jyutping(text: &str, parse: impl Fn(&str) -> Option<Syllable>) -> Result<String, String> {
+    map_syllables(text, |syllable| match parse(&syllable.to_lowercase()) {
+        Some(s) => Ok(Case::of(syllable).apply(&s.to_string())),
+        None => Err(format!("unrecognized syllable: {:?}", syllable)),
+    })
+}
+
+/// Nonstandard initials seen in learner input and older romanizations,
+/// longest first: Cantonese Pinyin / Sidney Lau `ts` and `dz`, Yale `ch`,
+/// and `y` for Jyutping `j`.
+const FUZZY_INITIALS: [(&str, &str); 5] = [
+    ("ts", "c"),
+    ("ch", "c"),
+    ("dz", "z"),
+    ("tz", "z"),
+    ("y", "j"),
+];
+
+/// Nonstandard finals, longest first: `ue` for `yu` and the oe/eo
+/// confusions, including Yale's `eu` spellings.
+const FUZZY_FINALS: [(&str, &str); 12] = [
+    ("eung", "oeng"),
+    ("eong", "oeng"),
+    ("uet", "yut"),
+    ("uen", "yun"),
+    ("eui", "eoi"),
+    ("oei", "eoi"),
+    ("eun", "eon"),
+    ("oen", "eon"),
+    ("eut", "eot"),
+    ("euk", "oek"),
+    ("eok", "oek"),
+    ("ue", "yu"),
+];
+
+/// A syllable the normalizer changed.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Correction {
+    pub from: String,
+    pub to: String,
+}
+
+/// Result of `normalize`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Normalized {
+    /// The input with every syllable in canonical Jyutping where possible.
+    pub jyutping: String,
+    pub corrections: Vec<Correction>,
+    /// Syllables that are still not legal Jyutping after normalization.
+    pub invalid: Vec<String>,
+}
+
+/// Canonical spelling of one lowercase syllable, if the fuzzy rules turn it
+/// into legal Jyutping. Only whole initials and finals are replaced, so
+/// valid syllables never change.
+pub fn normalize_syllable(syllable: &str) -> Option<String> {
+    if is_valid_syllable(syllable) {
+        return Some(syllable.to_string());
+    }
+    let (body, tone) = match syllable.strip_suffix(|c| ('1'..='6').contains(&c)) {
+        Some(body) => (body, &syllable[body.len()..]),
+        None => (syllable, ""),
+    };
+
+    let (initial, rest) = FUZZY_INITIALS
+        .iter()
+        .find_map(|&(from, to)| body.strip_prefix(from).map(|rest| (to, rest)))
+        .or_else(|| {
+            INITIALS
+                .iter()
+                .find_map(|&i| body.strip_prefix(i).map(|rest| (i, rest)))
+        })
+        .unwrap_or(("", body));
+    let mut fin = FUZZY_FINALS
+        .iter()
+        .find(|&&(from, _)| rest == from)
+        .map_or(rest.to_string(), |&(_, to)| to.to_string());
+    // "yu5", "yun" and "yut" drop the j of jyu, jyun and jyut
+    if body.starts_with('y') && matches!(fin.as_str(), "u" | "un" | "ut") {
+        fin.insert(0, 'y');
+    }
+
+    let fixed = format!("{}{}{}", initial, fin, tone);
+    is_valid_syllable(&fixed).then_some(fixed)
+}
+
+/// Map common nonstandard spellings to canonical Jyutping: `yuet6` →
+/// `jyut6`, `tsi1` → `ci1`, `yu5` → `jyu5`, `heui1` → `heoi1`. Input is
+/// lowercased and split on whitespace; each changed syllable is reported.
+pub fn normalize(text: &str) -> Normalized {
+    let mut out = Normalized {
+        jyutping: String::new(),
+        corrections: Vec::new(),
+        invalid: Vec::new(),
+    };
+    for syllable in text.split_whitespace() {
+        let syllable = syllable.to_lowercase();
+        let fixed = match normalize_syllable(&syllable) {
+            Some(fixed) => fixed,
+            None => {
+                out.invalid.push(syllable.clone());
+                syllable.clone()
+            }
+        };
+        if fixed != syllable {
+            out.corrections.push(Correction {
+                from: syllable,
+                to: fixed.clone(),
+            });
+        }
+        if !out.jyutping.is_empty() {
+            out.jyutping.push(' ');
+        }
+        out.jyutping.push_str(&fixed);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tones() {
+        assert_eq!(tones("gwong2 dung1 waa2"), [2, 1, 2]);
+        assert_eq!(tones("sik6 faan6"), [6, 6]);
+        assert_eq!(tones("m4 goi1"), [4, 1]);
+        // missing or out-of-range tone digits
+        assert_eq!(tones("ngo hai7"), [0, 0]);
+        assert!(tones("").is_empty());
+    }
+
+    #[test]
+    fn test_syllabify() {
+        let split = |s| syllabify(s).map(|v| v.join(" "));
+        assert_eq!(
+            split("gwong2dung1waa2").as_deref(),
+            Some("gwong2 dung1 waa2")
+        );
+        assert_eq!(split("gwongdungwaa").as_deref(), Some("gwong dung waa"));
+        assert_eq!(
+            split("hoeng1gong2jan4").as_deref(),
+            Some("hoeng1 gong2 jan4")
+        );
+        assert_eq!(split("m4goi1").as_deref(), Some("m4 goi1"));
+        assert_eq!(split("ngo5").as_deref(), Some("ngo5"));
+        assert_eq!(split("sinaa").as_deref(), Some("sin aa"));
+        assert_eq!(split("").as_deref(), Some(""));
+        assert_eq!(split("xyz"), None);
+        assert_eq!(split("gwong7"), None);
+        assert_eq!(split("zm"), None);
+        // Yale diacritics and CJK are not Jyutping (and not cut mid-char)
+        assert_eq!(split("néih"), None);
+        assert_eq!(split("hóu2"), None);
+        assert_eq!(split("你好"), None);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let n = normalize("Yuet6 tsi1 yu5 heui1 ngo5 jung4");
+        assert_eq!(n.jyutping, "jyut6 ci1 jyu5 heoi1 ngo5 jung4");
+        let fixed: Vec<_> = n.corrections.iter().map(|c| c.from.as_str()).collect();
+        assert_eq!(fixed, ["yuet6", "tsi1", "yu5", "heui1"]);
+        assert!(n.invalid.is_empty());
+
+        assert_eq!(
+            normalize("yung4 cheung4 seong1 deoi3").jyutping,
+            "jung4 coeng4 soeng1 deoi3"
+        );
+        let n = normalize("xyz gwong2");
+        assert_eq!(n.jyutping, "xyz gwong2");
+        assert_eq!(n.invalid, ["xyz"]);
+    }
+
+    #[test]
+    fn test_syllable() {
+        let s = Syllable::parse("gwong2").unwrap();
+        assert_eq!((s.onset, s.rime, s.tone), ("gw", "ong", 2));
+        let s = Syllable::parse("ng5").unwrap();
+        assert_eq!((s.onset, s.rime, s.tone), ("", "ng", 5));
+        assert_eq!(Syllable::parse("ngaa").unwrap().to_string(), "ngaa");
+        assert_eq!(Syllable::parse("hoeng1").unwrap().to_string(), "hoeng1");
+        assert_eq!(Syllable::parse("gwong7"), None);
+    }
+}
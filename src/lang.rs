@@ -0,0 +1,78 @@
+use serde::Serialize;
+
+use crate::token::Token;
+use crate::utils::is_cjk;
+
+/// The language a token is written in, as `guess` tells it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// Cantonese: CJK text, and Latin words with a Cantonese reading in the
+    /// dictionary (lettered.tsv: `feel`, `part-time`, `AB膠`).
+    Yue,
+    /// Latin words the dictionary does not read.
+    En,
+    /// Other letters: kana, Hangul, Cyrillic, ...
+    Other,
+}
+
+/// Guess the language of `token` from its script and whether the
+/// dictionary read it. Tokens without letters (punctuation, whitespace,
+/// digits, emoji) get None: they belong to the text around them.
+pub fn guess(token: &Token) -> Option<Lang> {
+    let word = &token.word;
+    if word.chars().any(is_cjk) || (token.reading.is_some() && word.chars().any(is_latin)) {
+        Some(Lang::Yue)
+    } else if word.chars().any(is_latin) {
+        Some(Lang::En)
+    } else if word.chars().any(char::is_alphabetic) {
+        Some(Lang::Other)
+    } else {
+        None
+    }
+}
+
+/// Latin letters, full-width ones included.
+fn is_latin(ch: char) -> bool {
+    ch.is_alphabetic()
+        && matches!(ch,
+            'A'..='Z' | 'a'..='z'
+            | '\u{00C0}'..='\u{024F}'   // Latin-1 Supplement, Extended-A and -B
+            | '\u{1E00}'..='\u{1EFF}'   // Latin Extended Additional
+            | '\u{FF21}'..='\u{FF3A}' | '\u{FF41}'..='\u{FF5A}' // full-width
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(word: &str, reading: Option<&str>) -> Token {
+        Token {
+            word: word.to_string(),
+            reading: reading.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_guess() {
+        let cases = [
+            (token("學生", Some("hok6 saang1")), Some(Lang::Yue)),
+            (token("𡃁", None), Some(Lang::Yue)),
+            (token("part-time", Some("paat1 taai1")), Some(Lang::Yue)),
+            (token("AB膠", Some("ei1 bi1 gaau1")), Some(Lang::Yue)),
+            (token("package", None), Some(Lang::En)),
+            (token("café", None), Some(Lang::En)),
+            (token("ＰＣ", None), Some(Lang::En)),
+            (token("すし", None), Some(Lang::Other)),
+            (token("，", None), None),
+            (token(" ", None), None),
+            (token("2024", None), None),
+            (token("😂", None), None),
+        ];
+        for (token, expected) in cases {
+            assert_eq!(guess(&token), expected, "{}", token.word);
+        }
+    }
+}
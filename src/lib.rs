@@ -1,19 +1,73 @@
+mod analysis;
+#[cfg(feature = "aho-corasick")]
+mod automaton;
+mod bigram;
+mod cache;
+mod capabilities;
+mod cedict;
+mod check;
+mod cloze;
+mod detect;
+mod diff;
+mod distance;
+mod document;
+mod dump;
+mod emoji;
+mod envelope;
+mod export;
+mod guess;
+mod ids;
+mod inline;
+mod interner;
+mod inventory;
+mod jyutping;
+mod lang;
+mod links;
+mod markup;
+mod minimal_pairs;
+mod numbers;
+mod options;
+mod output;
+#[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+mod parallel;
+mod particles;
+mod readability;
+mod rhyme;
+mod romanize;
+mod sandhi;
+mod search;
+mod sentence;
 mod token;
 mod trie;
+mod unihan;
 mod utils;
-mod yale;
-use std::sync::LazyLock;
+mod variants;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{LazyLock, Mutex, RwLock, RwLockReadGuard};
 
-use yale::{jyutping_to_yale, jyutping_to_yale_vec};
+use romanize::yale::{Style, jyutping_to_yale, jyutping_to_yale_vec, try_jyutping_to_yale};
 
+use cache::LruCache;
+use capabilities::{Capabilities, LoadedDictionary};
+use document::{Document, Edit};
+use envelope::{ErrorCode, envelope};
+use options::{KnownMode, Options, OutputFormat, RubyPlacement};
 use token::Token;
 use trie::Trie;
+use utils::capitalize;
 use wasm_minimal_protocol::*;
 
 initiate_protocol!();
 
 const TRIE_DATA: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/trie.dat"));
-static TRIE: LazyLock<Trie> = LazyLock::new(build_trie);
+static TRIE: LazyLock<RwLock<Trie>> = LazyLock::new(|| RwLock::new(build_trie()));
+
+/// Number of distinct input fragments whose tokens are kept between calls.
+const CACHE_CAPACITY: usize = 512;
+static CACHE: LazyLock<Mutex<LruCache<Vec<Token>>>> =
+    LazyLock::new(|| Mutex::new(LruCache::new(CACHE_CAPACITY)));
 
 fn build_trie() -> Trie {
     let mut data_ptr = TRIE_DATA;
@@ -21,23 +75,1103 @@ fn build_trie() -> Trie {
     postcard::from_bytes(&decomp).expect("Failed to deserialize trie data")
 }
 
+/// Documents registered with `open_document`, by id.
+static DOCUMENTS: LazyLock<Mutex<HashMap<u32, Document>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+static NEXT_DOCUMENT_ID: AtomicU32 = AtomicU32::new(1);
+
+/// Learner levels loaded with `load_levels`, by word or character.
+static LEVELS: LazyLock<RwLock<HashMap<String, u8>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Glosses from dictionaries loaded with `load_dictionary`, by word.
+static GLOSSES: LazyLock<RwLock<HashMap<String, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Dictionaries loaded with `load_dictionary`, for `get_capabilities`.
+static DICTIONARIES: Mutex<Vec<LoadedDictionary>> = Mutex::new(Vec::new());
+
+/// Shared read access to the global trie. A poisoned lock only means a
+/// loader panicked mid-insert; the trie itself is still usable.
+fn trie() -> RwLockReadGuard<'static, Trie> {
+    TRIE.read().unwrap_or_else(|e| e.into_inner())
+}
+
+fn clear_cached_tokens() {
+    CACHE.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Segment `text` and fill in the Yale readings, with the default output
+/// options applied.
+fn annotate_text(trie: &Trie, text: &str) -> Vec<Token> {
+    finish_tokens(with_yale(trie.segment(text)), &Options::default())
+}
+
+/// Segment and fill in Yale under the per-call options. All token fields
+/// are kept; `finish_tokens` applies the output options afterwards.
+fn annotate_text_with(trie: &Trie, text: &str, options: &Options) -> Vec<Token> {
+    let overlay = trie.overlay(&options.overrides);
+    let segment = |text: &str| {
+        trie.segment_with(
+            text,
+            options.normalization,
+            options.scoring,
+            options.matching(),
+            overlay.as_ref(),
+        )
+    };
+    #[cfg(all(feature = "parallel", not(target_arch = "wasm32")))]
+    let segment = |prose: &str| parallel::segment(prose, segment);
+    let (text, readings_at) = if options.inline_readings {
+        let (text, mut inline) = inline::strip(text);
+        let mut readings_at = options.readings_at.clone();
+        readings_at.append(&mut inline);
+        (Cow::Owned(text), Cow::Owned(readings_at))
+    } else {
+        (Cow::Borrowed(text), Cow::Borrowed(&options.readings_at))
+    };
+    let mut tokens = markup::segment(&text, options.input, segment);
+    if options.guess_readings {
+        guess::guess_readings(trie, &mut tokens);
+    }
+    if options.numerals {
+        tokens = numbers::read_numerals(tokens);
+    }
+    if options.particles {
+        tokens = particles::tag_particles(tokens);
+    }
+    if options.strokes {
+        for t in &mut tokens {
+            if let Some((radicals, strokes)) = trie.radicals_and_strokes(&t.word) {
+                t.radicals = Some(radicals);
+                t.strokes = Some(strokes);
+            }
+        }
+    }
+    token::set_readings_at(&mut tokens, &readings_at);
+    with_yale(tokens)
+}
+
+fn with_yale(mut tokens: Vec<Token>) -> Vec<Token> {
+    for t in &mut tokens {
+        t.yale = t
+            .reading
+            .as_deref()
+            .and_then(|r| jyutping_to_yale_vec(r, Style::Diacritics));
+        t.jyutping_syllables = t
+            .reading
+            .as_deref()
+            .map(|r| r.split_whitespace().map(str::to_string).collect())
+            .filter(|s: &Vec<String>| !s.is_empty());
+    }
+    tokens
+}
+
+/// Apply the output options (`merge_whitespace`, `freq`, `classifiers`, `weights`, `readings`,
+/// `rare_below`, `only_rare`, `known_words`, `cloze`, `ruby`, `tones`, `sandhi`,
+/// `romanization`, `high_falling`, `nfd`)
+/// to full tokens. Kept separate from segmentation so that
+/// cached tokens serve every variant.
+fn finish_tokens(mut tokens: Vec<Token>, options: &Options) -> Vec<Token> {
+    if options.merge_whitespace {
+        tokens = token::merge_whitespace(tokens);
+    }
+    // before readings are hidden: a lettered word's reading marks it yue
+    if options.lang {
+        for t in &mut tokens {
+            t.lang = lang::guess(t);
+        }
+    }
+    for t in &mut tokens {
+        if t.reading.is_some() && options.known_words.contains(&t.word) {
+            match options.known_mode {
+                KnownMode::Hide => {
+                    t.reading = None;
+                    t.yale = None;
+                    t.jyutping_syllables = None;
+                    t.freq = None;
+                    t.weight = None;
+                    t.confidence = None;
+                    t.readings = None;
+                }
+                KnownMode::Flag => t.known = Some(true),
+            }
+        }
+        if let (Some(threshold), Some(freq)) = (options.rare_below, t.freq) {
+            t.rare = Some(freq < threshold);
+        }
+        if options.only_rare && t.rare != Some(true) {
+            t.reading = None;
+            t.yale = None;
+            t.jyutping_syllables = None;
+            t.readings = None;
+        }
+        if !options.freq {
+            t.freq = None;
+        }
+        if !options.classifiers {
+            t.classifiers = None;
+        }
+        if !options.weights {
+            t.weight = None;
+            t.confidence = None;
+        }
+        if !options.readings {
+            t.readings = None;
+        }
+    }
+    if let Some(ratio) = options.cloze {
+        cloze::hide(&mut tokens, ratio, options.seed);
+    }
+    // the text formats place mono ruby themselves, within one element
+    if options.ruby == RubyPlacement::Char && options.format == OutputFormat::Json {
+        tokens = token::split_by_char(tokens);
+    }
+    if options.tones {
+        for t in &mut tokens {
+            t.tones = t.reading.as_deref().map(jyutping::tones);
+        }
+    }
+    if !options.sandhi.is_empty() {
+        sandhi::apply(&mut tokens, &options.sandhi);
+    }
+    if options.high_falling {
+        for t in &mut tokens {
+            t.yale = t
+                .reading
+                .as_deref()
+                .and_then(|r| jyutping_to_yale_vec(r, Style::HighFalling));
+        }
+    }
+    let system = options.romanizer();
+    if system.name() != "jyutping" {
+        for t in &mut tokens {
+            t.romanized = t.reading.as_deref().map(|r| romanize::romanize(r, system));
+        }
+    }
+    for t in tokens.iter_mut().filter(|t| t.proper_noun == Some(true)) {
+        for s in t.yale.iter_mut().chain(&mut t.romanized).flatten() {
+            *s = capitalize(s);
+        }
+    }
+    if options.nfd {
+        for t in &mut tokens {
+            for s in t.yale.iter_mut().chain(&mut t.romanized).flatten() {
+                *s = options.unicode_form(std::mem::take(s));
+            }
+        }
+    }
+    tokens
+}
+
+/// `annotate_text` through the segmentation cache.
+fn annotate_cached(trie: &Trie, text: &str, options: &Options) -> Vec<Token> {
+    if !options.cache {
+        return annotate_text_with(trie, text, options);
+    }
+    let key = options.cache_key(text);
+    if let Some(tokens) = CACHE.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        return tokens;
+    }
+    let tokens = annotate_text_with(trie, text, options);
+    CACHE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(key, tokens.clone());
+    tokens
+}
+
+#[wasm_func]
+pub fn annotate(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let options = Options::default();
+    let output = finish_tokens(annotate_cached(&trie(), text, &options), &options);
+
+    serde_json::to_string(&output)
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Like `annotate`, with a JSON object of options (see `options.rs`),
+/// e.g. b"{\"cache\": false}". Empty or invalid options give the defaults.
+/// Output: JSON tokens, JSON rows for `"table"`, or plain text for the
+/// other `format`s.
+#[wasm_func]
+pub fn annotate_with(input: &[u8], options: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    render(text, &Options::parse(options)).into_bytes()
+}
+
+/// Like `annotate_with`, but reports bad input instead of falling back to
+/// empty text or the default options, including unknown option names and
+/// romanization systems.
+/// Output: JSON `{"ok": tokens, "error": null}` (for the plain-text
+/// `format`s, `ok` is the text as a string), or `{"ok": null, "error": {"code":
+/// "invalid_utf8" | "invalid_options", "message": ...}}`.
+#[wasm_func]
+pub fn annotate_checked(input: &[u8], options: &[u8]) -> Vec<u8> {
+    fn annotate(input: &[u8], options: &[u8]) -> Result<String, envelope::Error> {
+        let text = envelope::utf8(input)?;
+        let options = Options::try_parse(options)
+            .map_err(|e| envelope::Error::new(ErrorCode::InvalidOptions, e))?;
+        let output = render(text, &options);
+        Ok(match options.format {
+            OutputFormat::Json | OutputFormat::Table => output,
+            _ => serde_json::to_string(&output).unwrap_or_default(),
+        })
+    }
+    envelope(annotate(input, options))
+}
+
+/// Annotate `text` and render it in the output `format`.
+fn render(text: &str, options: &Options) -> String {
+    let output = finish_tokens(annotate_cached(&trie(), text, options), options);
+
+    match options.format {
+        OutputFormat::Json => output::tokens_to_json(&output, options),
+        OutputFormat::Interlinear => output::interlinear(&output, options),
+        OutputFormat::Bracketed => output::bracketed(&output, options),
+        OutputFormat::Ssml => output::ssml(&output),
+        OutputFormat::Html => output::html(&output, options),
+        OutputFormat::Table => output::table(&output, options),
+    }
+}
+
+/// Annotate text as an SSML document for text-to-speech engines, with an
+/// IPA `<phoneme>` tag around every word that has a reading. Same as
+/// `annotate_with` and `{"format": "ssml"}`.
+/// Output: e.g. b"<speak ...><phoneme alphabet=\"ipa\" ph=\"nei˩˧.hou˧˥\">你好</phoneme></speak>"
+#[wasm_func]
+pub fn to_ssml(input: &[u8]) -> Vec<u8> {
+    annotate_with(input, br#"{"format": "ssml"}"#)
+}
+
+/// Transcribe text into braille for accessible documents: every word with a
+/// reading becomes its Jyutping in braille cells, tone as a lower-cell
+/// digit (see romanize/braille.rs), words separated by blank cells.
+/// Output: e.g. "⠝⠑⠊⠢⠀⠓⠕⠥⠆" for 你好
+#[wasm_func]
+pub fn annotate_braille(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let options = Options::default();
+    let output = finish_tokens(annotate_cached(&trie(), text, &options), &options);
+    output::braille(&output).into_bytes()
+}
+
+/// Input: JSON array of strings, e.g. b"[\"今日\", \"我要上堂\"]"
+/// Output: JSON array with one `annotate` token array per input string.
+/// Saves one plugin call per fragment when a document annotates many short
+/// pieces of text.
+#[wasm_func]
+pub fn annotate_batch(input: &[u8]) -> Vec<u8> {
+    let texts: Vec<String> = serde_json::from_slice(input).unwrap_or_default();
+    let trie = trie();
+    let output: Vec<Vec<Token>> = texts
+        .iter()
+        .map(|text| {
+            let options = Options::default();
+            finish_tokens(annotate_cached(&trie, text, &options), &options)
+        })
+        .collect();
+
+    serde_json::to_string(&output)
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Report what the dictionary does not cover in a text.
+/// Output: JSON array of `{"text", "kind", "count", "positions"}` for every
+/// CJK character (`kind: "cjk"`) or alpha run (`kind: "alpha"`) that got no
+/// reading, most frequent first; `positions` are character offsets.
+#[wasm_func]
+pub fn analyze_coverage(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let tokens = trie().segment(text);
+
+    serde_json::to_string(&analysis::coverage(&tokens))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Vocabulary list of a text, e.g. for a chapter glossary.
+/// Output: JSON array of `{"word", "jyutping", "yale", "freq", "count"}` for
+/// each distinct word with a reading, sorted by in-text `count`, then by
+/// corpus `freq` (from freq.txt), both descending.
+#[wasm_func]
+pub fn vocabulary(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let trie = trie();
+    let tokens = annotate_text(&trie, text);
+
+    serde_json::to_string(&analysis::vocabulary(&tokens, |w| trie.freq(w)))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Flashcards for the words of a text, as tab-separated rows for Anki:
+/// `word, jyutping, yale, gloss, example` — one row per distinct word with a
+/// reading, in order of first appearance. `gloss` is filled in for words
+/// from a CEDICT dictionary loaded with `load_dictionary`; `example` is the
+/// first sentence of the text that uses the word.
+#[wasm_func]
+pub fn export_flashcards(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let tokens = annotate_text(&trie(), text);
+    let glosses = GLOSSES.read().unwrap_or_else(|e| e.into_inner());
+
+    let cards = export::flashcards(text, &tokens, |w| glosses.get(w).cloned());
+    export::to_tsv(&cards).into_bytes()
+}
+
+/// Like `export_flashcards`, in Pleco's flashcard import format:
+/// `word<TAB>jyutping<TAB>definition` lines, the definition being the gloss
+/// if one is loaded, else the example sentence. A non-empty `category` adds
+/// a `// category` header so that Pleco files the cards under it.
+#[wasm_func]
+pub fn export_pleco(input: &[u8], category: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let category = std::str::from_utf8(category).unwrap_or("").trim();
+    let tokens = annotate_text(&trie(), text);
+    let glosses = GLOSSES.read().unwrap_or_else(|e| e.into_inner());
+
+    let cards = export::flashcards(text, &tokens, |w| glosses.get(w).cloned());
+    export::to_pleco(&cards, (!category.is_empty()).then_some(category)).into_bytes()
+}
+
+/// Level of `word`: from the loaded level list if the word is listed; else,
+/// if every character is listed (character tiers such as 常用字表), the
+/// hardest of those; else from its corpus frequency.
+fn word_level(trie: &Trie, levels: &HashMap<String, u8>, word: &str) -> u8 {
+    if let Some(&level) = levels.get(word) {
+        return level;
+    }
+    let char_levels: Option<Vec<u8>> = word
+        .chars()
+        .map(|c| levels.get(&c.to_string()).copied())
+        .collect();
+    match char_levels.and_then(|l| l.into_iter().max()) {
+        Some(level) => level,
+        None => readability::freq_level(trie.freq(word)),
+    }
+}
+
+/// Load a learner level list, e.g. 常用字表 tiers, replacing any earlier one.
+/// Input: `word<TAB>level` lines (words or single characters, level 1 =
+/// easiest). Output: number of entries loaded, e.g. b"3500"
+#[wasm_func]
+pub fn load_levels(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+    let levels: HashMap<String, u8> = text
+        .lines()
+        .filter_map(|line| {
+            let (word, level) = line.split_once('\t')?;
+            Some((word.to_string(), level.trim().parse().ok()?))
+        })
+        .collect();
+    let count = levels.len();
+    *LEVELS.write().unwrap_or_else(|e| e.into_inner()) = levels;
+    Ok(count.to_string().into_bytes())
+}
+
+/// Estimate how hard a passage is for a learner at `max_level` (options,
+/// default 2). Levels come from `load_levels` if loaded, else from corpus
+/// frequency (level 1 ≈ the 2,500 most common words, up to level 4).
+/// Output: JSON `{"words", "max_level", "within_level", "mean_level",
+/// "flagged": [{"word", "jyutping", "level", "positions"}]}`
+#[wasm_func]
+pub fn readability(input: &[u8], options: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let options = Options::parse(options);
+    let trie = trie();
+    let levels = LEVELS.read().unwrap_or_else(|e| e.into_inner());
+    let overlay = trie.overlay(&options.overrides);
+    let tokens = trie.segment_with(
+        text,
+        options.normalization,
+        options.scoring,
+        options.matching(),
+        overlay.as_ref(),
+    );
+
+    let report = readability::assess(
+        &tokens,
+        |w| word_level(&trie, &levels, w),
+        options.max_level,
+    );
+    serde_json::to_string(&report)
+        .unwrap_or_else(|_| "{}".to_string())
+        .into_bytes()
+}
+
+/// Rhyme scheme of verse or Cantopop lyrics: each non-blank line is
+/// segmented and its last syllable grouped by final, and by tone under the
+/// `rhyme_tones` option ("ignore", "class" or "exact").
+/// Output: JSON `{"lines": [{"line", "text", "syllable", "rime", "label"}],
+/// "scheme": "ABCB"}`, with "-" in `scheme` for lines without a reading.
+#[wasm_func]
+pub fn analyze_rhyme(input: &[u8], options: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let options = Options::parse(options);
+    let trie = trie();
+    let overlay = trie.overlay(&options.overrides);
+    let report = rhyme::analyze(text, options.rhyme_tones, |line| {
+        trie.segment_with(
+            line,
+            options.normalization,
+            options.scoring,
+            options.matching(),
+            overlay.as_ref(),
+        )
+    });
+    serde_json::to_string(&report)
+        .unwrap_or_else(|_| "{}".to_string())
+        .into_bytes()
+}
+
+/// Split text into sentences at Chinese and ASCII sentence-final punctuation
+/// (。！？… !? and newlines), keeping closing quotes and brackets with the
+/// sentence they close.
+/// Output: JSON array of `{"start", "end", "text"}`, offsets in characters,
+/// e.g. b"[{\"start\":0,\"end\":3,\"text\":\"你好！\"}]"
+#[wasm_func]
+pub fn split_sentences(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let mut offset = 0;
+    let output: Vec<serde_json::Value> = sentence::sentence_spans(text)
+        .into_iter()
+        .map(|span| {
+            let sentence = &text[span];
+            let start = offset;
+            offset += sentence.chars().count();
+            serde_json::json!({ "start": start, "end": offset, "text": sentence })
+        })
+        .collect();
+
+    serde_json::to_string(&output)
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Characters per `annotate_stream` batch; only one batch's DP tables and
+/// tokens are alive at a time.
+const STREAM_CHUNK_CHARS: usize = 4096;
+
+/// Annotate a large input chunk by chunk, cutting only at sentence
+/// boundaries, so that peak memory stays bounded by the chunk size.
+/// Output: newline-delimited JSON — one `annotate`-style token array per
+/// line. Concatenating all lines' arrays gives the same tokens as `annotate`,
+/// except that no word spans a sentence boundary.
+#[wasm_func]
+pub fn annotate_stream(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let trie = trie();
+    let mut output = Vec::new();
+    for chunk in sentence::chunks(text, STREAM_CHUNK_CHARS) {
+        let tokens = annotate_text(&trie, chunk);
+        if serde_json::to_writer(&mut output, &tokens).is_ok() {
+            output.push(b'\n');
+        }
+    }
+    output
+}
+
+/// Register a document for incremental re-annotation.
+/// Input: the full document text
+/// Output: JSON `{"id": 1, "tokens": [...]}`; pass the id to `edit_document`.
+#[wasm_func]
+pub fn open_document(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let trie = trie();
+    let doc = Document::new(text, |t| annotate_text(&trie, t));
+    let id = NEXT_DOCUMENT_ID.fetch_add(1, Ordering::Relaxed);
+
+    let output = serde_json::json!({ "id": id, "tokens": doc.tokens() });
+    DOCUMENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(id, doc);
+    output.to_string().into_bytes()
+}
+
+/// Apply an edit to a registered document and re-segment only around it.
+/// Input: document id, e.g. b"1", and a JSON edit in character offsets,
+/// e.g. b"{\"start\": 2, \"end\": 3, \"text\": \"大\"}"
+/// Output: JSON `{"first": i, "removed": k, "tokens": [...]}` — replace the
+/// `k` tokens starting at index `i` with `tokens`. An edit whose start is
+/// after its end, or whose end is past the end of the text, is an error.
+#[wasm_func]
+pub fn edit_document(id: &[u8], edit: &[u8]) -> Result<Vec<u8>, String> {
+    let id = parse_document_id(id)?;
+    let edit: Edit = serde_json::from_slice(edit).map_err(|e| e.to_string())?;
+    let trie = trie();
+    let mut documents = DOCUMENTS.lock().unwrap_or_else(|e| e.into_inner());
+    let doc = documents
+        .get_mut(&id)
+        .ok_or_else(|| format!("unknown document id: {}", id))?;
+
+    let patch = doc.apply(&edit, |t| annotate_text(&trie, t))?;
+    serde_json::to_vec(&patch).map_err(|e| e.to_string())
+}
+
+/// Romanize a registered document in another system without segmenting
+/// it again, for hosts that show several romanization rows (Yale, IPA,
+/// Sidney Lau, ...) over one segmentation.
+/// Input: document id, e.g. b"1", and a system name as for `romanize`,
+/// e.g. b"ipa"
+/// Output: JSON array in step with the document's tokens: each token's
+/// syllables in that system, capitalized for proper nouns as in
+/// `romanized`, or null for a token without a reading
+#[wasm_func]
+pub fn romanize_document(id: &[u8], system: &[u8]) -> Result<Vec<u8>, String> {
+    let id = parse_document_id(id)?;
+    let system = romanizer(system)?;
+    let documents = DOCUMENTS.lock().unwrap_or_else(|e| e.into_inner());
+    let doc = documents
+        .get(&id)
+        .ok_or_else(|| format!("unknown document id: {}", id))?;
+
+    let rows: Vec<Option<Vec<String>>> = doc
+        .tokens()
+        .iter()
+        .map(|t| {
+            let mut syllables = romanize::romanize(t.reading.as_deref()?, system);
+            if t.proper_noun == Some(true) {
+                for s in &mut syllables {
+                    *s = capitalize(s);
+                }
+            }
+            Some(syllables)
+        })
+        .collect();
+    serde_json::to_vec(&rows).map_err(|e| e.to_string())
+}
+
+/// Forget a registered document. Input: document id. Output: empty.
+#[wasm_func]
+pub fn close_document(id: &[u8]) -> Result<Vec<u8>, String> {
+    let id = parse_document_id(id)?;
+    DOCUMENTS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(&id)
+        .ok_or_else(|| format!("unknown document id: {}", id))?;
+    Ok(Vec::new())
+}
+
+fn parse_document_id(id: &[u8]) -> Result<u32, String> {
+    std::str::from_utf8(id)
+        .ok()
+        .and_then(|s| s.trim().parse().ok())
+        .ok_or_else(|| format!("invalid document id: {:?}", String::from_utf8_lossy(id)))
+}
+
+/// Load extra dictionary entries into the global trie at runtime.
+/// `format` is b"tsv" (`word<TAB>jyutping`, as in words.tsv/lettered.tsv),
+/// b"cedict" (`traditional simplified [pinyin] {jyutping} /gloss/`),
+/// b"emoji" (`emoji<TAB>jyutping`, read only for emoji the dictionary has
+/// no reading for, whatever their skin tone), b"names" (`name<TAB>jyutping`,
+/// like tsv, but tokens of these words are proper nouns, romanized with
+/// capitals), b"unihan" (Unihan lines: kCantonese from Unihan_Readings.txt,
+/// read as at build time for characters with no reading, and kRSUnicode and
+/// kTotalStrokes from Unihan_IRGSources.txt, for the `strokes` option;
+/// other lines are skipped) or b"ids" (`U+597D<TAB>好<TAB>⿰女子` lines, as in the CHISE
+/// and cjkvi-ids files, for `decompose`).
+/// Loaded readings rank after the bundled ones for the same word.
+/// Output: number of entries loaded, e.g. b"42"
+#[wasm_func]
+pub fn load_dictionary(format: &[u8], data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+    let mut trie = TRIE.write().unwrap_or_else(|e| e.into_inner());
+    let mut count = 0;
+
+    match format {
+        b"tsv" => {
+            for line in text.lines() {
+                // a weight in a third field, as in words.tsv, is ignored:
+                // runtime readings always rank after the bundled ones
+                let mut fields = line.split('\t');
+                let (Some(word), Some(reading)) = (fields.next(), fields.next()) else {
+                    continue;
+                };
+                trie.insert(word, reading.trim());
+                count += 1;
+            }
+        }
+        b"cedict" => {
+            let mut glosses = GLOSSES.write().unwrap_or_else(|e| e.into_inner());
+            for entry in text.lines().filter_map(cedict::parse_line) {
+                trie.insert(entry.traditional, entry.jyutping);
+                if entry.simplified != entry.traditional {
+                    trie.insert(entry.simplified, entry.jyutping);
+                }
+                if let Some(gloss) = entry.gloss {
+                    let gloss = gloss.replace('/', "; ");
+                    glosses.insert(entry.simplified.to_string(), gloss.clone());
+                    glosses.insert(entry.traditional.to_string(), gloss);
+                }
+                count += 1;
+            }
+        }
+        b"emoji" => count = trie.emoji.load(text),
+        b"names" => {
+            for line in text.lines() {
+                let Some((name, reading)) = line.split_once('\t') else {
+                    continue;
+                };
+                trie.insert_name(name.trim(), reading.trim());
+                count += 1;
+            }
+        }
+        b"unihan" => {
+            count = unihan::load(&mut trie.radical_strokes, text);
+            // as at build time, kCantonese only backfills characters with
+            // no reading at all
+            for (ch, readings) in text.lines().filter_map(unihan::parse_cantonese_line) {
+                let known = trie
+                    .child(trie.root(), ch)
+                    .is_some_and(|n| n.readings().next().is_some());
+                if !known {
+                    for reading in readings {
+                        trie.insert(&ch.to_string(), reading);
+                    }
+                    count += 1;
+                }
+            }
+        }
+        b"ids" => count = ids::load(&mut trie.ids, text),
+        _ => {
+            return Err(format!(
+                "unknown dictionary format: {:?}",
+                String::from_utf8_lossy(format)
+            ));
+        }
+    }
+
+    // cached results may be stale now that the trie has changed
+    drop(trie);
+    clear_cached_tokens();
+
+    DICTIONARIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(LoadedDictionary {
+            format: String::from_utf8_lossy(format).into_owned(),
+            entries: count,
+        });
+    Ok(count.to_string().into_bytes())
+}
+
+/// Load word pair counts for the "bigram" scoring option, replacing any
+/// earlier ones. Input: `word1<TAB>word2<TAB>count` lines, e.g. counted
+/// from a segmented corpus; words are matched as written in the text.
+/// Output: number of distinct pairs loaded, e.g. b"120000"
+#[wasm_func]
+pub fn load_bigrams(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+    let bigrams = bigram::Bigrams::parse(text);
+    let count = bigrams.len();
+    TRIE.write().unwrap_or_else(|e| e.into_inner()).bigrams = bigrams;
+    clear_cached_tokens();
+    Ok(count.to_string().into_bytes())
+}
+
+/// Dump the dictionary as it stands, runtime loads included, to audit what
+/// will match. `tier` is b"all", b"chars" (single characters), b"words"
+/// (words of characters), b"lettered" (entries with letters, digits or
+/// symbols), b"names" or b"emoji"; see dump.rs. Per-call `overrides` are
+/// not part of it.
+/// Output: `word<TAB>jyutping` lines, one per reading, most common first,
+/// with a `<TAB>3%` weight where the data gives one; loadable again with
+/// `load_dictionary(b"tsv", ...)` (or b"names", b"emoji")
+#[wasm_func]
+pub fn dump_dictionary(tier: &[u8]) -> Result<Vec<u8>, String> {
+    let name = String::from_utf8_lossy(tier);
+    let tier = dump::Tier::parse(&name).ok_or_else(|| format!("unknown tier: {:?}", name))?;
+    Ok(dump::dump(&trie(), tier).into_bytes())
+}
+
+/// Count word frequencies in a corpus of the user's (news, chat,
+/// subtitles, ...) to tune the frequency tie-breaker to it: the text is
+/// segmented and each dictionary word counted.
+/// Output: `word<TAB>count` lines, most frequent first, in the format of
+/// freq.txt and `load_frequencies`.
+#[wasm_func]
+pub fn count_frequencies(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let tokens = trie().segment(text);
+    let mut out = String::new();
+    for (word, count) in analysis::frequencies(&tokens) {
+        out.push_str(&format!("{}\t{}\n", word, count));
+    }
+    out.into_bytes()
+}
+
+/// Replace the corpus frequencies (freq.txt) used to break ties and by
+/// the `freq` option, e.g. with a `count_frequencies` table. Input:
+/// `word<TAB>count` lines; dictionary words not listed get 0, and listed
+/// words not in the dictionary are skipped.
+/// Output: number of words set, e.g. b"5000"
+#[wasm_func]
+pub fn load_frequencies(data: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(data).map_err(|e| e.to_string())?;
+    let table = analysis::parse_frequencies(text);
+    let count = TRIE
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace_freqs(&table);
+    clear_cached_tokens();
+    Ok(count.to_string().into_bytes())
+}
+
+/// What this build offers, so that Typst packages can check for features
+/// across plugin versions.
+/// Output: JSON `{"schema_version": 1, "version": "0.3.4", "features": [...],
+/// "romanizers": ["jyutping", ...], "dictionaries": [{"format": "cedict",
+/// "entries": 42}], "bigrams": 0, "options": {...}}`, where `options` holds
+/// every `annotate_with` option with its default value. `schema_version`
+/// goes up when an output changes shape incompatibly.
+#[wasm_func]
+pub fn get_capabilities() -> Vec<u8> {
+    let dictionaries = DICTIONARIES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    let bigrams = trie().bigrams.len();
+    serde_json::to_vec(&Capabilities::new(dictionaries, bigrams)).unwrap_or_default()
+}
+
+/// Reassemble text from tokens, e.g. after editing some of them.
+/// Input: a JSON token array as `annotate` or `annotate_with` return it
+/// (objects with a `word`, or compact `[word, ...]` arrays)
+/// Output: the words concatenated. For unedited tokens this is exactly the
+/// annotated text.
+#[wasm_func]
+pub fn detokenize(input: &[u8]) -> Result<Vec<u8>, String> {
+    let tokens: Vec<serde_json::Value> =
+        serde_json::from_slice(input).map_err(|e| e.to_string())?;
+    let mut text = String::new();
+    for (i, token) in tokens.iter().enumerate() {
+        let word = match token {
+            serde_json::Value::Array(fields) => fields.first(),
+            token => token.get("word"),
+        };
+        let word = word
+            .and_then(|w| w.as_str())
+            .ok_or_else(|| format!("token {} has no word", i))?;
+        text.push_str(word);
+    }
+    Ok(text.into_bytes())
+}
+
+/// Compare the annotations of a text under two sets of options, e.g.
+/// before and after adding `overrides`, to review what a data change would
+/// do to teaching materials.
+/// Output: JSON `[{"start", "end", "text", "before": [{"word", "jyutping"}],
+/// "after": [...]}]`, one entry per stretch of text (character offsets)
+/// whose words or readings differ.
+#[wasm_func]
+pub fn diff_annotations(input: &[u8], options_a: &[u8], options_b: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let trie = trie();
+    let run = |options: &[u8]| {
+        let options = Options::parse(options);
+        finish_tokens(annotate_cached(&trie, text, &options), &options)
+    };
+    serde_json::to_string(&diff::diff(&run(options_a), &run(options_b)))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Token boundaries only, for hosts that need the tokenization but not the
+/// readings: no readings or Yale are produced, so this is much cheaper than
+/// `annotate`.
+/// Output: JSON array of character offsets, 0 and then the end of each
+/// token, e.g. `[0, 1, 3]` for 好學生 (好 | 學生).
+#[wasm_func]
+pub fn segment_boundaries(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&trie().boundaries(text))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Every dictionary word found in a text, overlapping or not, for running
+/// another decoder or showing where the segmentation is ambiguous.
+/// Output: JSON `[{"start", "end", "word", "jyutping", "freq"}]` by start,
+/// then end; offsets are in characters.
+#[wasm_func]
+pub fn lattice(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&trie().lattice(text))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Explain how a span of text was segmented, for "wrong segmentation"
+/// reports: the dictionary paths walked from each start, the entries
+/// matched with their frequencies, the best candidate segmentations with
+/// their scores under the `scoring` option, and why the chosen one won.
+/// Takes the options of `annotate_with` (`scoring`, `normalization`,
+/// `max_word_length`, `connectors`, `overrides`). Keep the span short: at most 1000
+/// segmentations are compared, and `truncated` is true when there were more,
+/// as the runner-up in `paths` and `reason` may then not be the real one.
+/// Output: JSON `{"scoring", "explored": [{"start", "path"}], "matches":
+/// [{"start", "end", "word", "jyutping", "freq"}], "paths": [{"words",
+/// "tokens", "score", "chosen"}], "reason", "truncated"}`, offsets in
+/// characters.
+#[wasm_func]
+pub fn explain_segmentation(input: &[u8], options: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    let options = Options::parse(options);
+    let trie = trie();
+    let overlay = trie.overlay(&options.overrides);
+    let explanation = trie.explain(
+        text,
+        options.normalization,
+        options.scoring,
+        options.matching(),
+        overlay.as_ref(),
+    );
+    serde_json::to_vec(&explanation).unwrap_or_default()
+}
+
+/// Drop every cached segmentation result. Output: empty.
+#[wasm_func]
+pub fn clear_cache() -> Vec<u8> {
+    clear_cached_tokens();
+    Vec::new()
+}
+
+/// Problems build.rs found in the data files, as `file, line, severity,
+/// message` lines; debug builds only.
+#[cfg(debug_assertions)]
+const DATA_DIAGNOSTICS: &str = include_str!(concat!(env!("OUT_DIR"), "/diagnostics.tsv"));
+
+/// Debug builds only: lines of the bundled data that build.rs flagged.
+/// Errors fail the build, so these are warnings, such as syllables outside
+/// the standard Jyutping inventory.
+/// Output: JSON `[{"file": "words.tsv", "line": 21116, "severity": "warning",
+/// "message": "not a toned Jyutping syllable: \"pet6\""}, ...]`
+#[cfg(debug_assertions)]
+#[wasm_func]
+pub fn data_diagnostics() -> Vec<u8> {
+    let problems: Vec<serde_json::Value> = DATA_DIAGNOSTICS
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(4, '\t');
+            let file = fields.next()?;
+            let line: usize = fields.next()?.parse().ok()?;
+            let severity = fields.next()?;
+            let message = fields.next()?;
+            Some(serde_json::json!({
+                "file": file,
+                "line": line,
+                "severity": severity,
+                "message": message,
+            }))
+        })
+        .collect();
+    serde_json::to_vec(&problems).unwrap_or_default()
+}
+
+/// The ideographic description sequence of a character, for etymology
+/// sidebars: 好 → ⿰女子. Needs decomposition data (data/ids.txt at build
+/// time, or `load_dictionary(b"ids", ...)`).
+/// Input: one character, e.g. b"好"
+/// Output: e.g. "⿰女子"; the character itself if it has no components (一),
+/// and an error if it is not a single character or has no data.
+#[wasm_func]
+pub fn decompose(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    let mut chars = text.trim().chars();
+    let (Some(ch), None) = (chars.next(), chars.next()) else {
+        return Err(format!("not a single character: {:?}", text));
+    };
+    trie()
+        .ids
+        .get(&ch)
+        .map(|ids| ids.clone().into_bytes())
+        .ok_or_else(|| format!("no decomposition for {:?}", ch))
+}
+
+/// Split Jyutping typed without spaces into syllables, with or without tone
+/// digits. Whitespace, hyphens and apostrophes also separate syllables, and
+/// case is ignored.
+/// Input: e.g. b"gwong2dung1waa2" or b"GwongDungWaa"
+/// Output: e.g. b"gwong2 dung1 waa2" or b"gwong dung waa"; an error naming
+/// the first part that is not legal Jyutping.
+#[wasm_func]
+pub fn syllabify_jyutping(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    let text = text.to_lowercase();
+    let mut syllables = Vec::new();
+    for part in text.split(|c: char| c.is_whitespace() || c == '-' || c == '\'') {
+        match jyutping::syllabify(part) {
+            Some(s) => syllables.extend(s),
+            None => return Err(format!("not Jyutping: {:?}", part)),
+        }
+    }
+    Ok(syllables.join(" ").into_bytes())
+}
+
+/// Correct common nonstandard Jyutping spellings in learner input before
+/// conversion or lookup: Yale/Pinyin-style `y` and `ts`/`ch`/`dz` initials,
+/// `yu` written without `j`, `ue` for `yu`, and oe/eo mix-ups.
+/// Input: space-separated syllables, e.g. b"yuet6 tsi1"
+/// Output: JSON `{"jyutping": "jyut6 ci1", "corrections": [{"from", "to"}],
+/// "invalid": [...]}`, where `invalid` lists syllables left unrecognized.
+#[wasm_func]
+pub fn normalize_jyutping(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&jyutping::normalize(text))
+        .unwrap_or_else(|_| "{}".to_string())
+        .into_bytes()
+}
+
+/// Look up dictionary words by Jyutping without tones, for forgiving
+/// search boxes: b"si" finds 事, 時, 試, ...; unspaced queries such as
+/// b"neihou" are split into syllables.
+/// Options: JSON `{"aspiration": bool, "rounding": bool, "limit": 20}` to
+/// also ignore aspiration (p/b, t/d, k/g, c/z) or eo/oe.
+/// Output: JSON `[{"word", "jyutping", "freq"}]`, most frequent first.
+#[wasm_func]
+pub fn search_jyutping(input: &[u8], options: &[u8]) -> Vec<u8> {
+    let query = std::str::from_utf8(input).unwrap_or("");
+    let options = serde_json::from_slice(options).unwrap_or_default();
+    serde_json::to_string(&search::search(&trie(), query, &options))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Minimal pairs for pronunciation-drill worksheets: attested syllables
+/// that differ from the input in exactly one feature.
+/// Input: one syllable with a tone, e.g. b"si1"
+/// Output: JSON `[{"jyutping": "si2", "feature": "tone", "examples": ["史",
+/// ...]}]`, where `feature` is "tone", "initial" or "final" and `examples`
+/// are up to three characters read that way, most frequent first.
+#[wasm_func]
+pub fn minimal_pairs(input: &[u8]) -> Result<Vec<u8>, String> {
+    let syllable = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    let pairs = minimal_pairs::minimal_pairs(&trie(), syllable)?;
+    serde_json::to_string(&pairs)
+        .map(String::into_bytes)
+        .map_err(|e| e.to_string())
+}
+
+/// Check a learner's typed Jyutping, e.g. for grading romanization
+/// homework: every syllable is reported with its byte offsets, and illegal
+/// or toneless ones with up to five suggestions, closest first.
+/// Input: e.g. b"nei5hou2 yuet6"
+/// Output: JSON `[{"text", "start", "end", "valid", "suggestions"?}]`
+#[wasm_func]
+pub fn check_romanization(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&check::check(text))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Validate the Jyutping of a user dictionary before `load_dictionary`:
+/// syllables outside the inventory ("om"), with gw or kw before a front
+/// rounded final ("gweoi"), with a checked final in tone 4 or 5 ("sik4"), or without
+/// a tone are reported.
+/// Input: readings, one per line, or `word<TAB>reading` lines, e.g.
+/// b"食\tsik4"
+/// Output: JSON `[{"line", "syllable", "error"}]`, empty if all are legal
+#[wasm_func]
+pub fn validate_jyutping(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&check::validate(text))
+        .unwrap_or_else(|_| "[]".to_string())
+        .into_bytes()
+}
+
+/// Weighted phoneme edit distance between two Jyutping syllables, for fuzzy
+/// search and learner-error analysis: each added, dropped or replaced
+/// phoneme counts 1, a tone 0.5.
+/// Input: e.g. b"si1" and b"sik2"
+/// Output: the distance as a JSON number, e.g. "1.5"
+#[wasm_func]
+pub fn syllable_distance(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let a = std::str::from_utf8(a).unwrap_or("").trim().to_lowercase();
+    let b = std::str::from_utf8(b).unwrap_or("").trim().to_lowercase();
+    distance::distance(&a, &b).to_string().into_bytes()
+}
+
+/// `syllable_distance` for space-separated readings, aligning whole
+/// syllables: b"nei5 hou2" and b"nei5 hou3" are "0.5" apart.
+#[wasm_func]
+pub fn word_distance(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let a = std::str::from_utf8(a).unwrap_or("").to_lowercase();
+    let b = std::str::from_utf8(b).unwrap_or("").to_lowercase();
+    distance::word_distance(&a, &b).to_string().into_bytes()
+}
+
+/// Guess the romanization system of a text, so that converters can be
+/// chained when the source system is unknown.
+/// Output: JSON `{"system": "jyutping" | "yale" | "cantonese_pinyin" |
+/// "sidney_lau" | null, "scores": {...}}`, with each system's evidence
+/// score; `system` is null when nothing looks like romanized Cantonese.
+#[wasm_func]
+pub fn detect_romanization(input: &[u8]) -> Vec<u8> {
+    let text = std::str::from_utf8(input).unwrap_or("");
+    serde_json::to_string(&detect::detect(text))
+        .unwrap_or_else(|_| "{}".to_string())
+        .into_bytes()
+}
+
+/// Spell Jyutping in another romanization system, chosen by name:
+/// "jyutping", "jyutping_diacritics", "yale", "yale_numeric", "yale_plain",
+/// "cantonese_pinyin", "sidney_lau", "guangdong", "hk_government", "ipa",
+/// "xsampa", "espeak", "braille", "chao" or "chao_letters".
+/// Input: jyutping bytes, e.g. b"gwong2 dung1 waa2", and b"ipa"
+/// Output: e.g. "kʷɔːŋ˧˥ toŋ˥ waː˧˥"; punctuation and syllables that are
+/// not valid Jyutping are copied through.
+#[wasm_func]
+pub fn romanize(input: &[u8], system: &[u8]) -> Result<Vec<u8>, String> {
+    let jp = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    let system = romanizer(system)?;
+    Ok(romanize::romanize_text(jp, system).into_bytes())
+}
+
+/// Read text in a named romanization system (see `romanize`) back into
+/// Jyutping. Fails on the first syllable that cannot be read, and for
+/// output-only systems such as "ipa" and "braille".
 #[wasm_func]
-pub fn annotate(input: &[u8]) -> Vec<u8> {
-    let text = std::str::from_utf8(input).unwrap_or("");
-    let tokens = TRIE.segment(text);
+pub fn to_jyutping(input: &[u8], system: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    let system = romanizer(system)?;
+    jyutping::to_jyutping(text, |s| system.parse(s)).map(String::into_bytes)
+}
 
-    let output: Vec<Token> = tokens
-        .into_iter()
-        .map(|t| Token {
-            word: t.word,
-            yale: t.reading.as_deref().and_then(jyutping_to_yale_vec),
-            reading: t.reading,
-        })
-        .collect();
+fn romanizer(name: &[u8]) -> Result<&'static dyn romanize::Romanizer, String> {
+    let name = std::str::from_utf8(name).map_err(|e| e.to_string())?;
+    romanize::by_name(name.trim()).ok_or_else(|| format!("unknown romanization: {:?}", name))
+}
 
-    serde_json::to_string(&output)
-        .unwrap_or_else(|_| "[]".to_string())
-        .into_bytes()
+/// Input: Cantonese Pinyin (教院式), e.g. b"dzoey2 sik9"
+/// Output: Jyutping, e.g. b"zeoi2 sik6"; an error naming the first syllable
+/// that could not be read.
+#[wasm_func]
+pub fn cantonese_pinyin_to_jyutping(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    jyutping::to_jyutping(text, romanize::cantonese_pinyin::parse).map(String::into_bytes)
+}
+
+/// Input: Sidney Lau romanization, e.g. b"sze1 hui3"
+/// Output: Jyutping, e.g. b"si1 heoi3"
+#[wasm_func]
+pub fn sidney_lau_to_jyutping(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    jyutping::to_jyutping(text, romanize::sidney_lau::parse).map(String::into_bytes)
+}
+
+/// Input: Yale with diacritics or tone numbers, e.g. b"hohk s\xc4\x81ang"
+/// Output: Jyutping, e.g. b"hok6 saang1"
+#[wasm_func]
+pub fn yale_to_jyutping(input: &[u8]) -> Result<Vec<u8>, String> {
+    let text = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+    jyutping::to_jyutping(text, romanize::yale::parse).map(String::into_bytes)
 }
 
 /// Input: jyutping bytes, e.g. b"gwong2 dung1 waa2"
@@ -56,15 +1190,82 @@ pub fn to_yale_diacritics(input: &[u8]) -> Vec<u8> {
     jyutping_to_yale(jp, true).unwrap_or_default().into_bytes()
 }
 
+/// Like `to_yale_numeric`, but reports bad input instead of copying it.
+/// Output: JSON `{"ok": "gwong2 dung1 waa2", "error": null}`, or `{"ok":
+/// null, "error": {"code": "invalid_utf8" | "invalid_jyutping", ...}}`.
+#[wasm_func]
+pub fn to_yale_numeric_checked(input: &[u8]) -> Vec<u8> {
+    yale_checked(input, false)
+}
+
+/// Like `to_yale_diacritics`, but reports bad input instead of copying it;
+/// see `to_yale_numeric_checked`.
+#[wasm_func]
+pub fn to_yale_diacritics_checked(input: &[u8]) -> Vec<u8> {
+    yale_checked(input, true)
+}
+
+fn yale_checked(input: &[u8], diacritics: bool) -> Vec<u8> {
+    envelope(envelope::utf8(input).and_then(|jp| {
+        try_jyutping_to_yale(jp, diacritics)
+            .map(|yale| serde_json::to_string(&yale).unwrap_or_default())
+            .map_err(|e| envelope::Error::new(ErrorCode::InvalidJyutping, e))
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// `annotate_with` output, parsed as JSON tokens.
+    fn annotated(text: &str, options: impl AsRef<[u8]>) -> Vec<serde_json::Value> {
+        serde_json::from_slice(&annotate_with(text.as_bytes(), options.as_ref())).unwrap()
+    }
+
+    /// `annotate_with` output in a text `format`.
+    fn rendered(text: &str, options: impl AsRef<[u8]>) -> String {
+        String::from_utf8(annotate_with(text.as_bytes(), options.as_ref())).unwrap()
+    }
+
+    /// Held by the tests that load data into the globals, as the tests run
+    /// in parallel.
+    static GLOBALS: Mutex<()> = Mutex::new(());
+
+    /// Sole use of the globals; on drop, the bundled data is put back and
+    /// whatever was loaded forgotten, so that no test sees another's loads.
+    struct Globals {
+        _lock: std::sync::MutexGuard<'static, ()>,
+    }
+
+    fn globals() -> Globals {
+        Globals {
+            _lock: GLOBALS.lock().unwrap_or_else(|e| e.into_inner()),
+        }
+    }
+
+    impl Drop for Globals {
+        fn drop(&mut self) {
+            *TRIE.write().unwrap_or_else(|e| e.into_inner()) = build_trie();
+            GLOSSES.write().unwrap_or_else(|e| e.into_inner()).clear();
+            DICTIONARIES
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clear();
+            clear_cached_tokens();
+        }
+    }
+
+    // expectations assume the full bundled data set
     #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
     fn test_segmentation() {
+        type SegmentCase = (
+            &'static str,
+            &'static [(&'static str, Option<&'static str>)],
+        );
         let trie = build_trie();
 
-        let cases: &'static [(&str, &'static [(&str, Option<&str>)])] = &[
+        let cases: &[SegmentCase] = &[
             // --- basic CJK ---
             (
                 "佢係好學生",
@@ -103,6 +1304,8 @@ mod tests {
                     ("二", Some("ji6")),
                 ],
             ),
+            // --- Extension G ideograph is not swallowed by an alpha run ---
+            ("abc\u{30000}", &[("abc", None), ("\u{30000}", None)]),
             // --- accented letter in alpha run ---
             (
                 "café好",
@@ -121,6 +1324,45 @@ mod tests {
                 "Hap唔Happy呀",
                 &[("Hap唔Happy呀", Some("hep1 m4 hep1 pi2 aa3"))],
             ),
+            // --- full-width forms hit lettered entries, word text is kept ---
+            (
+                "ＡＢ膠３％",
+                &[
+                    ("ＡＢ膠", Some("ei1 bi1 gaau1")),
+                    ("３", None),
+                    ("％", Some("pat6 sen1")),
+                ],
+            ),
+            // --- typographic hyphens hit hyphenated entries ---
+            (
+                "做part\u{2011}time",
+                &[("做part\u{2011}time", Some("zou6 paat1 taai1"))],
+            ),
+            // --- full-width punctuation inside a words.tsv entry ---
+            (
+                "笑左，笑埋右",
+                &[("笑左，笑埋右", Some("siu3 zo2 siu3 maai4 jau6"))],
+            ),
+            // --- decomposed input is NFC-normalized for lookup only ---
+            (
+                "cafe\u{0301}好",
+                &[("cafe\u{0301}", Some("kat6 fei1")), ("好", Some("hou2"))],
+            ),
+            // --- emoji ZWJ sequences, flags and combining marks stay whole ---
+            (
+                "👨\u{200D}👩\u{200D}👧好🇭🇰",
+                &[
+                    ("👨\u{200D}👩\u{200D}👧", None),
+                    ("好", Some("hou2")),
+                    ("🇭🇰", None),
+                ],
+            ),
+            (
+                "nai\u{0308}ve 佢",
+                &[("nai\u{0308}ve", None), (" ", None), ("佢", Some("keoi5"))],
+            ),
+            // --- CJK + variation selector keeps the base reading ---
+            ("佢\u{FE00}", &[("佢\u{FE00}", Some("keoi5"))]),
             // --- newline becomes its own token ---
             (
                 "你好\n世界",
@@ -164,4 +1406,1174 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_normalization_option() {
+        let words = |text: &str, options: &[u8]| -> Vec<(String, Option<String>)> {
+            annotated(text, options)
+                .iter()
+                .map(|t| {
+                    (
+                        t["word"].as_str().unwrap().to_string(),
+                        t["jyutping"].as_str().map(String::from),
+                    )
+                })
+                .collect()
+        };
+        // ③ (U+2462) only becomes "3" under NFKC; the word keeps the original
+        let nfc = words("③%", b"{}");
+        let nfkc = words("③%", b"{\"normalization\": \"nfkc\"}");
+        assert_eq!(nfc[0], ("③".to_string(), None));
+        assert_eq!(nfkc[0].0, "③");
+        assert_eq!(nfkc[1], ("%".to_string(), Some("pat6 sen1".to_string())));
+
+        // no normalization: decomposed input misses the lettered entry
+        let none = words("cafe\u{0301}", b"{\"normalization\": \"none\"}");
+        assert_eq!(none, [("cafe\u{0301}".to_string(), None)]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_sandhi() {
+        assert!(
+            annotated("阿陳同哥哥講", "{}")
+                .iter()
+                .all(|t| t.get("surface_tones").is_none())
+        );
+        let changed = annotated("阿陳同哥哥講", r#"{"sandhi": ["kinship", "familiar"]}"#);
+        let surface: Vec<_> = changed
+            .iter()
+            .filter(|t| t.get("surface_tones").is_some())
+            .map(|t| {
+                (
+                    t["word"].clone(),
+                    t["underlying_tones"].clone(),
+                    t["surface_tones"].clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            surface,
+            [
+                (
+                    serde_json::json!("陳"),
+                    serde_json::json!([4]),
+                    serde_json::json!([2])
+                ),
+                (
+                    serde_json::json!("哥哥"),
+                    serde_json::json!([1, 1]),
+                    serde_json::json!([4, 1])
+                ),
+            ]
+        );
+        assert!(Options::try_parse(br#"{"sandhi": ["tone"]}"#).is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_merge_whitespace() {
+        let text = "你好\n\n  \t世界 ";
+        assert_eq!(annotated(text, "{}").len(), 9);
+        let merged = annotated(text, r#"{"merge_whitespace": true}"#);
+        let words: Vec<&str> = merged.iter().map(|t| t["word"].as_str().unwrap()).collect();
+        assert_eq!(words, ["你", "好", "\n\n  \t", "世界", " "]);
+        assert!(merged[2]["jyutping"].is_null());
+    }
+
+    #[test]
+    fn test_typographic_apostrophe() {
+        // Typst's smart quotes turn i'm into i’m
+        let tokens = annotated("i’m", r#"{"overrides": {"i'm": "aai1 m4"}}"#);
+        assert_eq!(tokens.len(), 1);
+        assert_eq!(tokens[0]["word"], "i’m");
+        assert_eq!(tokens[0]["jyutping"], "aai1 m4");
+
+        // with no entry, i’m is one alpha run as i'm is
+        let curly = annotated("i’m", "{}");
+        let straight = annotated("i'm", "{}");
+        assert_eq!(curly.len(), 1);
+        assert_eq!(curly[0]["word"], "i’m");
+        assert_eq!(curly[0]["jyutping"], straight[0]["jyutping"]);
+        assert_eq!(straight.len(), 1);
+    }
+
+    #[test]
+    fn test_connectors() {
+        let words = |options: &str| -> Vec<String> {
+            annotated("C++ Wi-Fi O’Brien v1.2.", options)
+                .iter()
+                .map(|t| t["word"].as_str().unwrap().to_string())
+                .filter(|w| w != " ")
+                .collect()
+        };
+        assert_eq!(
+            words("{}"),
+            ["C", "+", "+", "Wi-Fi", "O’Brien", "v1", ".", "2", "."]
+        );
+        assert_eq!(
+            words(r#"{"connectors": "-_'.+"}"#),
+            ["C++", "Wi-Fi", "O’Brien", "v1.2", "."]
+        );
+        assert_eq!(
+            words(r#"{"connectors": ""}"#),
+            [
+                "C", "+", "+", "Wi", "-", "Fi", "O", "’", "Brien", "v1", ".", "2", "."
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_max_word_length() {
+        let split = |text: &str, max: &str| -> String {
+            let options = format!(r#"{{"max_word_length": {}, "format": "bracketed"}}"#, max);
+            rendered(text, options)
+        };
+        assert_eq!(
+            split("香港中文大學", "null"),
+            "香港中文大學(hoeng1 gong2 zung1 man4 daai6 hok6)"
+        );
+        assert_eq!(
+            split("香港中文大學", "4"),
+            "香港(hoeng1 gong2)中文大學(zung1 man4 daai6 hok6)"
+        );
+        assert_eq!(
+            split("香港中文大學", "2"),
+            "香港(hoeng1 gong2)中文(zung1 man2)大學(daai6 hok6)"
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_scoring() {
+        let _globals = globals();
+        let split = |text: &str, scoring: &str| -> String {
+            let options = format!(r#"{{"scoring": "{}", "format": "bracketed"}}"#, scoring);
+            rendered(text, options)
+        };
+        assert_eq!(split("好學生", "fewest"), "好(hou2)學生(hok6 saang1)");
+        assert_eq!(split("好學生", "longest"), "好學(hou3 hok6)生(saang1)");
+        assert_eq!(split("好學生", "frequency"), "好(hou2)學生(hok6 saang1)");
+        // frequent words outweigh one rare long entry
+        assert_eq!(
+            split("香港中文大學", "fewest"),
+            "香港中文大學(hoeng1 gong2 zung1 man4 daai6 hok6)"
+        );
+        assert_eq!(
+            split("香港中文大學", "frequency"),
+            "香港(hoeng1 gong2)中文(zung1 man2)大學(daai6 hok6)"
+        );
+        // unigram probabilities undo a merge that token counting prefers
+        assert_eq!(
+            split("香港人口", "fewest"),
+            "香港人(hoeng1 gong2 jan4)口(hau2)"
+        );
+        assert_eq!(
+            split("香港人口", "unigram"),
+            "香港(hoeng1 gong2)人口(jan4 hau2)"
+        );
+        assert_eq!(
+            split("大學生活", "unigram"),
+            "大學(daai6 hok6)生活(sang1 wut6)"
+        );
+        // bigram counts (none loaded yet) settle what unigram gets wrong
+        assert_eq!(
+            split("睇一齊全", "bigram"),
+            "睇(tai2)一齊(jat1 cai4)全(cyun4)"
+        );
+        assert_eq!(
+            load_bigrams("睇\t一\t40\n一\t齊全\t5\n".as_bytes()).unwrap(),
+            b"2"
+        );
+        assert_eq!(
+            split("睇一齊全", "bigram"),
+            "睇(tai2)一(jat1)齊全(cai4 cyun4)"
+        );
+        assert_eq!(
+            split("大家一齊唱", "bigram"),
+            "大家(daai6 gaa1)一齊(jat1 cai4)唱(coeng3)"
+        );
+        load_bigrams(b"").unwrap();
+        // the cache keeps the policies apart
+        assert_eq!(
+            split("研究生命", "longest"),
+            "研究生(jin4 gau3 sang1)命(ming6)"
+        );
+        assert_eq!(
+            split("研究生命", "fewest"),
+            "研究(jin4 gau3)生命(sang1 ming6)"
+        );
+        // maximum matching, greedy from either end
+        assert_eq!(
+            split("研究生命", "forward"),
+            "研究生(jin4 gau3 sang1)命(ming6)"
+        );
+        assert_eq!(
+            split("研究生命", "backward"),
+            "研究(jin4 gau3)生命(sang1 ming6)"
+        );
+        assert_eq!(
+            split("好學生 part-time 3%", "forward"),
+            "好學(hou3 hok6)生(saang1) part-time(paat1 taai1) 3%(pat6 sen1)"
+        );
+        assert_eq!(
+            split("好學生 part-time 3%", "backward"),
+            "好(hou2)學生(hok6 saang1) part-time(paat1 taai1) 3%(pat6 sen1)"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_freq_options() {
+        // hidden by default
+        let plain = annotated("我囉唆，", "{}");
+        assert!(plain[0].get("freq").is_none());
+        assert!(plain[0].get("rare").is_none());
+
+        let flagged = annotated("我囉唆，", r#"{"freq": true, "rare_below": 1000}"#);
+        assert_eq!(flagged[0]["freq"], 12831921);
+        assert_eq!(flagged[0]["rare"], false);
+        assert_eq!(flagged[1]["word"], "囉唆");
+        assert_eq!(flagged[1]["rare"], true);
+        // punctuation has neither
+        assert!(flagged[2].get("freq").is_none());
+        assert!(flagged[2].get("rare").is_none());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_weights() {
+        assert!(annotated("阿好學生", "{}")[0].get("weight").is_none());
+
+        let weighted = annotated("阿好學生", r#"{"weights": true}"#);
+        // aa3 5%, o1 5%, aa2 0%, aak3 0%
+        assert_eq!(weighted[0]["jyutping"], "aa3");
+        assert_eq!(weighted[0]["weight"], 5);
+        assert_eq!(weighted[0]["confidence"], 0.5);
+        // hou2 has no percentage, hou3 5%
+        assert_eq!(weighted[1]["weight"], 100);
+        let confidence = weighted[1]["confidence"].as_f64().unwrap();
+        assert!((confidence - 100.0 / 105.0).abs() < 1e-9);
+        // words have neither
+        assert!(weighted[2].get("weight").is_none());
+        assert!(weighted[2].get("confidence").is_none());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_readings() {
+        assert!(annotated("阿好學生", "{}")[0].get("readings").is_none());
+
+        let listed = annotated("阿好學生", r#"{"readings": true}"#);
+        // by weight, then equal weights by reading: aa3 and o1 at 5%, aa2
+        // and aak3 at 0%
+        assert_eq!(
+            listed[0]["readings"],
+            serde_json::json!(["aa3", "o1", "aa2", "aak3"])
+        );
+        assert_eq!(listed[1]["readings"], serde_json::json!(["hou2", "hou3"]));
+        // a single reading is not listed
+        assert!(listed[2].get("readings").is_none());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_overrides() {
+        let bracketed = |text: &str, options: &str| -> String {
+            rendered(text, format!(r#"{{"format": "bracketed", {}}}"#, options))
+        };
+        let overrides =
+            r#""overrides": {"行": "hang4", "好學生": "hou2 hok6 saang1", "阿Q": "aa3 kiu1"}"#;
+        assert_eq!(bracketed("行", overrides), "行(hang4)");
+        // new words are segmented as words
+        assert_eq!(bracketed("好學生", overrides), "好學生(hou2 hok6 saang1)");
+        assert_eq!(bracketed("阿Q", overrides), "阿Q(aa3 kiu1)");
+        // words containing an overridden one keep their readings
+        assert_eq!(bracketed("銀行", overrides), "銀行(ngan4 hong4)");
+        // nothing leaks into other calls, cached or not
+        assert_eq!(bracketed("行", r#""cache": true"#), "行(haang4)");
+        assert_eq!(
+            bracketed("好學生", r#""cache": true"#),
+            "好(hou2)學生(hok6 saang1)"
+        );
+
+        // frequencies of known words are kept
+        let options = r#"{"freq": true, "overrides": {"學生": "hok6 sang1"}}"#;
+        let tokens = annotated("學生", options);
+        assert_eq!(tokens[0]["jyutping"], "hok6 sang1");
+        assert_eq!(tokens[0]["freq"], trie().freq("學生"));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_readings_at() {
+        let bracketed = |text: &str, readings_at: &str| -> String {
+            let options = format!(
+                r#"{{"format": "bracketed", "readings_at": {}}}"#,
+                readings_at
+            );
+            rendered(text, options)
+        };
+        // the same character, read two ways
+        assert_eq!(
+            bracketed("行，行", r#"{"2": "hang4"}"#),
+            "行(haang4)，行(hang4)"
+        );
+        // a syllable inside a word
+        assert_eq!(
+            bracketed("好學生", r#"{"2": "sang1"}"#),
+            "好(hou2)學生(hok6 sang1)"
+        );
+        // offsets count characters, not bytes or UTF-16 units
+        assert_eq!(bracketed("𠮩行", r#"{"1": "hang4"}"#), "𠮩(aa1)行(hang4)");
+        // words not read one syllable per character are left alone
+        assert_eq!(
+            bracketed("part-time", r#"{"0": "paa1"}"#),
+            "part-time(paat1 taai1)"
+        );
+        assert_eq!(bracketed("行", r#"{"5": "hang4"}"#), "行(haang4)");
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_mono_ruby() {
+        let words = |options: &[u8]| -> Vec<String> {
+            annotated("學生做part-time", options)
+                .iter()
+                .map(|t| format!("{}:{}:{}", t["word"], t["jyutping"], t["yale"]))
+                .map(|s| s.replace('"', ""))
+                .collect()
+        };
+        assert_eq!(
+            words(br#"{"ruby": "mono"}"#),
+            [
+                "學:hok6:[hohk]",
+                "生:saang1:[sāang]",
+                // not one syllable per character
+                "做part-time:zou6 paat1 taai1:[jouh,pāat,tāai]"
+            ]
+        );
+        assert_eq!(
+            words(br#"{"ruby": "group"}"#)[0],
+            "學生:hok6 saang1:[hohk,sāang]"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_dump_dictionary() {
+        let names = String::from_utf8(dump_dictionary(b"names").unwrap()).unwrap();
+        assert!(names.lines().any(|l| l == "銅鑼灣\ttung4 lo4 waan1"));
+        let chars = String::from_utf8(dump_dictionary(b"chars").unwrap()).unwrap();
+        assert!(chars.lines().any(|l| l.starts_with("行\thaang4")));
+        assert!(
+            chars
+                .lines()
+                .all(|l| l.split('\t').next().unwrap().chars().count() == 1)
+        );
+        assert!(dump_dictionary(b"cedict").is_err());
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_frequencies() {
+        let table = String::from_utf8(count_frequencies("學生，好學生。".as_bytes())).unwrap();
+        assert_eq!(table, "學生\t2\n好\t1\n");
+
+        // a recounted table decides the ties instead of freq.txt
+        let mut trie = Trie::default();
+        for (word, reading) in [
+            ("好", "hou2"),
+            ("好學", "hou3 hok6"),
+            ("學生", "hok6 saang1"),
+            ("生", "saang1"),
+        ] {
+            trie.insert(word, reading);
+        }
+        let words = |trie: &Trie| -> Vec<String> {
+            trie.segment("好學生").into_iter().map(|t| t.word).collect()
+        };
+        let table = analysis::parse_frequencies(&table);
+        assert_eq!(trie.replace_freqs(&table), 2);
+        assert_eq!(words(&trie), ["好", "學生"]);
+        assert_eq!(trie.replace_freqs(&[("好學".into(), 20)]), 1);
+        assert_eq!(trie.freq("學生"), 0);
+        assert_eq!(words(&trie), ["好學", "生"]);
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_inline_readings() {
+        let bracketed = |text: &str, options: &str| -> String {
+            rendered(text, format!(r#"{{"format": "bracketed", {}}}"#, options))
+        };
+        assert_eq!(
+            bracketed("行{hang4}，行", r#""inline_readings": true"#),
+            "行(hang4)，行(haang4)"
+        );
+        // inside a word, with readings_at counting the stripped text
+        assert_eq!(
+            bracketed(
+                "好學生{sang1}行",
+                r#""inline_readings": true, "readings_at": {"3": "hang4"}"#
+            ),
+            "好(hou2)學生(hok6 sang1)行(hang4)"
+        );
+        // off by default; braces around other text stay
+        assert_eq!(
+            bracketed("行{hang4}", r#""cache": false"#),
+            "行(haang4){hang4}"
+        );
+        assert_eq!(
+            bracketed("行{x}", r#""inline_readings": true"#),
+            "行(haang4){x}"
+        );
+    }
+
+    #[test]
+    fn test_markdown_input() {
+        let options = r#"{"input": "markdown", "format": "bracketed"}"#;
+        assert_eq!(
+            rendered("行`行`[行](行.md)", options),
+            "行(haang4)`行`[行(haang4)](行.md)"
+        );
+    }
+
+    #[test]
+    fn test_typst_input() {
+        let options = r#"{"input": "typst", "format": "bracketed"}"#;
+        assert_eq!(
+            rendered("#set par(行: 1)\n#strong[行]$行$", options),
+            "#set par(行: 1)\n#strong[行(haang4)]$行$"
+        );
+    }
+
+    #[test]
+    fn test_links() {
+        let tokens: Vec<serde_json::Value> = serde_json::from_slice(&annotate(
+            "睇https://x.io/a_b.html，@chan_tm：#TBT".as_bytes(),
+        ))
+        .unwrap();
+        let words: Vec<&str> = tokens.iter().map(|t| t["word"].as_str().unwrap()).collect();
+        assert_eq!(
+            words,
+            [
+                "睇",
+                "https://x.io/a_b.html",
+                "，",
+                "@chan_tm",
+                "：",
+                "#TBT"
+            ]
+        );
+        assert_eq!(tokens[1]["category"], "url");
+        assert!(tokens[1]["jyutping"].is_null());
+        assert_eq!(tokens[3]["category"], "mention");
+        assert!(tokens[0].get("category").is_none());
+    }
+
+    #[test]
+    fn test_numerals() {
+        let text = "第3章 Ⅱ XIV 第ⅩⅡ";
+        let bracketed = |options: &str| rendered(text, options);
+        assert_eq!(
+            bracketed(r#"{"format": "bracketed"}"#),
+            "第(dai6)3章(zoeng1) Ⅱ XIV 第(dai6)ⅩⅡ"
+        );
+        assert_eq!(
+            bracketed(r#"{"format": "bracketed", "numerals": true}"#),
+            "第3(dai6 saam1)章(zoeng1) Ⅱ(ji6) XIV(sap6 sei3) 第ⅩⅡ(dai6 sap6 ji6)"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_particles() {
+        let tokens = annotated("你畀啦！咁都得㗎啦喎。", r#"{"particles": true}"#);
+        let particles: Vec<&str> = tokens
+            .iter()
+            .filter(|t| t["particle"] == true)
+            .map(|t| t["word"].as_str().unwrap())
+            .collect();
+        assert_eq!(particles, ["啦", "㗎啦", "喎"]);
+        assert_eq!(tokens[1]["word"], "畀");
+        assert_eq!(tokens[1]["yale"], serde_json::json!(["béi"]));
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_lang() {
+        let tokens = annotated(
+            "我做part-time, package 好正！",
+            r#"{"lang": true, "known_words": ["做part-time"]}"#,
+        );
+        let langs: Vec<(&str, &serde_json::Value)> = tokens
+            .iter()
+            .map(|t| (t["word"].as_str().unwrap(), &t["lang"]))
+            .filter(|(_, lang)| !lang.is_null())
+            .collect();
+        assert_eq!(
+            langs,
+            [
+                ("我", &serde_json::json!("yue")),
+                ("做part-time", &serde_json::json!("yue")),
+                ("package", &serde_json::json!("en")),
+                ("好", &serde_json::json!("yue")),
+                ("正", &serde_json::json!("yue")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_html_input() {
+        let options = r#"{"input": "html", "format": "html"}"#;
+        assert_eq!(
+            rendered("<p title=\"行\">行&amp;</p>", options),
+            "<p title=\"行\"><ruby>行<rt>haang4</rt></ruby>&amp;</p>"
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_only_rare() {
+        let tokens = annotated("我囉唆", r#"{"rare_below": 1000, "only_rare": true}"#);
+        assert_eq!(tokens[0]["word"], "我");
+        assert!(tokens[0]["jyutping"].is_null());
+        assert!(tokens[0]["yale"].is_null());
+        assert_eq!(tokens[1]["jyutping"], "lo1 so1");
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_known_words() {
+        let hidden = annotated("我係學生", r#"{"known_words": ["我", "學生"]}"#);
+        assert!(hidden[0]["jyutping"].is_null());
+        assert_eq!(hidden[1]["jyutping"], "hai6");
+        assert!(hidden[2]["jyutping"].is_null());
+        assert!(hidden[0].get("known").is_none());
+
+        let flagged = annotated(
+            "我係學生",
+            r#"{"known_words": ["我"], "known_mode": "flag"}"#,
+        );
+        assert_eq!(flagged[0]["jyutping"], "ngo5");
+        assert_eq!(flagged[0]["known"], true);
+        assert!(flagged[1].get("known").is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_annotate_batch() {
+        let output = annotate_batch("[\"佢\", \"\", \"學生\"]".as_bytes());
+        let batch: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            batch,
+            serde_json::json!([
+                [{"word": "佢", "jyutping": "keoi5", "yale": ["kéuih"], "jyutping_syllables": ["keoi5"]}],
+                [],
+                [{
+                    "word": "學生",
+                    "jyutping": "hok6 saang1",
+                    "yale": ["hohk", "sāang"],
+                    "jyutping_syllables": ["hok6", "saang1"],
+                }],
+            ])
+        );
+        assert_eq!(annotate_batch(b"not json"), b"[]");
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_vocabulary() {
+        let output = vocabulary("學生話學生".as_bytes());
+        let vocab: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(vocab[0]["word"], "學生");
+        assert_eq!(vocab[0]["count"], 2);
+        assert_eq!(vocab[0]["freq"], 71278);
+        assert_eq!(vocab[1]["word"], "話");
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "small-freq", feature = "chars-only")))]
+    fn test_readability() {
+        let output = readability("我好囉唆".as_bytes(), b"{\"max_level\": 2}");
+        let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(report["max_level"], 2);
+        assert_eq!(report["flagged"][0]["word"], "囉唆");
+        assert_eq!(report["flagged"][0]["level"], 3);
+
+        // a loaded list takes precedence, per word or per character
+        let mut levels = HashMap::new();
+        levels.insert("囉".to_string(), 1);
+        levels.insert("唆".to_string(), 2);
+        assert_eq!(word_level(&trie(), &levels, "囉唆"), 2);
+        assert_eq!(word_level(&trie(), &levels, "我"), 1);
+    }
+
+    #[test]
+    fn test_split_sentences() {
+        let output = split_sentences("你好！「食咗飯未？」".as_bytes());
+        let sentences: serde_json::Value = serde_json::from_slice(&output).unwrap();
+        assert_eq!(
+            sentences,
+            serde_json::json!([
+                {"start": 0, "end": 3, "text": "你好！"},
+                {"start": 3, "end": 10, "text": "「食咗飯未？」"},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_annotate_stream() {
+        let output = annotate_stream("佢係好學生。你好\n".as_bytes());
+        let lines: Vec<serde_json::Value> = output
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| serde_json::from_slice(line).unwrap())
+            .collect();
+        // small input fits in a single batch
+        assert_eq!(lines.len(), 1);
+        let whole: serde_json::Value =
+            serde_json::from_slice(&annotate("佢係好學生。你好\n".as_bytes())).unwrap();
+        assert_eq!(lines[0], whole);
+        assert!(annotate_stream(b"").is_empty());
+    }
+
+    #[test]
+    fn test_document_edit() {
+        let opened: serde_json::Value =
+            serde_json::from_slice(&open_document("佢係好學生".as_bytes())).unwrap();
+        let id = opened["id"].to_string();
+        let mut tokens = opened["tokens"].as_array().unwrap().clone();
+
+        // 好 → 大: "大學生" has to be re-segmented as one word
+        let patch = edit_document(
+            id.as_bytes(),
+            "{\"start\": 2, \"end\": 3, \"text\": \"大\"}".as_bytes(),
+        )
+        .unwrap();
+        let patch: serde_json::Value = serde_json::from_slice(&patch).unwrap();
+        let first = patch["first"].as_u64().unwrap() as usize;
+        let removed = patch["removed"].as_u64().unwrap() as usize;
+        tokens.splice(
+            first..first + removed,
+            patch["tokens"].as_array().unwrap().iter().cloned(),
+        );
+
+        let full: serde_json::Value =
+            serde_json::from_slice(&annotate("佢係大學生".as_bytes())).unwrap();
+        assert_eq!(serde_json::Value::Array(tokens), full);
+
+        // a reversed range, or one past the end of the 5 characters
+        for edit in [
+            "{\"start\": 3, \"end\": 1, \"text\": \"大\"}",
+            "{\"start\": 4, \"end\": 6}",
+        ] {
+            assert!(edit_document(id.as_bytes(), edit.as_bytes()).is_err());
+        }
+
+        assert!(close_document(id.as_bytes()).is_ok());
+        assert!(close_document(id.as_bytes()).is_err());
+        assert!(edit_document(id.as_bytes(), b"{}").is_err());
+    }
+
+    #[test]
+    fn test_romanize_document() {
+        let opened: serde_json::Value =
+            serde_json::from_slice(&open_document("你好，沙田".as_bytes())).unwrap();
+        let id = opened["id"].to_string();
+        let rows = |system: &str| -> serde_json::Value {
+            serde_json::from_slice(&romanize_document(id.as_bytes(), system.as_bytes()).unwrap())
+                .unwrap()
+        };
+
+        // the same rows as annotating again with `romanization`
+        for system in ["yale", "ipa", "sidney_lau"] {
+            let options = format!(r#"{{"romanization": "{}"}}"#, system);
+            let tokens = annotated("你好，沙田", options);
+            let romanized: Vec<_> = tokens.iter().map(|t| t["romanized"].clone()).collect();
+            assert_eq!(
+                rows(system),
+                serde_json::Value::Array(romanized),
+                "{}",
+                system
+            );
+        }
+        let comma = opened["tokens"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .position(|t| t["word"] == "，")
+            .unwrap();
+        assert!(rows("yale")[comma].is_null());
+        assert!(romanize_document(id.as_bytes(), b"klingon").is_err());
+
+        close_document(id.as_bytes()).unwrap();
+        assert!(romanize_document(id.as_bytes(), b"yale").is_err());
+    }
+
+    #[test]
+    fn test_cache_invalidated_by_load() {
+        let _globals = globals();
+        let readings = |options: &str| -> Vec<Option<String>> {
+            annotated("㗎喇喎", options)
+                .iter()
+                .map(|t| t["jyutping"].as_str().map(String::from))
+                .collect()
+        };
+        let before = readings("{}");
+        assert!(before.len() > 1);
+        // cached and uncached paths agree
+        assert_eq!(readings("{}"), before);
+        assert_eq!(readings(r#"{"cache": false}"#), before);
+
+        load_dictionary(b"tsv", "㗎喇喎\tgaa3 laa3 wo3".as_bytes()).unwrap();
+        assert_eq!(readings("{}"), [Some("gaa3 laa3 wo3".to_string())]);
+    }
+
+    #[test]
+    fn test_load_dictionary() {
+        let _globals = globals();
+        let loaded = load_dictionary(
+            b"cedict",
+            "# comment\n嚿㗎 嚿㗎 [] {gau6 gaa3} /x/\n".as_bytes(),
+        );
+        assert_eq!(loaded, Ok(b"1".to_vec()));
+        let loaded = load_dictionary(b"tsv", "咪住先啦\tmai5 zyu6 sin1 laa1\n".as_bytes());
+        assert_eq!(loaded, Ok(b"1".to_vec()));
+        assert!(load_dictionary(b"xml", b"").is_err());
+
+        let trie = trie();
+        let result = trie.segment("嚿㗎咪住先啦");
+        let words: Vec<_> = result
+            .iter()
+            .map(|t| (t.word.as_str(), t.reading.as_deref()))
+            .collect();
+        assert_eq!(
+            words,
+            [
+                ("嚿㗎", Some("gau6 gaa3")),
+                ("咪住先啦", Some("mai5 zyu6 sin1 laa1")),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_proper_nouns() {
+        let _globals = globals();
+        let tokens: Vec<serde_json::Value> =
+            serde_json::from_slice(&annotate("去銅鑼灣".as_bytes())).unwrap();
+        assert_eq!(tokens[1]["word"], "銅鑼灣");
+        assert_eq!(tokens[1]["proper_noun"], true);
+        assert_eq!(tokens[1]["jyutping"], "tung4 lo4 waan1");
+        assert_eq!(
+            tokens[1]["yale"],
+            serde_json::json!(["Tùhng", "Lòh", "Wāan"])
+        );
+        assert!(tokens[0].get("proper_noun").is_none());
+
+        let loaded = load_dictionary(b"names", "蘇屋邨\tsou1 uk1 cyun1\n".as_bytes());
+        assert_eq!(loaded, Ok(b"1".to_vec()));
+        let tokens: Vec<serde_json::Value> =
+            serde_json::from_slice(&annotate("蘇屋邨".as_bytes())).unwrap();
+        assert_eq!(tokens[0]["proper_noun"], true);
+        assert_eq!(tokens[0]["yale"][0], "Sōu");
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_classifiers() {
+        let plain = annotated("一架車", "{}");
+        assert!(plain.iter().all(|t| t.get("classifiers").is_none()));
+
+        let tagged = annotated("一架車", r#"{"classifiers": true, "cache": false}"#);
+        let car = tagged.iter().find(|t| t["word"] == "車").unwrap();
+        assert_eq!(car["classifiers"], serde_json::json!(["架", "部"]));
+        assert!(tagged[0].get("classifiers").is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_variants() {
+        let trie = trie();
+        let words: Vec<_> = trie
+            .segment("佢喺裡頭為咗你")
+            .into_iter()
+            .map(|t| (t.word, t.reading))
+            .collect();
+        let expected = [
+            ("佢", "keoi5"),
+            ("喺", "hai2"),
+            ("裡頭", "leoi5 tau4"),
+            ("為咗", "wai6 zo2"),
+            ("你", "nei5"),
+        ];
+        assert_eq!(
+            words,
+            expected.map(|(word, reading)| (word.to_string(), Some(reading.to_string())))
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_strokes() {
+        let _globals = globals();
+        let unihan = "U+597D\tkRSUnicode\t38.3\n\
+                      U+597D\tkTotalStrokes\t6\n\
+                      U+4EBA\tkRSUnicode\t9.0\n\
+                      U+4EBA\tkTotalStrokes\t2\n\
+                      U+E000\tkCantonese\tjat1\n\
+                      U+597D\tkCantonese\thou3\n";
+        assert_eq!(
+            load_dictionary(b"unihan", unihan.as_bytes()),
+            Ok(b"5".to_vec())
+        );
+        // kCantonese only reads characters the dictionary cannot
+        let read = annotated("\u{E000}好", r#"{"cache": false}"#);
+        assert_eq!(read[0]["jyutping"], "jat1");
+        assert_eq!(read[1]["jyutping"], "hou2");
+        let tokens = annotated("好人，", r#"{"strokes": true, "cache": false}"#);
+        assert_eq!(tokens[0]["word"], "好人");
+        assert_eq!(tokens[0]["radicals"], serde_json::json!([38, 9]));
+        assert_eq!(tokens[0]["strokes"], serde_json::json!([6, 2]));
+        assert!(tokens[1].get("strokes").is_none());
+    }
+
+    #[test]
+    fn test_decompose() {
+        let _globals = globals();
+        let ids = "U+597D\t好\t⿰女子\nU+4E00\t一\t一\n";
+        assert_eq!(load_dictionary(b"ids", ids.as_bytes()), Ok(b"2".to_vec()));
+        assert_eq!(decompose("好".as_bytes()), Ok("⿰女子".as_bytes().to_vec()));
+        assert_eq!(decompose("一\n".as_bytes()), Ok("一".as_bytes().to_vec()));
+        assert!(decompose("好人".as_bytes()).is_err());
+        assert!(decompose(b"a").is_err());
+    }
+
+    #[test]
+    fn test_guess_readings() {
+        let _globals = globals();
+        let ids = "U+3469\t㑩\t⿰亻羅\n";
+        assert_eq!(load_dictionary(b"ids", ids.as_bytes()), Ok(b"1".to_vec()));
+        let plain = annotated("㑩好", r#"{"cache": false}"#);
+        assert!(plain[0]["jyutping"].is_null());
+
+        let guessed = annotated("㑩好", r#"{"guess_readings": true, "cache": false}"#);
+        assert_eq!(guessed[0]["jyutping"], "lo4");
+        assert_eq!(guessed[0]["yale"], serde_json::json!(["lòh"]));
+        assert_eq!(guessed[0]["guessed"], true);
+        assert!(guessed[1].get("guessed").is_none());
+    }
+
+    #[test]
+    fn test_emoji() {
+        let _globals = globals();
+        let before = annotated("好🦜🏻❤️", "{}");
+        assert_eq!(before.len(), 3);
+        assert_eq!(before[1]["category"], "emoji");
+        assert!(before[1]["jyutping"].is_null());
+
+        let loaded = load_dictionary(b"emoji", "🦜\tjing1 mou5\n好\thou2\n".as_bytes());
+        assert_eq!(loaded, Ok(b"1".to_vec()));
+        let after = annotated("好🦜🏻", "{}");
+        assert_eq!(after[1]["jyutping"], "jing1 mou5");
+        assert_eq!(after[1]["yale"], serde_json::json!(["yīng", "móuh"]));
+    }
+
+    #[test]
+    fn test_export_flashcards() {
+        let _globals = globals();
+        let entry = "㗎㗎嚿 㗎㗎嚿 [] {gaa3 gaa3 gau6} /lump/clump/\n";
+        assert_eq!(
+            load_dictionary(b"cedict", entry.as_bytes()),
+            Ok(b"1".to_vec())
+        );
+
+        let tsv = String::from_utf8(export_flashcards("好大㗎㗎嚿。".as_bytes())).unwrap();
+        let rows: Vec<Vec<&str>> = tsv.lines().map(|l| l.split('\t').collect()).collect();
+        let card = rows.iter().find(|r| r[0] == "㗎㗎嚿").unwrap();
+        assert_eq!(
+            card[1..],
+            [
+                "gaa3 gaa3 gau6",
+                "ga ga gauh",
+                "lump; clump",
+                "好大㗎㗎嚿。"
+            ]
+        );
+        assert!(rows.iter().all(|r| r.len() == 5));
+    }
+
+    #[test]
+    #[cfg(all(
+        debug_assertions,
+        not(any(feature = "no-lettered", feature = "chars-only"))
+    ))]
+    fn test_data_diagnostics() {
+        let problems: serde_json::Value = serde_json::from_slice(&data_diagnostics()).unwrap();
+        let problems = problems.as_array().unwrap();
+        assert!(problems.iter().all(|p| p["severity"] == "warning"));
+        // "啱feel ngaam1 feel" mixes English into its reading
+        assert!(problems.iter().any(|p| {
+            p["file"] == "lettered.tsv" && p["message"].as_str().unwrap().contains("\"feel\"")
+        }));
+    }
+
+    #[test]
+    fn test_checked() {
+        let checked =
+            |output: Vec<u8>| -> serde_json::Value { serde_json::from_slice(&output).unwrap() };
+        let result = checked(annotate_checked("好".as_bytes(), b""));
+        assert_eq!(result["ok"][0]["jyutping"], "hou2");
+        assert!(result["error"].is_null());
+        assert_eq!(result["schema_version"], capabilities::SCHEMA_VERSION);
+        let result = checked(annotate_checked(b"", b"{}"));
+        assert_eq!(result["ok"], serde_json::json!([]));
+        let result = checked(annotate_checked(
+            "好".as_bytes(),
+            br#"{"format": "bracketed"}"#,
+        ));
+        assert_eq!(result["ok"], "好(hou2)");
+
+        let result = checked(annotate_checked(b"\xff", b""));
+        assert!(result["ok"].is_null());
+        assert_eq!(result["error"]["code"], "invalid_utf8");
+        let result = checked(annotate_checked(b"", br#"{"scoring": 3}"#));
+        assert_eq!(result["error"]["code"], "invalid_options");
+        let result = checked(annotate_checked(b"", b"{"));
+        assert_eq!(result["error"]["code"], "invalid_options");
+        // misspelled option names and unknown systems are not ignored
+        let result = checked(annotate_checked(b"", br#"{"fromat": "html"}"#));
+        assert_eq!(result["error"]["code"], "invalid_options");
+        assert_eq!(result["error"]["message"], "unknown option: \"fromat\"");
+        let result = checked(annotate_checked(b"", br#"{"romanization": "wade_giles"}"#));
+        assert_eq!(result["error"]["code"], "invalid_options");
+        assert_eq!(
+            result["error"]["message"],
+            "unknown romanization: \"wade_giles\""
+        );
+        let result = checked(annotate_checked(b"", br#"{"romanization": "ipa"}"#));
+        assert!(result["error"].is_null());
+        // annotate_with keeps the options it knows
+        let output = rendered("好", r#"{"fromat": 1, "format": "bracketed"}"#);
+        assert_eq!(output, "好(hou2)");
+
+        let result = checked(to_yale_diacritics_checked(b"gwong2 dung1 waa2"));
+        assert_eq!(result["ok"], "gwóng dūng wá");
+        assert_eq!(checked(to_yale_numeric_checked(b""))["ok"], "");
+        let result = checked(to_yale_numeric_checked(b"nei5 hou2, OK?"));
+        assert_eq!(result["error"]["code"], "invalid_jyutping");
+        assert_eq!(result["error"]["message"], "not Jyutping: \"OK\"");
+    }
+
+    #[test]
+    fn test_get_capabilities() {
+        let capabilities: serde_json::Value = serde_json::from_slice(&get_capabilities()).unwrap();
+        assert_eq!(capabilities["schema_version"], 1);
+        assert_eq!(capabilities["version"], env!("CARGO_PKG_VERSION"));
+        let romanizers = capabilities["romanizers"].as_array().unwrap();
+        assert!(romanizers.contains(&"yale".into()));
+        assert_eq!(capabilities["options"]["scoring"], "fewest");
+        assert_eq!(
+            capabilities["options"]["brackets"],
+            serde_json::json!(["(", ")"])
+        );
+        assert!(capabilities["dictionaries"].is_array());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let texts = [
+            "a\r\nb\u{2028}c\u{85}d\u{a0}e\u{3000}f\u{feff}g\u{200b}h\u{200d}",
+            "\u{feff}你好 \t\u{301}café\u{301}",
+            "ＡＢ膠３％😀👨‍👩‍👧🇭🇰e\u{301}\u{301}",
+            "㍿①\u{2f800}",
+            "\r\r\n\n ",
+            "part-time e-mail! 2024",
+        ];
+        for options in [
+            "{}",
+            r#"{"normalization": "nfkc", "scoring": "unigram"}"#,
+            r#"{"normalization": "none", "max_word_length": 1}"#,
+            r#"{"compact": true, "known_words": ["你好"], "cloze": 0.5}"#,
+        ] {
+            for text in texts {
+                let tokens = annotate_with(text.as_bytes(), options.as_bytes());
+                assert_eq!(detokenize(&tokens).unwrap(), text.as_bytes(), "{}", options);
+            }
+        }
+        assert_eq!(detokenize(b"[]").unwrap(), b"");
+        assert!(detokenize(b"[{}]").is_err());
+        assert!(detokenize(b"{").is_err());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_segment_boundaries() {
+        assert_eq!(segment_boundaries("好學生".as_bytes()), b"[0,1,3]");
+        assert_eq!(segment_boundaries(b""), b"[0]");
+        for text in ["e\u{301}學生 part-time 3%！", "㍿👨‍👩‍👧\r\n香港人口"] {
+            let mut boundaries = vec![0];
+            for token in trie().segment(text) {
+                boundaries.push(boundaries.last().unwrap() + token.word.chars().count());
+            }
+            assert_eq!(trie().boundaries(text), boundaries, "{}", text);
+        }
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_diff_annotations() {
+        let output = diff_annotations(
+            "好學生，行".as_bytes(),
+            b"{}",
+            r#"{"scoring": "forward", "readings_at": {"4": "hang4"}}"#.as_bytes(),
+        );
+        let changes: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0]["text"], "好學生");
+        assert_eq!(changes[0]["before"][1]["word"], "學生");
+        assert_eq!(changes[0]["after"][0]["jyutping"], "hou3 hok6");
+        assert_eq!(
+            (changes[1]["start"].as_u64(), changes[1]["end"].as_u64()),
+            (Some(4), Some(5))
+        );
+        assert_eq!(changes[1]["after"][0]["jyutping"], "hang4");
+        assert_eq!(diff_annotations("好學生".as_bytes(), b"{}", b""), b"[]");
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_explain_segmentation() {
+        let explain = |text: &str, options: &str| -> serde_json::Value {
+            serde_json::from_slice(&explain_segmentation(text.as_bytes(), options.as_bytes()))
+                .unwrap()
+        };
+        let output = explain("好學生", "");
+        assert_eq!(output["scoring"], "fewest");
+        assert_eq!(
+            output["paths"][0]["words"],
+            serde_json::json!(["好", "學生"])
+        );
+        assert_eq!(output["paths"][0]["chosen"], true);
+        assert_eq!(
+            output["paths"][1]["words"],
+            serde_json::json!(["好學", "生"])
+        );
+        assert_eq!(output["paths"][1]["chosen"], false);
+        assert_eq!(
+            output["paths"][0]["score"],
+            trie().freq("好") + trie().freq("學生")
+        );
+        assert!(
+            output["reason"]
+                .as_str()
+                .unwrap()
+                .starts_with("as many tokens (2)")
+        );
+        assert_eq!(output["explored"][0]["path"], "好學");
+        let matched = output["matches"].as_array().unwrap();
+        assert!(
+            matched
+                .iter()
+                .any(|m| m["word"] == "好學" && m["start"] == 0)
+        );
+
+        let output = explain("研究生命", r#"{"scoring": "backward"}"#);
+        assert_eq!(
+            output["paths"][0]["words"],
+            serde_json::json!(["研究", "生命"])
+        );
+        assert!(output["reason"].as_str().unwrap().starts_with("backward"));
+        assert_eq!(explain("好", "")["reason"], "the only segmentation");
+        assert_eq!(explain("好", "")["truncated"], false);
+
+        // 3^8 segmentations, more than are compared
+        let output = explain(&"好學生".repeat(8), "");
+        assert_eq!(output["truncated"], true);
+        assert!(
+            output["reason"]
+                .as_str()
+                .unwrap()
+                .ends_with("(among the first 1000 candidate segmentations only)")
+        );
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "no-lettered", feature = "chars-only")))]
+    fn test_lattice() {
+        let output = lattice("好學生".as_bytes());
+        let matches: Vec<serde_json::Value> = serde_json::from_slice(&output).unwrap();
+        let spans: Vec<(u64, u64, &str)> = matches
+            .iter()
+            .map(|m| {
+                (
+                    m["start"].as_u64().unwrap(),
+                    m["end"].as_u64().unwrap(),
+                    m["word"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        for span in [(0, 1, "好"), (0, 2, "好學"), (1, 3, "學生"), (2, 3, "生")] {
+            assert!(spans.contains(&span), "{:?}", span);
+        }
+        let i = spans.iter().position(|s| s.2 == "學生").unwrap();
+        assert_eq!(matches[i]["jyutping"], "hok6 saang1");
+        assert!(matches[i]["freq"].as_i64().unwrap() > 0);
+        assert!(
+            spans
+                .windows(2)
+                .all(|w| (w[0].0, w[0].1) < (w[1].0, w[1].1))
+        );
+
+        // offsets are in the original text, not the normalized one
+        let matches = trie().lattice("e\u{301}學生");
+        assert!(matches.iter().any(|m| (m.start, m.end) == (2, 4)));
+        assert_eq!(lattice(b"  "), b"[]");
+    }
+
+    #[test]
+    fn test_syllabify_jyutping() {
+        assert_eq!(
+            syllabify_jyutping(b"GwongDungWaa hoeng1-gong2"),
+            Ok(b"gwong dung waa hoeng1 gong2".to_vec())
+        );
+        assert!(syllabify_jyutping(b"gwong2 xyz").is_err());
+        assert!(syllabify_jyutping("néih".as_bytes()).is_err());
+        assert!(syllabify_jyutping("你好".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_romanize() {
+        assert_eq!(
+            romanize(b"sik6 faan6", b"cantonese_pinyin"),
+            Ok(b"sik9 faan6".to_vec())
+        );
+        assert_eq!(
+            to_jyutping(b"sihk faahn", b"yale"),
+            Ok(b"sik6 faan6".to_vec())
+        );
+        assert!(romanize(b"sik6", b"wade_giles").is_err());
+        assert!(to_jyutping("sek̚˨".as_bytes(), b"ipa").is_err());
+
+        let tokens = annotated("食", r#"{"romanization": "sidney_lau"}"#);
+        assert_eq!(tokens[0]["romanized"], serde_json::json!(["sik6"]));
+        let plain = annotated("食", "{}");
+        assert!(plain[0].get("romanized").is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "chars-only"))]
+    fn test_romanize_checked_tone() {
+        // checked syllables in tone 4 are flagged by validate_jyutping but
+        // still romanized: 卜卜 is read buk4 buk4
+        assert_ne!(romanize(b"buk4", b"ipa"), Ok(b"buk4".to_vec()));
+        let tokens = annotated("卜卜", r#"{"romanization": "ipa"}"#);
+        let romanized = tokens[0]["romanized"].as_array().unwrap();
+        assert_eq!(romanized.len(), 2);
+        assert!(romanized.iter().all(|r| r != "buk4"));
+    }
 }
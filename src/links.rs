@@ -0,0 +1,140 @@
+use crate::markup::bare_url;
+use crate::token::Category;
+use crate::utils::is_alpha_char;
+
+/// Find the URLs, email addresses, @mentions and hashtags in `chars`, as
+/// `(start, end, category)` spans in order, for the segmenter to keep whole
+/// instead of cutting at every dot and slash:
+/// - URLs start with "http://", "https://" or "www." (see `bare_url`)
+/// - email addresses are `local@domain.tld`, ASCII only
+/// - @mentions are "@" and an ASCII name (letters, digits, `_`, inner `.`)
+/// - hashtags are "#" and non-CJK letters, digits or `_`, not all digits;
+///   CJK hashtags (#香港) are left to the dictionary, so they keep their
+///   readings
+///
+/// None of them starts right after a letter or digit ("a#b" is not a
+/// hashtag), and every span ends on a cluster boundary.
+pub fn find(chars: &[char], boundary: &[bool]) -> Vec<(usize, usize, Category)> {
+    let mut links = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let after_word = i > 0 && (is_alpha_char(chars[i - 1]) || is_local(chars[i - 1]));
+        let found = match chars[i] {
+            _ if after_word => None,
+            '@' => name(&chars[i + 1..], |c| c.is_ascii_alphanumeric() || c == '_')
+                .map(|len| (1 + len, Category::Mention)),
+            '#' => name(&chars[i + 1..], |c| is_alpha_char(c) || c == '_')
+                .filter(|&len| !chars[i + 1..i + 1 + len].iter().all(char::is_ascii_digit))
+                .map(|len| (1 + len, Category::Mention)),
+            c if is_local(c) => url(&chars[i..])
+                .or_else(|| email(&chars[i..]))
+                .map(|len| (len, Category::Url)),
+            _ => None,
+        };
+        match found {
+            Some((len, category)) if boundary[i + len] => {
+                links.push((i, i + len, category));
+                i += len;
+            }
+            // skip the rest of a word that is not a link
+            _ if is_local(chars[i]) => {
+                i += chars[i..].iter().take_while(|&&c| is_local(c)).count();
+            }
+            _ => i += 1,
+        }
+    }
+    links
+}
+
+/// Characters of the local part of an email address.
+fn is_local(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '%' | '+' | '-')
+}
+
+/// Length of the URL at the start of `chars`.
+fn url(chars: &[char]) -> Option<usize> {
+    // a URL is ASCII, so its length in bytes is its length in chars
+    let rest: String = chars.iter().take_while(|c| c.is_ascii_graphic()).collect();
+    bare_url(&rest).filter(|&len| len > "www.".len())
+}
+
+/// Length of the email address at the start of `chars`: a local part, "@"
+/// and at least two dot-separated domain labels, the last alphabetic.
+fn email(chars: &[char]) -> Option<usize> {
+    let local = chars.iter().take_while(|&&c| is_local(c)).count();
+    if local == 0 || chars.get(local) != Some(&'@') {
+        return None;
+    }
+    let mut end = local + 1;
+    let mut labels = 0;
+    let mut last = 0;
+    loop {
+        let label = chars[end..]
+            .iter()
+            .take_while(|c| c.is_ascii_alphanumeric() || **c == '-')
+            .count();
+        if label == 0 {
+            break;
+        }
+        labels += 1;
+        last = end;
+        end += label;
+        let next_label = chars.get(end + 1).is_some_and(char::is_ascii_alphanumeric);
+        if chars.get(end) != Some(&'.') || !next_label {
+            break;
+        }
+        end += 1;
+    }
+    let tld = chars[last..end].iter().all(char::is_ascii_alphabetic) && end - last >= 2;
+    (labels >= 2 && tld).then_some(end)
+}
+
+/// Length of the name at the start of `chars`: `is_name` characters, with
+/// single inner dots ("chan.tai.man", but not the full stop after it).
+fn name(chars: &[char], is_name: impl Fn(char) -> bool) -> Option<usize> {
+    let mut len = 0;
+    while let Some(&c) = chars.get(len) {
+        let inner_dot = c == '.' && len > 0 && chars.get(len + 1).is_some_and(|&n| is_name(n));
+        if !is_name(c) && !inner_dot {
+            break;
+        }
+        len += 1;
+    }
+    (len > 0).then_some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links(text: &str) -> Vec<(String, Category)> {
+        let chars: Vec<char> = text.chars().collect();
+        let boundary = vec![true; chars.len() + 1];
+        find(&chars, &boundary)
+            .into_iter()
+            .map(|(start, end, category)| (chars[start..end].iter().collect(), category))
+            .collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let found = links(
+            "睇https://x.io/a?b=1，電郵chan.tm+hk@mail.example.hk。\
+             @chan.tai.man 同 #TBT #2024 #香港 www.typst.app！",
+        );
+        let expected = [
+            ("https://x.io/a?b=1", Category::Url),
+            ("chan.tm+hk@mail.example.hk", Category::Url),
+            ("@chan.tai.man", Category::Mention),
+            ("#TBT", Category::Mention),
+            ("www.typst.app", Category::Url),
+        ];
+        assert_eq!(
+            found,
+            expected.map(|(text, category)| (text.to_string(), category))
+        );
+
+        // not links: a name after a word, a bare domain, "@" alone
+        assert!(links("a#b x@y www. @ # a@b.c 3.5").is_empty());
+    }
+}
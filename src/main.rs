@@ -0,0 +1,138 @@
+//! Command-line front end for native builds. It calls the same functions the
+//! Typst plugin exports, so output is identical to the WASM plugin's.
+//!
+//! ```sh
+//! echo 今日我要上堂 | rust-canto
+//! rust-canto --format interlinear chapter1.txt
+//! rust-canto --options '{"compact": true}' < input.txt
+//! rust-canto flashcards --dictionary cccanto.txt chapter1.txt > deck.tsv
+//! rust-canto freq subtitles.txt > freq.tsv
+//! rust-canto dump --tier words --dictionary cccanto.txt > words.tsv
+//! ```
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: rust-canto [--format FORMAT] [--options JSON] [--dictionary FILE] [FILE]
+       rust-canto flashcards [--format anki|pleco] [--category NAME]
+                             [--dictionary FILE] [FILE]
+       rust-canto freq [--dictionary FILE] [FILE]
+       rust-canto dump [--tier TIER] [--dictionary FILE]
+
+Annotate FILE (or standard input) with Jyutping and Yale, export its
+words as flashcards for Anki (tab-separated) or Pleco, or count its words
+into a word<TAB>count frequency table. dump writes the dictionary, as
+loaded, as word<TAB>jyutping lines.
+
+  --format FORMAT    json (default), interlinear, bracketed, ssml, html or
+                     table; for flashcards: anki (default) or pleco
+  --category NAME    Pleco flashcard category
+  --tier TIER        entries to dump: all (default), chars, words,
+                     lettered, names or emoji
+  --options JSON     options object, as for annotate_with
+  --dictionary FILE  load extra words (and glosses) from a CEDICT-format
+                     file, or a word<TAB>jyutping file if it ends in .tsv;
+                     may be repeated
+  --frequencies FILE replace the word frequencies that break ties with a
+                     table written by freq
+  -h, --help         show this help";
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("rust-canto: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result<(), String> {
+    let mut options = serde_json::Map::new();
+    let mut file = None;
+    let mut category = String::new();
+    let mut tier = String::from("all");
+
+    let mut args = std::env::args().skip(1).peekable();
+    let command = args.next_if(|a| ["flashcards", "freq", "dump"].contains(&a.as_str()));
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            "--format" => {
+                let format = args.next().ok_or("--format needs a value")?;
+                options.insert("format".into(), format.into());
+            }
+            "--options" => {
+                let json = args.next().ok_or("--options needs a value")?;
+                let parsed: serde_json::Map<String, serde_json::Value> =
+                    serde_json::from_str(&json).map_err(|e| format!("--options: {}", e))?;
+                options.extend(parsed);
+            }
+            "--category" => {
+                category = args.next().ok_or("--category needs a value")?;
+            }
+            "--tier" => {
+                tier = args.next().ok_or("--tier needs a value")?;
+            }
+            "--dictionary" => {
+                let path = args.next().ok_or("--dictionary needs a value")?;
+                let data = std::fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+                let format: &[u8] = if path.ends_with(".tsv") {
+                    b"tsv"
+                } else {
+                    b"cedict"
+                };
+                rust_canto::load_dictionary(format, &data)
+                    .map_err(|e| format!("{}: {}", path, e))?;
+            }
+            "--frequencies" => {
+                let path = args.next().ok_or("--frequencies needs a value")?;
+                let data = std::fs::read(&path).map_err(|e| format!("{}: {}", path, e))?;
+                rust_canto::load_frequencies(&data).map_err(|e| format!("{}: {}", path, e))?;
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown flag {}\n\n{}", arg, USAGE)),
+            _ => file = Some(arg),
+        }
+    }
+
+    let read_input = || match &file {
+        Some(path) => std::fs::read(path).map_err(|e| format!("{}: {}", path, e)),
+        None => {
+            let mut buf = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut buf)
+                .map_err(|e| e.to_string())?;
+            Ok(buf)
+        }
+    };
+
+    let output = match command.as_deref() {
+        Some("flashcards") => match options.get("format").and_then(|f| f.as_str()) {
+            None | Some("anki") => rust_canto::export_flashcards(&read_input()?),
+            Some("pleco") => rust_canto::export_pleco(&read_input()?, category.as_bytes()),
+            Some(other) => return Err(format!("unknown flashcard format {}", other)),
+        },
+        Some("freq") => rust_canto::count_frequencies(&read_input()?),
+        Some(_) => rust_canto::dump_dictionary(tier.as_bytes())?,
+        None => {
+            let options = serde_json::Value::Object(options).to_string();
+            rust_canto::annotate_with(&read_input()?, options.as_bytes())
+        }
+    };
+
+    let mut stdout = std::io::stdout().lock();
+    stdout
+        .write_all(&output)
+        .and_then(|_| {
+            if output.ends_with(b"\n") {
+                Ok(())
+            } else {
+                stdout.write_all(b"\n")
+            }
+        })
+        .map_err(|e| e.to_string())
+}
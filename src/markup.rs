@@ -0,0 +1,585 @@
+use std::ops::Range;
+
+use crate::options::InputFormat;
+use crate::token::Token;
+
+/// Segment `text` read as `input`: each markup span (see `markdown`,
+/// `typst` and `html`) becomes one token without a reading, and only the prose between them
+/// goes through `segment`. The tokens still cover the text exactly.
+pub fn segment(text: &str, input: InputFormat, segment: impl Fn(&str) -> Vec<Token>) -> Vec<Token> {
+    let raw = match input {
+        InputFormat::Text => return segment(text),
+        InputFormat::Markdown => markdown(text),
+        InputFormat::Typst => typst(text),
+        InputFormat::Html => html(text),
+    };
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    for range in raw {
+        if pos < range.start {
+            tokens.extend(segment(&text[pos..range.start]));
+        }
+        tokens.push(Token {
+            word: text[range.clone()].to_string(),
+            ..Default::default()
+        });
+        pos = range.end;
+    }
+    if pos < text.len() {
+        tokens.extend(segment(&text[pos..]));
+    }
+    tokens
+}
+
+/// Byte ranges of the Markdown in `text` that is not prose, in order:
+/// fenced code blocks (an unclosed fence runs to the end), inline code,
+/// link destinations (`(url "title")` after `]`), autolinks (`<...>`) and
+/// bare URLs. Link and image text is prose.
+fn markdown(text: &str) -> Vec<Range<usize>> {
+    let mut raw = Vec::new();
+    // fence char, fence length, start of the block
+    let mut fence: Option<(char, usize, usize)> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start_matches(' ');
+        let run = |c: char| trimmed.chars().take_while(|&x| x == c).count();
+        match fence {
+            Some((c, len, block)) => {
+                if run(c) >= len && trimmed[run(c)..].trim().is_empty() {
+                    raw.push(block..offset);
+                    fence = None;
+                }
+            }
+            None => match trimmed.chars().next() {
+                Some(c @ ('`' | '~')) if run(c) >= 3 => fence = Some((c, run(c), start)),
+                _ => inline(line, start, &mut raw),
+            },
+        }
+    }
+    if let Some((_, _, block)) = fence {
+        raw.push(block..text.len());
+    }
+    raw
+}
+
+/// Add the inline markup spans of `line`, which starts at `base` in the
+/// text, to `raw`.
+fn inline(line: &str, base: usize, raw: &mut Vec<Range<usize>>) {
+    let mut i = 0;
+    while let Some(ch) = line[i..].chars().next() {
+        let rest = &line[i..];
+        let len = match ch {
+            '`' => code_span(rest),
+            '(' if line[..i].ends_with(']') => link_destination(rest),
+            '<' => autolink(rest),
+            'h' | 'w' => bare_url(rest),
+            _ => None,
+        };
+        match len {
+            Some(len) => {
+                raw.push(base + i..base + i + len);
+                i += len;
+            }
+            // an unmatched backtick run is literal as a whole
+            None if ch == '`' => i += rest.bytes().take_while(|&b| b == b'`').count(),
+            None => i += ch.len_utf8(),
+        }
+    }
+}
+
+/// Length of the code span at the start of `rest`: a backtick run up to
+/// the next run of the same length.
+fn code_span(rest: &str) -> Option<usize> {
+    let n = rest.bytes().take_while(|&b| b == b'`').count();
+    let mut j = n;
+    while let Some(k) = rest[j..].find('`') {
+        let start = j + k;
+        let m = rest[start..].bytes().take_while(|&b| b == b'`').count();
+        if m == n {
+            return Some(start + m);
+        }
+        j = start + m;
+    }
+    None
+}
+
+/// Length of the parenthesized link destination at the start of `rest`,
+/// nested parentheses included.
+fn link_destination(rest: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, ch) in rest.char_indices() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            '\n' => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Length of the autolink at the start of `rest`: `<` and `>` around a URI
+/// or email address, with no whitespace.
+fn autolink(rest: &str) -> Option<usize> {
+    let end = rest.find('>')?;
+    let inner = &rest[1..end];
+    let plausible = (inner.contains(':') || inner.contains('@'))
+        && !inner.contains(|c: char| c.is_whitespace() || c == '<');
+    plausible.then_some(end + 1)
+}
+
+/// Length of the bare URL at the start of `rest` ("http://", "https://" or
+/// "www."): up to whitespace or a non-ASCII character, less trailing
+/// punctuation.
+pub(crate) fn bare_url(rest: &str) -> Option<usize> {
+    if !["http://", "https://", "www."]
+        .iter()
+        .any(|p| rest.starts_with(p))
+    {
+        return None;
+    }
+    let url = rest
+        .split(|c: char| c.is_whitespace() || !c.is_ascii() || c == '<')
+        .next()?;
+    Some(
+        url.trim_end_matches(['.', ',', ':', ';', '!', '?', '\'', '"', ')', '*', '_'])
+            .len(),
+    )
+}
+
+/// Byte ranges of the Typst source in `text` that is not prose, in order:
+/// code after `#`, math, raw text, comments, labels, references, escapes
+/// and URLs. The content blocks `[...]` trailing a call, as in
+/// `#strong[粗體]`, are markup again, so their text is annotated; code
+/// statements (`#let`, `#set`, `#show`, `#if`, ...) are skipped whole, to
+/// the end of the line.
+fn typst(text: &str) -> Vec<Range<usize>> {
+    let mut scanner = Typst {
+        text,
+        raw: Vec::new(),
+    };
+    scanner.markup(0, false);
+    scanner.raw
+}
+
+struct Typst<'a> {
+    text: &'a str,
+    raw: Vec<Range<usize>>,
+}
+
+impl Typst<'_> {
+    /// Record a raw range, merged with the previous one if they touch.
+    fn push(&mut self, range: Range<usize>) {
+        push_merged(&mut self.raw, range);
+    }
+
+    /// Scan markup from `i` to the end of the text or, in a content block,
+    /// to its closing `]`; returns the position of the `]` (or the end).
+    fn markup(&mut self, mut i: usize, in_block: bool) -> usize {
+        let text = self.text;
+        let mut depth = 0;
+        while let Some(ch) = text[i..].chars().next() {
+            let rest = &text[i..];
+            let len = match ch {
+                '[' => {
+                    depth += 1;
+                    None
+                }
+                ']' if in_block && depth == 0 => return i,
+                ']' => {
+                    depth -= 1;
+                    None
+                }
+                '\\' => rest[1..].chars().next().map(|c| 1 + c.len_utf8()),
+                '/' if rest.starts_with("//") => Some(rest.find('\n').unwrap_or(rest.len())),
+                '/' if rest.starts_with("/*") => Some(block_comment(rest)),
+                '`' => Some(code_span(rest).unwrap_or(rest.len())),
+                '$' => Some(math(rest)),
+                '<' => label(rest),
+                '@' => reference(rest),
+                'h' => bare_url(rest),
+                '#' => {
+                    i = self.code(i);
+                    continue;
+                }
+                _ => None,
+            };
+            match len {
+                Some(len) => {
+                    self.push(i..i + len);
+                    i += len;
+                }
+                None => i += ch.len_utf8(),
+            }
+        }
+        i
+    }
+
+    /// Skip the code starting with the `#` at `start`, recording it as raw
+    /// apart from trailing content blocks; returns the position after it.
+    fn code(&mut self, start: usize) -> usize {
+        let text = self.text;
+        let mut i = start + 1;
+        let ident = ident_len(&text[i..]);
+        if ident == 0 {
+            let end = match text[i..].chars().next() {
+                Some('(' | '{') => skip_balanced(text, i),
+                Some('"') => skip_string(text, i),
+                Some('[') => i,
+                // a literal "#"
+                _ => return i,
+            };
+            self.push(start..end);
+            i = end;
+        } else if matches!(
+            &text[i..i + ident],
+            "let" | "set" | "show" | "import" | "include" | "if" | "for" | "while" | "return"
+        ) {
+            let end = statement_end(text, i);
+            self.push(start..end);
+            return end;
+        } else {
+            i += ident;
+        }
+
+        // calls, fields and trailing content blocks
+        let mut from = start;
+        loop {
+            let rest = &text[i..];
+            if rest.starts_with('(') {
+                i = skip_balanced(text, i);
+            } else if rest.starts_with('.') && ident_len(&rest[1..]) > 0 {
+                i += 1 + ident_len(&rest[1..]);
+            } else if rest.starts_with('[') {
+                self.push(from..i + 1);
+                let close = self.markup(i + 1, true);
+                if close == text.len() {
+                    return close;
+                }
+                from = close;
+                i = close + 1;
+            } else {
+                break;
+            }
+        }
+        self.push(from..i);
+        i
+    }
+}
+
+/// Length of the Typst identifier at the start of `rest`, 0 if none.
+fn ident_len(rest: &str) -> usize {
+    let mut chars = rest.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || c == '-'))
+        .map_or(rest.len(), |(i, _)| i)
+}
+
+/// Position after the bracket closing the one at `i`, skipping strings
+/// and nested brackets; the end of the text if it is never closed.
+fn skip_balanced(text: &str, mut i: usize) -> usize {
+    let mut depth = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        match ch {
+            '"' => {
+                i = skip_string(text, i);
+                continue;
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i + 1;
+                }
+            }
+            _ => {}
+        }
+        i += ch.len_utf8();
+    }
+    text.len()
+}
+
+/// Position after the string literal starting at `i`.
+fn skip_string(text: &str, i: usize) -> usize {
+    let mut escaped = false;
+    for (j, ch) in text[i + 1..].char_indices() {
+        match ch {
+            '"' if !escaped => return i + 1 + j + 1,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    text.len()
+}
+
+/// End of the code statement starting at `i`: the end of the line, or
+/// just after a `;`, with brackets and strings skipped whole.
+fn statement_end(text: &str, mut i: usize) -> usize {
+    while let Some(ch) = text[i..].chars().next() {
+        match ch {
+            '\n' => return i,
+            ';' => return i + 1,
+            '(' | '[' | '{' => i = skip_balanced(text, i),
+            '"' => i = skip_string(text, i),
+            _ => i += ch.len_utf8(),
+        }
+    }
+    i
+}
+
+/// Length of the (possibly nested) block comment at the start of `rest`.
+fn block_comment(rest: &str) -> usize {
+    let mut depth = 0;
+    let mut i = 0;
+    while i < rest.len() {
+        if rest[i..].starts_with("/*") {
+            depth += 1;
+            i += 2;
+        } else if rest[i..].starts_with("*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += rest[i..].chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    rest.len()
+}
+
+/// Length of the math at the start of `rest`, to the next unescaped `$`.
+fn math(rest: &str) -> usize {
+    let mut escaped = false;
+    for (i, ch) in rest.char_indices().skip(1) {
+        match ch {
+            '$' if !escaped => return i + 1,
+            '\\' => escaped = !escaped,
+            _ => escaped = false,
+        }
+    }
+    rest.len()
+}
+
+/// Length of the label (`<intro>`) at the start of `rest`.
+fn label(rest: &str) -> Option<usize> {
+    let name = rest[1..]
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')))
+        .unwrap_or(rest.len() - 1);
+    (name > 0 && rest[1 + name..].starts_with('>')).then_some(name + 2)
+}
+
+/// Length of the reference (`@intro`) at the start of `rest`; a final dot
+/// ends the sentence rather than the name.
+fn reference(rest: &str) -> Option<usize> {
+    let name = rest[1..]
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.')))
+        .unwrap_or(rest.len() - 1);
+    let name = rest[1..1 + name].trim_end_matches('.').len();
+    (name > 0).then_some(name + 1)
+}
+
+/// Elements whose content is not prose: scripts, styles, and the readings
+/// of ruby already in the page.
+const RAW_ELEMENTS: [&str; 4] = ["script", "style", "rt", "rp"];
+
+/// Byte ranges of the HTML in `text` that is not text-node content, in
+/// order: tags, comments, doctypes, character references (`&amp;`) and the
+/// content of `RAW_ELEMENTS`. A `<` that does not start a tag is text.
+fn html(text: &str) -> Vec<Range<usize>> {
+    let mut raw = Vec::new();
+    let mut i = 0;
+    while let Some(ch) = text[i..].chars().next() {
+        let rest = &text[i..];
+        let len = match ch {
+            '<' => tag(rest).map(|len| match raw_element(&rest[..len]) {
+                // skip to the end of the element's closing tag
+                Some(name) => {
+                    let close = format!("</{}", name);
+                    rest[len..]
+                        .to_ascii_lowercase()
+                        .find(&close)
+                        .and_then(|k| rest[len + k..].find('>').map(|e| len + k + e + 1))
+                        .unwrap_or(rest.len())
+                }
+                None => len,
+            }),
+            '&' => entity(rest),
+            _ => None,
+        };
+        match len {
+            Some(len) => {
+                push_merged(&mut raw, i..i + len);
+                i += len;
+            }
+            None => i += ch.len_utf8(),
+        }
+    }
+    raw
+}
+
+/// Length of the tag, comment or declaration at the start of `rest`, with
+/// quoted attribute values skipped whole.
+fn tag(rest: &str) -> Option<usize> {
+    if let Some(comment) = rest.strip_prefix("<!--") {
+        return Some(comment.find("-->").map_or(rest.len(), |k| 4 + k + 3));
+    }
+    let name = rest[1..].trim_start_matches('/');
+    if !(rest[1..].starts_with(['!', '?']) || name.starts_with(|c: char| c.is_ascii_alphabetic())) {
+        return None;
+    }
+    let mut quote = None;
+    for (i, ch) in rest.char_indices().skip(1) {
+        match (quote, ch) {
+            (None, '"' | '\'') => quote = Some(ch),
+            (Some(q), _) if q == ch => quote = None,
+            (None, '>') => return Some(i + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The name of the `RAW_ELEMENTS` element `tag` opens, if it does.
+fn raw_element(tag: &str) -> Option<&'static str> {
+    let name: String = tag[1..]
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+    let self_closing = tag.ends_with("/>");
+    RAW_ELEMENTS
+        .into_iter()
+        .find(|e| !self_closing && name.eq_ignore_ascii_case(e))
+}
+
+/// Length of the character reference (`&amp;`, `&#23383;`, `&#x5B57;`) at
+/// the start of `rest`.
+fn entity(rest: &str) -> Option<usize> {
+    let end = rest[1..].find(';')? + 1;
+    let name = &rest[1..end];
+    let valid = match name.strip_prefix('#') {
+        Some(num) => match num.strip_prefix(['x', 'X']) {
+            Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+            None => !num.is_empty() && num.chars().all(|c| c.is_ascii_digit()),
+        },
+        None => !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric()),
+    };
+    (valid && end <= 32).then_some(end + 1)
+}
+
+/// Add `range` to `raw`, merged with the last range if they touch.
+fn push_merged(raw: &mut Vec<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() {
+        return;
+    }
+    match raw.last_mut() {
+        Some(last) if last.end == range.start => last.end = range.end,
+        _ => raw.push(range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pieces(text: &str) -> Vec<&str> {
+        markdown(text).into_iter().map(|r| &text[r]).collect()
+    }
+
+    #[test]
+    fn test_markdown() {
+        let text = "用 `cargo build` 編譯，見[文檔](https://x.io/a_(b) \"t\")同<https://y.io>。\n\
+                    ```rust\nlet 我 = 1;\n\n```\n\
+                    網址 https://z.io/p?q=1。``` 唔係 fence\n~~~\n未完";
+        assert_eq!(
+            pieces(text),
+            [
+                "`cargo build`",
+                "(https://x.io/a_(b) \"t\")",
+                "<https://y.io>",
+                "```rust\nlet 我 = 1;\n\n```\n",
+                "https://z.io/p?q=1",
+                "~~~\n未完",
+            ]
+        );
+        assert!(pieces("a < b, c > d, [x] (y), `tick, wwwow").is_empty());
+
+        // stand-in segmenter: one token per char
+        let split = |s: &str| -> Vec<Token> {
+            s.chars()
+                .map(|c| Token {
+                    word: c.to_string(),
+                    ..Default::default()
+                })
+                .collect()
+        };
+        let tokens = segment(text, InputFormat::Markdown, split);
+        assert!(tokens.iter().any(|t| t.word == "`cargo build`"));
+        assert_eq!(crate::token::detokenize(&tokens), text);
+        assert_eq!(segment("`x`", InputFormat::Text, split).len(), 3);
+    }
+
+    #[test]
+    fn test_html() {
+        let text = "<!DOCTYPE html><p class=\"a>b\">我哋&amp;你哋 1 < 2</p>\n\
+                    <!-- 註 --><script>let x = \"<p>字</p>\";</script>\
+                    <ruby>行<rt>haang4</rt></ruby>&#x5B57;& <br/>完";
+        let pieces: Vec<&str> = html(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(
+            pieces,
+            [
+                "<!DOCTYPE html><p class=\"a>b\">",
+                "&amp;",
+                "</p>",
+                "<!-- 註 --><script>let x = \"<p>字</p>\";</script><ruby>",
+                "<rt>haang4</rt></ruby>&#x5B57;",
+                "<br/>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_typst() {
+        let pieces = |text: &'static str| -> Vec<&'static str> {
+            typst(text).into_iter().map(|r| &text[r]).collect()
+        };
+        let text = "#set text(font: \"Noto\")\n= 標題 <intro>\n\
+                    見 @intro.#strong[粗體 #emph[重]]同 $x^2$，`raw`、\\#。\n\
+                    #link(\"https://typst.app\")[網站] https://x.io // 註\n\
+                    /* 一 /* 二 */ */#figure(image(\"a.png\"), caption: [圖])#x.y 完";
+        assert_eq!(
+            pieces(text),
+            [
+                "#set text(font: \"Noto\")",
+                "<intro>",
+                "@intro",
+                "#strong[",
+                "#emph[",
+                "]]",
+                "$x^2$",
+                "`raw`",
+                "\\#",
+                "#link(\"https://typst.app\")[",
+                "]",
+                "https://x.io",
+                "// 註",
+                "/* 一 /* 二 */ */#figure(image(\"a.png\"), caption: [圖])#x.y",
+            ]
+        );
+        // "#" without code after it, and unclosed blocks
+        assert!(pieces("第 # 1 名 #1").is_empty());
+        assert_eq!(pieces("#f[未完"), ["#f["]);
+        assert_eq!(pieces("#let x = (\n1)\n文字"), ["#let x = (\n1)"]);
+    }
+}
@@ -0,0 +1,124 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::jyutping::Syllable;
+use crate::trie::Trie;
+
+/// Most example characters given per syllable.
+const EXAMPLES: usize = 3;
+
+/// The one feature in which a minimal pair differs.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Feature {
+    Tone,
+    Initial,
+    Final,
+}
+
+/// An attested syllable differing from the drilled one in one feature.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MinimalPair {
+    pub jyutping: String,
+    pub feature: Feature,
+    /// Characters read this way, most frequent first.
+    pub examples: Vec<String>,
+}
+
+/// Syllables that single characters in the dictionary are read with, and
+/// those characters, most frequent first.
+fn attested(trie: &Trie) -> HashMap<String, Vec<String>> {
+    let mut chars: HashMap<String, Vec<(i64, char)>> = HashMap::new();
+    for (ch, node) in trie.children(trie.root()) {
+        for reading in node.readings() {
+            if Syllable::parse(&reading).is_some() {
+                chars.entry(reading).or_default().push((node.freq, ch));
+            }
+        }
+    }
+    chars
+        .into_iter()
+        .map(|(syllable, mut list)| {
+            list.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+            let examples = list.iter().map(|(_, ch)| ch.to_string()).collect();
+            (syllable, examples)
+        })
+        .collect()
+}
+
+/// Minimal pairs for pronunciation drills: attested syllables that differ
+/// from `syllable` in exactly its tone, its initial or its final, with
+/// example characters, e.g. si1 → si2 (史), ji1 (衣), saa1 (沙), ...
+/// Ordered by feature, then spelling. The drilled syllable needs a tone.
+pub fn minimal_pairs(trie: &Trie, syllable: &str) -> Result<Vec<MinimalPair>, String> {
+    let target = Syllable::parse(&syllable.trim().to_lowercase())
+        .filter(|s| s.tone > 0)
+        .ok_or_else(|| format!("not a Jyutping syllable with a tone: {:?}", syllable))?;
+
+    let mut pairs: Vec<MinimalPair> = attested(trie)
+        .into_iter()
+        .filter_map(|(jyutping, examples)| {
+            let other = Syllable::parse(&jyutping)?;
+            let same = [
+                other.tone == target.tone,
+                other.onset == target.onset,
+                other.rime == target.rime,
+            ];
+            let feature = match same {
+                [false, true, true] => Feature::Tone,
+                [true, false, true] => Feature::Initial,
+                [true, true, false] => Feature::Final,
+                _ => return None,
+            };
+            Some(MinimalPair {
+                jyutping,
+                feature,
+                examples: examples.into_iter().take(EXAMPLES).collect(),
+            })
+        })
+        .collect();
+    pairs.sort_by(|a, b| {
+        a.feature
+            .cmp(&b.feature)
+            .then_with(|| a.jyutping.cmp(&b.jyutping))
+    });
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_minimal_pairs() {
+        let mut trie = Trie::default();
+        for (word, reading) in [
+            ("詩", "si1"),
+            ("史", "si2"),
+            ("使", "si2"),
+            ("衣", "ji1"),
+            ("沙", "saa1"),
+            ("時", "si4"),
+            ("食", "sik6"),
+            ("詩人", "si1 jan4"),
+        ] {
+            trie.insert(word, reading);
+        }
+        let pairs = minimal_pairs(&trie, "si1").unwrap();
+        let summary: Vec<_> = pairs
+            .iter()
+            .map(|p| (p.jyutping.as_str(), p.feature, p.examples.join("")))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("si2", Feature::Tone, "使史".to_string()),
+                ("si4", Feature::Tone, "時".to_string()),
+                ("ji1", Feature::Initial, "衣".to_string()),
+                ("saa1", Feature::Final, "沙".to_string()),
+            ]
+        );
+        assert!(minimal_pairs(&trie, "si").is_err());
+        assert!(minimal_pairs(&trie, "xyz1").is_err());
+    }
+}
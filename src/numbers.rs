@@ -0,0 +1,227 @@
+use crate::token::Token;
+use crate::utils::fold_width;
+
+const DIGITS: [&str; 10] = [
+    "ling4", "jat1", "ji6", "saam1", "sei3", "ng5", "luk6", "cat1", "baat3", "gau2",
+];
+
+/// 十, 百, 千 by power of ten within a group of four digits.
+const UNITS: [&str; 4] = ["", "sap6", "baak3", "cin1"];
+
+/// 萬 and 億 by group of four digits.
+const GROUPS: [&str; 3] = ["", "maan6", "jik1"];
+
+/// The Cantonese reading of `n` in Jyutping, as it is read out: 十二 "sap6
+/// ji6", 兩百 "loeng5 baak3", 一萬零五 "jat1 maan6 ling4 ng5". None from
+/// 10¹² up.
+pub fn reading(n: u64) -> Option<String> {
+    if n >= 1_0000_0000_0000 {
+        return None;
+    }
+    if n == 0 {
+        return Some(DIGITS[0].to_string());
+    }
+    let mut syllables: Vec<&str> = Vec::new();
+    // whether a zero was skipped since the last digit read
+    let mut zero = false;
+    for group_index in (0..GROUPS.len() as u32).rev() {
+        let group = n / 10_000u64.pow(group_index) % 10_000;
+        if group == 0 {
+            zero = !syllables.is_empty();
+            continue;
+        }
+        for power in (0..UNITS.len() as u32).rev() {
+            let digit = group / 10u64.pow(power) % 10;
+            if digit == 0 {
+                zero = !syllables.is_empty();
+                continue;
+            }
+            if zero {
+                syllables.push(DIGITS[0]);
+                zero = false;
+            }
+            let first = syllables.is_empty();
+            match digit {
+                // 十二, not 一十二; but 一百一十
+                1 if first && power == 1 => {}
+                // 兩百, 兩千, 兩萬
+                2 if first && (power >= 2 || (group == 2 && group_index > 0)) => {
+                    syllables.push("loeng5")
+                }
+                _ => syllables.push(DIGITS[digit as usize]),
+            }
+            if power > 0 {
+                syllables.push(UNITS[power as usize]);
+            }
+        }
+        if group_index > 0 {
+            syllables.push(GROUPS[group_index as usize]);
+        }
+    }
+    Some(syllables.join(" "))
+}
+
+/// The value of the Roman numeral `word`, in capitals (XIV) or numeral
+/// characters (Ⅱ, ⅩⅣ, ⅻ), in the usual subtractive form (not IIII).
+/// Single capitals ("I", "V") are left alone, as they are more often words
+/// and initials.
+pub fn roman(word: &str) -> Option<u64> {
+    let mut letters = String::new();
+    let mut numeral_chars = false;
+    for c in word.chars() {
+        match numeral_char(c) {
+            Some(n) => {
+                letters.push_str(&to_roman(n));
+                numeral_chars = true;
+            }
+            None if matches!(c, 'I' | 'V' | 'X' | 'L' | 'C' | 'D' | 'M') => letters.push(c),
+            None => return None,
+        }
+    }
+    if !numeral_chars && letters.len() < 2 {
+        return None;
+    }
+    let mut total = 0;
+    for (i, c) in letters.chars().enumerate() {
+        let v = value(c);
+        match letters[i + 1..].chars().next().map(value) {
+            Some(next) if next > v => total -= v as i64,
+            _ => total += v as i64,
+        }
+    }
+    // only the canonical spelling of the value
+    let n = u64::try_from(total).ok().filter(|&n| n > 0)?;
+    (to_roman(n) == letters).then_some(n)
+}
+
+/// The value of a Roman numeral character (U+2160–U+217F).
+fn numeral_char(c: char) -> Option<u64> {
+    match c {
+        '\u{2160}'..='\u{216B}' => Some(c as u64 - 0x2160 + 1),
+        '\u{2170}'..='\u{217B}' => Some(c as u64 - 0x2170 + 1),
+        '\u{216C}' | '\u{217C}' => Some(50),
+        '\u{216D}' | '\u{217D}' => Some(100),
+        '\u{216E}' | '\u{217E}' => Some(500),
+        '\u{216F}' | '\u{217F}' => Some(1000),
+        _ => None,
+    }
+}
+
+fn value(letter: char) -> u64 {
+    match letter {
+        'I' => 1,
+        'V' => 5,
+        'X' => 10,
+        'L' => 50,
+        'C' => 100,
+        'D' => 500,
+        _ => 1000,
+    }
+}
+
+fn to_roman(mut n: u64) -> String {
+    const NUMERALS: [(u64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut out = String::new();
+    for (v, s) in NUMERALS {
+        while n >= v {
+            out.push_str(s);
+            n -= v;
+        }
+    }
+    out
+}
+
+/// Read Roman numerals and ordinals (the `numerals` option): a numeral
+/// token without a reading gets the reading of its value (XIV "sap6 sei3"),
+/// and 第 before digits or a numeral becomes one token with both (第3
+/// "dai6 saam1"). Other digits are left unread.
+pub fn read_numerals(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    for mut token in tokens {
+        if token.reading.is_some() || token.category.is_some() {
+            out.push(token);
+            continue;
+        }
+        let numeral = roman(&token.word);
+        let digits: String = token.word.chars().map(fold_width).collect();
+        let is_digits = !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit());
+        let after_di =
+            matches!(out.last(), Some(prev) if prev.word == "第" && prev.reading.is_some());
+        let ordinal = match (after_di, numeral) {
+            (false, _) => None,
+            (true, Some(n)) => Some(n),
+            (true, None) => digits.parse().ok().filter(|_| is_digits),
+        };
+        match (ordinal.and_then(reading), numeral.and_then(reading)) {
+            (Some(number), _) => {
+                let prev = out.last_mut().expect("ordinal after 第");
+                prev.word.push_str(&token.word);
+                prev.reading = prev.reading.take().map(|r| format!("{} {}", r, number));
+                prev.freq = None;
+                prev.weight = None;
+                prev.confidence = None;
+            }
+            (None, Some(number)) => {
+                token.reading = Some(number);
+                out.push(token);
+            }
+            (None, None) => out.push(token),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading() {
+        let cases = [
+            (0, "ling4"),
+            (7, "cat1"),
+            (10, "sap6"),
+            (12, "sap6 ji6"),
+            (20, "ji6 sap6"),
+            (110, "jat1 baak3 jat1 sap6"),
+            (200, "loeng5 baak3"),
+            (1010, "jat1 cin1 ling4 jat1 sap6"),
+            (2024, "loeng5 cin1 ling4 ji6 sap6 sei3"),
+            (10_005, "jat1 maan6 ling4 ng5"),
+            (20_000, "loeng5 maan6"),
+            (100_500, "sap6 maan6 ling4 ng5 baak3"),
+            (3_0000_0000, "saam1 jik1"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(reading(n).as_deref(), Some(expected), "{}", n);
+        }
+        assert_eq!(reading(1_0000_0000_0000), None);
+    }
+
+    #[test]
+    fn test_roman() {
+        assert_eq!(roman("XIV"), Some(14));
+        assert_eq!(roman("MMXXIV"), Some(2024));
+        assert_eq!(roman("Ⅱ"), Some(2));
+        assert_eq!(roman("ⅻ"), Some(12));
+        assert_eq!(roman("ⅩⅣ"), Some(14));
+        assert_eq!(roman("Ⅿ"), Some(1000));
+        for word in ["I", "V", "IIII", "ⅡⅡ", "IC", "xiv", "MIXED", ""] {
+            assert_eq!(roman(word), None, "{}", word);
+        }
+    }
+}
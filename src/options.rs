@@ -0,0 +1,394 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::romanize::{self, Romanizer};
+use crate::trie::Matching;
+use crate::utils::CONNECTORS;
+
+/// Per-call options for `annotate_with`, passed as a JSON object.
+/// Every field is optional, so `{}` behaves exactly like `annotate`.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct Options {
+    /// Look up and store results in the segmentation cache (default true).
+    /// Set to false for one-off texts that would only evict useful entries.
+    pub cache: bool,
+    /// Unicode normalization applied to the input before dictionary lookup:
+    /// "nfc" (default), "nfkc" (also folds compatibility forms such as ㍿ and
+    /// ①) or "none". Token words always keep the original text.
+    pub normalization: Normalization,
+    /// How the segmenter chooses between splits: "fewest" (default: fewest
+    /// tokens, then highest total frequency), "longest" (fewest tokens, then
+    /// the longest words, ignoring frequency), "frequency" (highest total
+    /// frequency, then fewest tokens), "unigram" (most probable split
+    /// under a unigram model from freq.txt) or "bigram" (the same, with
+    /// each word conditioned on the one before it by the counts loaded with
+    /// `load_bigrams`). "forward" and "backward" skip the search and take
+    /// the longest word at each step, from the start or the end of the text
+    /// (forward and backward maximum matching). See `Trie::segment`.
+    pub scoring: Scoring,
+    /// Longest dictionary word, in characters, the segmenter may match
+    /// (default: no limit). Lower it to keep long runtime entries, such as
+    /// whole phrases in a user dictionary, from swallowing text; shorter
+    /// words and characters then cover the span instead.
+    pub max_word_length: Option<usize>,
+    /// The characters that join letters and digits into one token where
+    /// the dictionary has no word for them (an alpha run), by default
+    /// hyphen, underscore and apostrophe (`"-_'"`: part-time, i'm); curly
+    /// apostrophes and hyphen variants count as `'` and `-`. Add `.`, `/`
+    /// or `+` to keep v1.2, A/B or C++ whole. Only `+` and `#` may end a
+    /// run; the others join only between letters.
+    pub connectors: String,
+    /// Readings for this call only, by word, e.g. `{"行": "hang4"}`. The
+    /// words are matched like dictionary entries, so new words can be added
+    /// too, and their readings come before the dictionary's. Nothing
+    /// outside the call changes.
+    pub overrides: BTreeMap<String, String>,
+    /// Readings of single characters by character offset in the text, e.g.
+    /// `{"12": "hang4"}`, for a word that reads differently in different
+    /// places. Inside a longer word the offset's syllable is replaced, if
+    /// the word has one syllable per character.
+    pub readings_at: BTreeMap<usize, String>,
+    /// Read readings written inline after characters, 漢{hon3}字{zi6}, as
+    /// `readings_at`, and drop them from the words (see inline.rs). The
+    /// offsets of `readings_at` then count the text without them.
+    pub inline_readings: bool,
+    /// Read Roman numerals (Ⅱ, XIV) and ordinals (第3) out as numbers
+    /// instead of leaving them unread (see numbers.rs). Off by default, as
+    /// capitals such as "MIX" are valid numerals too.
+    pub numerals: bool,
+    /// Tag sentence-final particles (㗎, 啦喎, 咋嘛, ...) with `particle:
+    /// true`, splitting them off words the dictionary merged them into (畀啦
+    /// → 畀 啦); see particles.rs.
+    pub particles: bool,
+    /// Add the Kangxi radical (`radicals`) and total stroke count
+    /// (`strokes`) of each character of a token, for dictionary-style
+    /// appendices and stroke-ordered indexes. Needs radical-stroke data:
+    /// Unihan_IRGSources.txt at build time, or `load_dictionary(b"unihan",
+    /// ...)`.
+    pub strokes: bool,
+    /// Guess a reading for CJK characters the dictionary has none for from
+    /// their phonetic component (⿰氵青 → cing1), marked `guessed: true`;
+    /// see guess.rs. Needs decomposition data (see `decompose` in lib.rs).
+    pub guess_readings: bool,
+    /// What the text is: "text" (default), "markdown", whose code, link
+    /// destinations and URLs are passed through unsegmented, each as one
+    /// token without a reading, "typst" source, whose code, math, raw text
+    /// and comments are passed through the same way, or an "html" fragment,
+    /// of which only text nodes are annotated (see markup.rs).
+    pub input: InputFormat,
+    /// Target learner level for `readability` (default 2): words above it
+    /// are flagged. See readability.rs for how levels are assigned.
+    pub max_level: u8,
+    /// Whether tones count for rhyme in `analyze_rhyme`: "ignore" (default,
+    /// final only), "class" (level 平 vs oblique 仄) or "exact".
+    pub rhyme_tones: RhymeTones,
+    /// Include each token's corpus frequency (`freq`) in the output.
+    pub freq: bool,
+    /// Include the classifiers (measure words) of nouns, `classifiers`:
+    /// ["本"] for 書, ["架", "部"] for 車, from data/classifiers.tsv. For
+    /// classifier hints in learner materials.
+    pub classifiers: bool,
+    /// Add each token's language, `lang`: "yue", "en" or "other", guessed
+    /// from its script and whether the dictionary reads it, for typesetting
+    /// embedded English differently. Punctuation and other tokens without
+    /// letters get none.
+    pub lang: bool,
+    /// Include the data files' weight of the chosen reading (`weight`) on
+    /// characters and weighted words and, where the entry has several
+    /// readings, its share of their weights (`confidence`), so that
+    /// uncertain readings can be flagged for review.
+    pub weights: bool,
+    /// Include every reading of the matched entry, `readings`, most common
+    /// first, on tokens whose entry has more than one: the order is fixed by
+    /// the data, not by the order of its lines, so it is stable across
+    /// builds.
+    pub readings: bool,
+    /// Mark tokens with a reading as `rare: true/false` depending on whether
+    /// their corpus frequency is below this threshold.
+    pub rare_below: Option<i64>,
+    /// Graded-reader mode: keep readings (and Yale) only on rare tokens, as
+    /// decided by `rare_below`; all other tokens come back unannotated.
+    pub only_rare: bool,
+    /// Words the reader already knows, e.g. a learner's vocabulary list.
+    pub known_words: HashSet<String>,
+    /// What to do with tokens in `known_words`: "hide" their readings
+    /// (default) or "flag" them with `known: true` and keep the readings.
+    pub known_mode: KnownMode,
+    /// Cloze mode: fraction (0–1) of the tokens with a reading to mark
+    /// `hidden: true`, with their readings withheld, for fill-in-the-reading
+    /// exercises.
+    pub cloze: Option<f64>,
+    /// Seed for picking the `cloze` tokens (default 0). The same seed and
+    /// text always hide the same tokens.
+    pub seed: u64,
+    /// Add a `tones` array to tokens with a reading, one Jyutping tone
+    /// number (1–6) per syllable, e.g. `[2, 1, 2]` for 廣東話.
+    pub tones: bool,
+    /// Tone sandhi rules to apply, by name (default none): "kinship"
+    /// (哥哥 go4 go1), "familiar" (阿陳 aa3 can2) and "reduplication"
+    /// (慢慢哋 maan6 maan2 dei2). Tokens a rule changes get
+    /// `underlying_tones` and `surface_tones`; see sandhi.rs.
+    pub sandhi: Vec<SandhiRule>,
+    /// Output each token as a `[word, jyutping, yale]` array instead of an
+    /// object (see output.rs).
+    pub compact: bool,
+    /// Merge each run of whitespace (spaces, tabs, newlines) into one
+    /// token whose `word` is the run exactly as in the input, for fewer
+    /// tokens on heavily formatted documents.
+    pub merge_whitespace: bool,
+    /// Leave out null fields (trailing nulls in compact mode).
+    pub omit_null: bool,
+    /// Object key style: "snake" (default, `jyutping_syllables`) or "camel"
+    /// (`jyutpingSyllables`).
+    pub key_case: KeyCase,
+    /// Output format of `annotate_with`: "json" (default), or one of the
+    /// text formats in output.rs: "interlinear" (words over readings),
+    /// "bracketed" (readings in brackets), "ssml" (IPA phoneme tags),
+    /// "html" (`<ruby>` markup) or "table" (`[word, reading]` rows as JSON,
+    /// for Typst tables).
+    pub format: OutputFormat,
+    /// Romanization system by name (see romanize/mod.rs): "jyutping"
+    /// (default), "jyutping_diacritics", "yale", "yale_numeric", "yale_plain",
+    /// "cantonese_pinyin", "sidney_lau", "guangdong", "hk_government", "ipa",
+    /// "xsampa", "espeak", "braille", "chao" or "chao_letters". Used by the
+    /// plain-text formats; for any other system than Jyutping, JSON tokens
+    /// also get a `romanized` array. Unknown names fall back to Jyutping.
+    pub romanization: String,
+    /// Write unchecked tone 1 in Yale as the high falling variant with a
+    /// grave ("sì") instead of the high level macron ("sī"), as classic Yale
+    /// textbooks do. Jyutping data does not record which variant a word
+    /// uses, so this applies to every such syllable.
+    pub high_falling: bool,
+    /// Keep the low-register h in `"yale_plain"` ("yuh"); false drops it
+    /// too ("yu"), leaving bare letters.
+    pub register_h: bool,
+    /// Return Yale and other romanized spellings decomposed (NFD: base
+    /// letter plus combining mark) instead of composed (NFC), for hosts that
+    /// do their own shaping or diff decomposed text.
+    pub nfd: bool,
+    /// Opening and closing bracket for the "bracketed" format.
+    pub brackets: (String, String),
+    /// Reading placement: after each "word" (default, group ruby) or after
+    /// each "char" when syllables and characters line up (mono ruby), as
+    /// publishers differ. With "char", such words also come back as one
+    /// JSON token per character (see `token::split_by_char`). "group" and
+    /// "mono" are accepted as names too.
+    pub ruby: RubyPlacement,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    Interlinear,
+    Bracketed,
+    Ssml,
+    Html,
+    Table,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    #[default]
+    Text,
+    Markdown,
+    Typst,
+    Html,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RubyPlacement {
+    #[default]
+    #[serde(alias = "group")]
+    Word,
+    #[serde(alias = "mono")]
+    Char,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyCase {
+    #[default]
+    Snake,
+    Camel,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum KnownMode {
+    #[default]
+    Hide,
+    Flag,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scoring {
+    #[default]
+    Fewest,
+    Longest,
+    Frequency,
+    Unigram,
+    Bigram,
+    Forward,
+    Backward,
+}
+
+/// A tone change of spoken Cantonese, for the `sandhi` option.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SandhiRule {
+    /// Doubled kinship terms: 21 + 55 or 21 + 35 (哥哥, 妹妹).
+    Kinship,
+    /// 阿 before a one-character name: a low tone rises to 35 (阿陳).
+    Familiar,
+    /// Reduplicated adjectives with 哋: the second syllable and 哋 rise
+    /// to 35 (紅紅哋).
+    Reduplication,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RhymeTones {
+    #[default]
+    Ignore,
+    Class,
+    Exact,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Normalization {
+    None,
+    #[default]
+    Nfc,
+    Nfkc,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            cache: true,
+            normalization: Normalization::default(),
+            scoring: Scoring::default(),
+            max_word_length: None,
+            connectors: CONNECTORS.to_string(),
+            overrides: BTreeMap::new(),
+            readings_at: BTreeMap::new(),
+            inline_readings: false,
+            numerals: false,
+            particles: false,
+            strokes: false,
+            guess_readings: false,
+            input: InputFormat::default(),
+            max_level: 2,
+            rhyme_tones: RhymeTones::default(),
+            freq: false,
+            classifiers: false,
+            lang: false,
+            weights: false,
+            readings: false,
+            rare_below: None,
+            only_rare: false,
+            known_words: HashSet::new(),
+            known_mode: KnownMode::default(),
+            cloze: None,
+            seed: 0,
+            tones: false,
+            sandhi: Vec::new(),
+            compact: false,
+            merge_whitespace: false,
+            omit_null: false,
+            key_case: KeyCase::default(),
+            format: OutputFormat::default(),
+            romanization: "jyutping".to_string(),
+            high_falling: false,
+            register_h: true,
+            nfd: false,
+            brackets: ("(".to_string(), ")".to_string()),
+            ruby: RubyPlacement::default(),
+        }
+    }
+}
+
+impl Options {
+    /// Parse options JSON. Empty or invalid input gives the defaults;
+    /// unknown option names are ignored, and an unknown romanization
+    /// system is read as Jyutping.
+    pub fn parse(input: &[u8]) -> Self {
+        serde_json::from_slice(input).unwrap_or_default()
+    }
+
+    /// Parse options JSON, failing on invalid JSON, values of the wrong
+    /// type, unknown option names and unknown romanization systems. Empty
+    /// input gives the defaults.
+    pub fn try_parse(input: &[u8]) -> Result<Self, String> {
+        if input.trim_ascii().is_empty() {
+            return Ok(Options::default());
+        }
+        let value: serde_json::Value = serde_json::from_slice(input).map_err(|e| e.to_string())?;
+        // the option names are the keys the defaults serialize to
+        if let (Some(given), Ok(serde_json::Value::Object(known))) =
+            (value.as_object(), serde_json::to_value(Options::default()))
+            && let Some(key) = given.keys().find(|k| !known.contains_key(*k))
+        {
+            return Err(format!("unknown option: {:?}", key));
+        }
+        let options: Options = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        if romanize::by_name(&options.romanization).is_none() {
+            return Err(format!("unknown romanization: {:?}", options.romanization));
+        }
+        Ok(options)
+    }
+
+    /// Cache key for `text` under these options. Every option that changes
+    /// segmentation or readings has to be part of it.
+    pub fn cache_key(&self, text: &str) -> String {
+        format!(
+            "{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{:?}\u{0}{}",
+            self.input,
+            self.normalization,
+            self.scoring,
+            self.max_word_length,
+            self.connectors,
+            self.overrides,
+            self.readings_at,
+            self.inline_readings,
+            self.numerals,
+            self.particles,
+            self.strokes,
+            self.guess_readings,
+            text
+        )
+    }
+
+    /// A romanized spelling (NFC) in the Unicode form selected by `nfd`.
+    pub fn unicode_form(&self, s: String) -> String {
+        if self.nfd { s.nfd().collect() } else { s }
+    }
+
+    /// The segmenter's `max_word_length` and `connectors`.
+    pub fn matching(&self) -> Matching<'_> {
+        Matching {
+            max_word_length: self.max_word_length,
+            connectors: &self.connectors,
+        }
+    }
+
+    /// The romanization system selected by `romanization`.
+    pub fn romanizer(&self) -> &'static dyn Romanizer {
+        match romanize::by_name(&self.romanization) {
+            Some(r) if self.high_falling && r.name() == "yale" => &romanize::yale::YaleHighFalling,
+            Some(r) if !self.register_h && r.name() == "yale_plain" => {
+                &romanize::yale::YalePlainNoH
+            }
+            Some(r) => r,
+            None => &romanize::Jyutping,
+        }
+    }
+}
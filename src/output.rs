@@ -0,0 +1,471 @@
+use serde_json::Value;
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::options::{InputFormat, KeyCase, Options, RubyPlacement};
+use crate::romanize;
+use crate::token::Token;
+use crate::utils::{capitalize, display_width};
+
+/// Serialize tokens as JSON under the output-shape options:
+/// - `compact`: each token becomes `[word, jyutping, yale]`; the optional
+///   fields (freq, rare, known, ...) are left out
+/// - `omit_null`: drop null fields (in compact mode, trailing nulls)
+/// - `key_case`: "snake" (default) or "camel" object keys
+pub fn tokens_to_json(tokens: &[Token], options: &Options) -> String {
+    if !options.compact && !options.omit_null && options.key_case == KeyCase::Snake {
+        return serde_json::to_string(tokens).unwrap_or_else(|_| "[]".to_string());
+    }
+
+    let values: Vec<Value> = tokens
+        .iter()
+        .map(|t| {
+            if options.compact {
+                compact_token(t, options.omit_null)
+            } else {
+                reshape(serde_json::to_value(t).unwrap_or(Value::Null), options)
+            }
+        })
+        .collect();
+    serde_json::to_string(&values).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn compact_token(t: &Token, omit_null: bool) -> Value {
+    let mut fields = vec![
+        Value::from(t.word.as_str()),
+        t.reading.as_deref().map_or(Value::Null, Value::from),
+        t.yale
+            .as_ref()
+            .map_or(Value::Null, |y| Value::from(y.clone())),
+    ];
+    if omit_null {
+        while fields.last().is_some_and(Value::is_null) {
+            fields.pop();
+        }
+    }
+    Value::Array(fields)
+}
+
+fn reshape(value: Value, options: &Options) -> Value {
+    let Value::Object(map) = value else {
+        return value;
+    };
+    map.into_iter()
+        .filter(|(_, v)| !(options.omit_null && v.is_null()))
+        .map(|(k, v)| match options.key_case {
+            KeyCase::Snake => (k, v),
+            KeyCase::Camel => (to_camel(&k), v),
+        })
+        .collect()
+}
+
+/// "jyutping_syllables" → "jyutpingSyllables"
+fn to_camel(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for ch in key.chars() {
+        if ch == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(ch.to_uppercase());
+            upper = false;
+        } else {
+            out.push(ch);
+        }
+    }
+    out
+}
+
+/// Render tokens as interlinear plain text: each input line becomes a
+/// line of words over a line of readings, every column padded to the wider
+/// of the two, with a blank line between blocks. Whitespace tokens only
+/// separate columns.
+///
+/// ```text
+/// 今日      我   要   上堂
+/// gam1 jat6 ngo5 jiu3 soeng5 tong4
+/// ```
+pub fn interlinear(tokens: &[Token], options: &Options) -> String {
+    let mut blocks = Vec::new();
+    for line in tokens.split(|t| t.word.contains('\n')) {
+        let mut top = String::new();
+        let mut bottom = String::new();
+        for token in line.iter().filter(|t| !t.word.trim().is_empty()) {
+            let reading = syllables(token, options)
+                .map(|s| s.join(" "))
+                .unwrap_or_default();
+            let reading = reading.as_str();
+            let width = display_width(&token.word).max(display_width(reading));
+            if !top.is_empty() {
+                top.push(' ');
+                bottom.push(' ');
+            }
+            pad(&mut top, &token.word, width);
+            pad(&mut bottom, reading, width);
+        }
+        blocks.push(format!("{}\n{}\n", top.trim_end(), bottom.trim_end()));
+    }
+    blocks.join("\n")
+}
+
+/// Render tokens as the original text with readings in brackets:
+/// "學生(hok6 saang1)" per word, or "學(hok6)生(saang1)" per character when
+/// the word has exactly one syllable per grapheme; otherwise per word.
+pub fn bracketed(tokens: &[Token], options: &Options) -> String {
+    let (open, close) = &options.brackets;
+    let mut out = String::new();
+    for token in tokens {
+        let Some(syllables) = syllables(token, options) else {
+            out.push_str(&token.word);
+            continue;
+        };
+        let graphemes: Vec<&str> = token.word.graphemes(true).collect();
+        if options.ruby == RubyPlacement::Char && graphemes.len() == syllables.len() {
+            for (g, s) in graphemes.iter().zip(&syllables) {
+                out.push_str(g);
+                out.push_str(open);
+                out.push_str(s);
+                out.push_str(close);
+            }
+        } else {
+            out.push_str(&token.word);
+            out.push_str(open);
+            out.push_str(&syllables.join(" "));
+            out.push_str(close);
+        }
+    }
+    out
+}
+
+/// Wrap tokens in an SSML document for text-to-speech: every token with a
+/// reading becomes `<phoneme alphabet="ipa" ph="...">word</phoneme>`, with
+/// its syllables separated by "."; other text is copied, XML-escaped.
+pub fn ssml(tokens: &[Token]) -> String {
+    let mut out = String::from(
+        "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+         xml:lang=\"yue-HK\">",
+    );
+    for token in tokens {
+        match &token.reading {
+            Some(reading) => {
+                let ipa = romanize::romanize(reading, &romanize::ipa::Ipa).join(".");
+                out.push_str("<phoneme alphabet=\"ipa\" ph=\"");
+                push_escaped(&mut out, &ipa);
+                out.push_str("\">");
+                push_escaped(&mut out, &token.word);
+                out.push_str("</phoneme>");
+            }
+            None => push_escaped(&mut out, &token.word),
+        }
+    }
+    out.push_str("</speak>");
+    out
+}
+
+/// Render tokens as HTML with a `<ruby>` element around every word that has
+/// a reading: `<ruby>學生<rt>hok6 saang1</rt></ruby>`, or one `<rt>` per
+/// character as in `bracketed`. Other text is HTML-escaped, except with
+/// `"input": "html"`, where it is already HTML and copied as it is.
+pub fn html(tokens: &[Token], options: &Options) -> String {
+    let source = options.input == InputFormat::Html;
+    let push_text = |out: &mut String, s: &str| match source {
+        true => out.push_str(s),
+        false => push_escaped(out, s),
+    };
+    let mut out = String::new();
+    for token in tokens {
+        let Some(syllables) = syllables(token, options) else {
+            push_text(&mut out, &token.word);
+            continue;
+        };
+        let graphemes: Vec<&str> = token.word.graphemes(true).collect();
+        let pairs = if options.ruby == RubyPlacement::Char && graphemes.len() == syllables.len() {
+            graphemes.into_iter().zip(syllables).collect()
+        } else {
+            vec![(token.word.as_str(), syllables.join(" "))]
+        };
+        out.push_str("<ruby>");
+        for (text, reading) in pairs {
+            push_text(&mut out, text);
+            out.push_str("<rt>");
+            push_escaped(&mut out, &reading);
+            out.push_str("</rt>");
+        }
+        out.push_str("</ruby>");
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, s: &str) {
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            ch => out.push(ch),
+        }
+    }
+}
+
+/// Render tokens as braille text: each word with a reading becomes its
+/// syllables in braille cells (see romanize/braille.rs), spaces become blank
+/// cells, and words without a reading are kept as they are.
+pub fn braille(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    let mut prev_read = false;
+    for token in tokens {
+        match &token.reading {
+            Some(reading) => {
+                // keep adjacent words apart, as spaces do in romanized text
+                if prev_read {
+                    out.push(romanize::braille::BLANK);
+                }
+                out.extend(romanize::romanize(reading, &romanize::braille::Braille));
+            }
+            None => out.extend(token.word.chars().map(|c| match c {
+                ' ' => romanize::braille::BLANK,
+                c => c,
+            })),
+        }
+        prev_read = token.reading.is_some();
+    }
+    out
+}
+
+/// Render tokens as rows for Typst's `table` and `grid`: a JSON array of
+/// `[word, reading]` arrays, one per distinct word with a reading, in order
+/// of first appearance, the reading in the chosen romanization.
+///
+/// ```typst
+/// #table(columns: 2, ..json(canto.annotate_with(text, opts)).flatten())
+/// ```
+pub fn table(tokens: &[Token], options: &Options) -> String {
+    let mut seen = HashSet::new();
+    let rows: Vec<[String; 2]> = tokens
+        .iter()
+        .filter_map(|t| Some([t.word.clone(), syllables(t, options)?.join(" ")]))
+        .filter(|row| seen.insert(row.clone()))
+        .collect();
+    serde_json::to_string(&rows).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// The token's reading as syllables in the chosen romanization.
+fn syllables(token: &Token, options: &Options) -> Option<Vec<String>> {
+    let reading = token.reading.as_deref()?;
+    let system = options.romanizer();
+    // Jyutping stays lower case, even for proper nouns
+    let capitals = token.proper_noun == Some(true) && system.name() != "jyutping";
+    Some(
+        romanize::romanize(reading, system)
+            .into_iter()
+            .map(|s| if capitals { capitalize(&s) } else { s })
+            .map(|s| options.unicode_form(s))
+            .collect(),
+    )
+}
+
+fn pad(out: &mut String, s: &str, width: usize) {
+    out.push_str(s);
+    out.extend(std::iter::repeat_n(
+        ' ',
+        width.saturating_sub(display_width(s)),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_to_json() {
+        let tokens = [
+            Token {
+                word: "好".into(),
+                reading: Some("hou2".into()),
+                yale: Some(vec!["hóu".into()]),
+                ..Default::default()
+            },
+            Token {
+                word: "，".into(),
+                ..Default::default()
+            },
+        ];
+        let json = |o: &str| -> Value {
+            let options: Options = serde_json::from_str(o).unwrap();
+            serde_json::from_str(&tokens_to_json(&tokens, &options)).unwrap()
+        };
+
+        assert_eq!(
+            json("{}"),
+            serde_json::json!([
+                {"word": "好", "jyutping": "hou2", "yale": ["hóu"]},
+                {"word": "，", "jyutping": null, "yale": null},
+            ])
+        );
+        assert_eq!(
+            json(r#"{"omit_null": true}"#),
+            serde_json::json!([
+                {"word": "好", "jyutping": "hou2", "yale": ["hóu"]},
+                {"word": "，"},
+            ])
+        );
+        assert_eq!(
+            json(r#"{"compact": true}"#),
+            serde_json::json!([["好", "hou2", ["hóu"]], ["，", null, null]])
+        );
+        assert_eq!(
+            json(r#"{"compact": true, "omit_null": true}"#),
+            serde_json::json!([["好", "hou2", ["hóu"]], ["，"]])
+        );
+        assert_eq!(to_camel("jyutping_syllables"), "jyutpingSyllables");
+    }
+
+    #[test]
+    fn test_bracketed() {
+        let tokens = [
+            Token {
+                word: "學生".into(),
+                reading: Some("hok6 saang1".into()),
+                yale: Some(vec!["hohk".into(), "sāang".into()]),
+                ..Default::default()
+            },
+            Token {
+                word: "做part-time".into(),
+                reading: Some("zou6 paat1 taai1".into()),
+                ..Default::default()
+            },
+            Token {
+                word: "！".into(),
+                ..Default::default()
+            },
+        ];
+        let render = |o: &str| bracketed(&tokens, &serde_json::from_str(o).unwrap());
+
+        assert_eq!(
+            render("{}"),
+            "學生(hok6 saang1)做part-time(zou6 paat1 taai1)！"
+        );
+        // per char where syllables line up, per word otherwise
+        assert_eq!(
+            render(r#"{"ruby": "char", "brackets": ["【", "】"]}"#),
+            "學【hok6】生【saang1】做part-time【zou6 paat1 taai1】！"
+        );
+        assert_eq!(
+            render(r#"{"romanization": "yale"}"#),
+            "學生(hohk sāang)做part-time(jouh pāat tāai)！"
+        );
+        assert_eq!(
+            render(r#"{"romanization": "yale", "nfd": true}"#),
+            "學生(hohk sa\u{304}ang)做part-time(jouh pa\u{304}at ta\u{304}ai)！"
+        );
+    }
+
+    #[test]
+    fn test_ssml() {
+        let tokens = [
+            Token {
+                word: "你好".into(),
+                reading: Some("nei5 hou2".into()),
+                ..Default::default()
+            },
+            Token {
+                word: " <b>".into(),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            ssml(&tokens),
+            "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" \
+             xml:lang=\"yue-HK\"><phoneme alphabet=\"ipa\" ph=\"nei˩˧.hou˧˥\">你好</phoneme> \
+             &lt;b&gt;</speak>"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let tokens = [
+            Token {
+                word: "學生".into(),
+                reading: Some("hok6 saang1".into()),
+                ..Default::default()
+            },
+            Token {
+                word: "&amp;".into(),
+                ..Default::default()
+            },
+        ];
+        let render = |options: &str| html(&tokens, &Options::parse(options.as_bytes()));
+        assert_eq!(
+            render("{}"),
+            "<ruby>學生<rt>hok6 saang1</rt></ruby>&amp;amp;"
+        );
+        assert_eq!(
+            render(r#"{"input": "html", "ruby": "char"}"#),
+            "<ruby>學<rt>hok6</rt>生<rt>saang1</rt></ruby>&amp;"
+        );
+    }
+
+    #[test]
+    fn test_table() {
+        let token = |word: &str, reading: Option<&str>| Token {
+            word: word.into(),
+            reading: reading.map(Into::into),
+            ..Default::default()
+        };
+        let tokens = [
+            token("學生", Some("hok6 saang1")),
+            token("，", None),
+            token("行", Some("haang4")),
+            token("學生", Some("hok6 saang1")),
+            token("行", Some("hang4")),
+        ];
+        let render = |options: &str| table(&tokens, &Options::parse(options.as_bytes()));
+        assert_eq!(
+            render("{}"),
+            r#"[["學生","hok6 saang1"],["行","haang4"],["行","hang4"]]"#
+        );
+        assert_eq!(
+            render(r#"{"romanization": "yale"}"#),
+            r#"[["學生","hohk sāang"],["行","hàahng"],["行","hàhng"]]"#
+        );
+    }
+
+    #[test]
+    fn test_braille() {
+        let token = |word: &str, reading: Option<&str>| Token {
+            word: word.into(),
+            reading: reading.map(String::from),
+            ..Default::default()
+        };
+        let tokens = [
+            token("你", Some("nei5")),
+            token("好", Some("hou2")),
+            token("！", None),
+            token(" ", None),
+            token("OK", None),
+        ];
+        assert_eq!(braille(&tokens), "⠝⠑⠊⠢\u{2800}⠓⠕⠥⠆！\u{2800}OK");
+    }
+
+    #[test]
+    fn test_interlinear() {
+        let token = |word: &str, reading: Option<&str>| Token {
+            word: word.into(),
+            reading: reading.map(String::from),
+            ..Default::default()
+        };
+        let tokens = [
+            token("今日", Some("gam1 jat6")),
+            token("我", Some("ngo5")),
+            token(" ", None),
+            token("OK", None),
+            token("\n", None),
+            token("好", Some("hou2")),
+        ];
+        assert_eq!(
+            interlinear(&tokens, &Options::default()),
+            "今日      我   OK\ngam1 jat6 ngo5\n\n好\nhou2\n"
+        );
+    }
+}
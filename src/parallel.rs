@@ -0,0 +1,64 @@
+use rayon::prelude::*;
+
+use crate::token::Token;
+
+/// Split `text` after each blank line, so that concatenating the pieces
+/// gives `text` back. No dictionary word contains a line break, so
+/// segmenting the pieces apart gives the same tokens as the whole text.
+fn paragraphs(text: &str) -> Vec<&str> {
+    let mut paragraphs = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+    for line in text.split_inclusive('\n') {
+        end += line.len();
+        if line.trim().is_empty() {
+            paragraphs.push(&text[start..end]);
+            start = end;
+        }
+    }
+    if start < text.len() {
+        paragraphs.push(&text[start..]);
+    }
+    paragraphs
+}
+
+/// Segment `text` paragraph by paragraph on rayon's thread pool (the
+/// `parallel` feature), with the token streams joined in order.
+pub fn segment(text: &str, segment: impl Fn(&str) -> Vec<Token> + Sync) -> Vec<Token> {
+    let paragraphs = paragraphs(text);
+    if paragraphs.len() < 2 {
+        return segment(text);
+    }
+    paragraphs.into_par_iter().flat_map_iter(&segment).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::Trie;
+
+    #[test]
+    fn test_segment() {
+        let text = "你好\n\n\n  我哋\r\n\r\n食飯\n飲茶\n\n";
+        assert_eq!(
+            paragraphs(text),
+            ["你好\n\n", "\n", "  我哋\r\n\r\n", "食飯\n飲茶\n\n"]
+        );
+        assert!(paragraphs("").is_empty());
+
+        let mut trie = Trie::default();
+        for (word, reading) in [
+            ("你好", "nei5 hou2"),
+            ("我哋", "ngo5 dei6"),
+            ("食飯", "sik6 faan6"),
+        ] {
+            trie.insert(word, reading);
+        }
+        let words =
+            |tokens: Vec<Token>| -> Vec<String> { tokens.into_iter().map(|t| t.word).collect() };
+        assert_eq!(
+            words(segment(text, |p| trie.segment(p))),
+            words(trie.segment(text))
+        );
+    }
+}
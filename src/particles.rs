@@ -0,0 +1,235 @@
+use crate::token::Token;
+
+/// Characters that end a sentence as a particle, with their readings as
+/// particles: 㗎 gaa3, 啦 laa1, 喎 wo3, ... A run of them read so (㗎啦喎)
+/// is a particle cluster; the same characters read otherwise are words of
+/// their own, such as 喇嘛 laa1 maa4 and 咯咯 gok4 gok1.
+const PARTICLES: &[(char, &str)] = &[
+    ('啊', "aa1 aa3 aa4"),
+    ('呀', "aa3 aa4"),
+    ('吖', "aa1"),
+    ('啦', "laa1 laa3"),
+    ('喇', "laa1 laa3"),
+    ('嘞', "laa3 laak3 lak3"),
+    ('喎', "wo3 wo4 wo5"),
+    ('噃', "bo3"),
+    ('咩', "me1"),
+    ('嘛', "maa3"),
+    ('咋', "zaa1 zaa3"),
+    ('啫', "ze1 zek1"),
+    ('㗎', "gaa2 gaa3 gaa4"),
+    ('嘅', "ge2 ge3"),
+    ('囉', "lo1 lo3"),
+    ('咯', "lo1 lo3 lok3"),
+    ('啩', "gwaa3"),
+    ('吓', "haa2 haa5"),
+    ('呢', "le1 ne1"),
+    ('嗎', "maa1 maa3"),
+    ('咧', "le2 le3 le5"),
+];
+
+/// Interjections that end in particle characters (哎呀) but are words of
+/// their own, not a word plus a particle.
+const INTERJECTIONS: &str = "哎唉噢哦嘩";
+
+fn is_particle_char(ch: char) -> bool {
+    PARTICLES.iter().any(|&(c, _)| c == ch)
+}
+
+/// Whether `ch` read `syllable` is a particle.
+fn is_particle_syllable(ch: char, syllable: &str) -> bool {
+    PARTICLES
+        .iter()
+        .any(|&(c, readings)| c == ch && readings.split(' ').any(|r| r == syllable))
+}
+
+/// A particle, or a cluster of them. A single particle character counts
+/// whatever its reading, but a word of several only if each is read as a
+/// particle.
+fn is_particle(token: &Token) -> bool {
+    let chars: Vec<char> = token.word.chars().collect();
+    match chars[..] {
+        [] => false,
+        [ch] => is_particle_char(ch),
+        _ => {
+            let syllables: Vec<&str> = token
+                .reading
+                .as_deref()
+                .unwrap_or_default()
+                .split(' ')
+                .collect();
+            syllables.len() == chars.len()
+                && chars
+                    .iter()
+                    .zip(syllables)
+                    .all(|(&c, s)| is_particle_syllable(c, s))
+        }
+    }
+}
+
+/// Punctuation, whitespace, emoji: anything that can follow a sentence.
+fn is_break(word: &str) -> bool {
+    !word.chars().any(char::is_alphanumeric)
+}
+
+/// Tag sentence-final particles (the `particles` option): tokens made of
+/// particle characters at the end of a sentence, before punctuation or the
+/// end of the text, get `particle: true`. A word the dictionary merged
+/// particles into (畀啦, 得㗎) is split first, if it has one syllable per
+/// character, so that 你畀啦！ gives 畀 and 啦 rather than 畀啦.
+pub fn tag_particles(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    // whether only particles and breaks follow, up to the end of the sentence
+    let mut at_end = true;
+    for mut token in tokens.into_iter().rev() {
+        if is_break(&token.word) {
+            at_end = true;
+        } else if at_end && token.reading.is_some() {
+            if is_particle(&token) {
+                token.particle = Some(true);
+            } else {
+                if let Some(mut particle) = split_particles(&mut token) {
+                    particle.particle = Some(true);
+                    out.push(particle);
+                }
+                at_end = false;
+            }
+        } else {
+            at_end = false;
+        }
+        out.push(token);
+    }
+    out.reverse();
+    out
+}
+
+/// Split the particles off the end of `token`, returning them as a token
+/// of their own with their syllables of the reading. Both parts keep what
+/// the dictionary says of the word (frequency, proper noun, classifiers,
+/// ...).
+fn split_particles(token: &mut Token) -> Option<Token> {
+    let chars: Vec<char> = token.word.chars().collect();
+    let reading = token.reading.as_deref()?;
+    let syllables: Vec<&str> = reading.split(' ').collect();
+    if syllables.len() != chars.len() {
+        return None;
+    }
+    let suffix = chars
+        .iter()
+        .zip(&syllables)
+        .rev()
+        .take_while(|&(&c, s)| is_particle_syllable(c, s))
+        .count();
+    let prefix = chars.len() - suffix;
+    if suffix == 0 || chars[..prefix].iter().all(|&c| INTERJECTIONS.contains(c)) {
+        return None;
+    }
+    let part = |chars: &[char], syllables: &[&str]| Token {
+        word: chars.iter().collect(),
+        reading: Some(syllables.join(" ")),
+        freq: token.freq,
+        guessed: token.guessed,
+        proper_noun: token.proper_noun,
+        classifiers: token.classifiers.clone(),
+        category: token.category,
+        ..Default::default()
+    };
+    let particle = part(&chars[prefix..], &syllables[prefix..]);
+    *token = part(&chars[..prefix], &syllables[..prefix]);
+    Some(particle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(spec: &[(&str, Option<&str>)]) -> Vec<Token> {
+        spec.iter()
+            .map(|&(word, reading)| Token {
+                word: word.to_string(),
+                reading: reading.map(str::to_string),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tag_particles() {
+        let tagged = tag_particles(tokens(&[
+            ("你", Some("nei5")),
+            ("畀啦", Some("bei2 laa1")),
+            ("！", None),
+            ("佢", Some("keoi5")),
+            ("話", Some("waa6")),
+            ("啦", Some("laa1")),
+            ("你", Some("nei5")),
+            ("。", None),
+            ("得", Some("dak1")),
+            ("㗎啦", Some("gaa3 laa1")),
+            ("喎", Some("wo3")),
+            ("。", None),
+            ("哎呀", Some("aai1 aa4")),
+            ("！", None),
+            ("佢", Some("keoi5")),
+            ("係", Some("hai6")),
+            ("喇嘛", Some("laa1 maa4")),
+            ("。", None),
+            ("大", Some("daai6")),
+            ("喇嘛", Some("laa1 maa4")),
+        ]));
+        let summary: Vec<(&str, Option<&str>, bool)> = tagged
+            .iter()
+            .map(|t| {
+                (
+                    t.word.as_str(),
+                    t.reading.as_deref(),
+                    t.particle == Some(true),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("你", Some("nei5"), false),
+                ("畀", Some("bei2"), false),
+                ("啦", Some("laa1"), true),
+                ("！", None, false),
+                ("佢", Some("keoi5"), false),
+                ("話", Some("waa6"), false),
+                ("啦", Some("laa1"), false),
+                ("你", Some("nei5"), false),
+                ("。", None, false),
+                ("得", Some("dak1"), false),
+                ("㗎啦", Some("gaa3 laa1"), true),
+                ("喎", Some("wo3"), true),
+                ("。", None, false),
+                ("哎呀", Some("aai1 aa4"), false),
+                ("！", None, false),
+                ("佢", Some("keoi5"), false),
+                ("係", Some("hai6"), false),
+                ("喇嘛", Some("laa1 maa4"), false),
+                ("。", None, false),
+                ("大", Some("daai6"), false),
+                ("喇嘛", Some("laa1 maa4"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_keeps_fields() {
+        let mut tokens = tokens(&[("得㗎", Some("dak1 gaa3"))]);
+        tokens[0].freq = Some(1234);
+        tokens[0].proper_noun = Some(true);
+        tokens[0].classifiers = Some(vec!["個".into()]);
+        tokens[0].guessed = Some(true);
+        let tagged = tag_particles(tokens);
+        assert_eq!(tagged.len(), 2);
+        for t in &tagged {
+            assert_eq!(t.freq, Some(1234));
+            assert_eq!(t.proper_noun, Some(true));
+            assert_eq!(t.classifiers.as_deref(), Some(&["個".to_string()][..]));
+            assert_eq!(t.guessed, Some(true));
+        }
+        assert_eq!(tagged[1].particle, Some(true));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::Serialize;
+
+use crate::token::Token;
+
+/// Lowest corpus frequency (freq.txt) for levels 1, 2 and 3; anything rarer
+/// is level 4. Level 1 is roughly the 2,500 most frequent words, level 2 the
+/// next 20,000.
+const FREQ_BANDS: [i64; 3] = [10_000, 1_000, 200];
+
+/// Hardest level a word can get from frequency alone.
+pub const MAX_FREQ_LEVEL: u8 = FREQ_BANDS.len() as u8 + 1;
+
+/// Learner level of a word from its corpus frequency: 1 (common) to
+/// `MAX_FREQ_LEVEL` (rare or unlisted).
+pub fn freq_level(freq: i64) -> u8 {
+    FREQ_BANDS
+        .iter()
+        .position(|&min| freq >= min)
+        .map_or(MAX_FREQ_LEVEL, |i| i as u8 + 1)
+}
+
+/// A word above the target level.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Flagged {
+    pub word: String,
+    pub jyutping: String,
+    pub level: u8,
+    /// Character offsets of every occurrence.
+    pub positions: Vec<usize>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Report {
+    /// Number of word tokens (tokens with a reading).
+    pub words: usize,
+    /// Target level the passage was checked against.
+    pub max_level: u8,
+    /// Share of word tokens at or below `max_level`, 0.0–1.0 (1.0 if empty).
+    pub within_level: f64,
+    /// Mean level over all word tokens (0.0 if empty).
+    pub mean_level: f64,
+    /// Distinct words above `max_level`, in order of first appearance.
+    pub flagged: Vec<Flagged>,
+}
+
+/// Score a segmented passage against `max_level`, with `level_of` giving
+/// each word's level.
+pub fn assess(tokens: &[Token], level_of: impl Fn(&str) -> u8, max_level: u8) -> Report {
+    let mut words = 0;
+    let mut within = 0;
+    let mut level_sum = 0u64;
+    let mut flagged: Vec<Flagged> = Vec::new();
+
+    let mut offset = 0;
+    for token in tokens {
+        let start = offset;
+        offset += token.word.chars().count();
+        let Some(reading) = &token.reading else {
+            continue;
+        };
+        let level = level_of(&token.word);
+        words += 1;
+        level_sum += u64::from(level);
+        if level <= max_level {
+            within += 1;
+            continue;
+        }
+        match flagged.iter_mut().find(|f| f.word == token.word) {
+            Some(f) => f.positions.push(start),
+            None => flagged.push(Flagged {
+                word: token.word.clone(),
+                jyutping: reading.clone(),
+                level,
+                positions: vec![start],
+            }),
+        }
+    }
+
+    Report {
+        words,
+        max_level,
+        within_level: if words == 0 {
+            1.0
+        } else {
+            within as f64 / words as f64
+        },
+        mean_level: if words == 0 {
+            0.0
+        } else {
+            level_sum as f64 / words as f64
+        },
+        flagged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freq_level() {
+        assert_eq!(freq_level(12_831_921), 1);
+        assert_eq!(freq_level(10_000), 1);
+        assert_eq!(freq_level(9_999), 2);
+        assert_eq!(freq_level(511), 3);
+        assert_eq!(freq_level(0), MAX_FREQ_LEVEL);
+    }
+
+    #[test]
+    fn test_assess() {
+        let token = |word: &str, reading: Option<&str>| Token {
+            word: word.to_string(),
+            reading: reading.map(String::from),
+            ..Default::default()
+        };
+        let tokens = [
+            token("我", Some("ngo5")),
+            token("囉唆", Some("lo1 so1")),
+            token("，", None),
+            token("囉唆", Some("lo1 so1")),
+        ];
+        let level_of = |w: &str| if w == "我" { 1 } else { 3 };
+        let report = assess(&tokens, level_of, 2);
+        assert_eq!(report.words, 3);
+        assert!((report.within_level - 1.0 / 3.0).abs() < 1e-9);
+        assert!((report.mean_level - 7.0 / 3.0).abs() < 1e-9);
+        assert_eq!(
+            report.flagged,
+            [Flagged {
+                word: "囉唆".into(),
+                jyutping: "lo1 so1".into(),
+                level: 3,
+                positions: vec![1, 4],
+            }]
+        );
+
+        let empty = assess(&[], level_of, 2);
+        assert_eq!((empty.words, empty.within_level), (0, 1.0));
+    }
+}
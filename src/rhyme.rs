@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::jyutping::Syllable;
+use crate::options::RhymeTones;
+use crate::token::Token;
+
+/// One line of verse and the syllable it ends on.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RhymeLine {
+    /// Index of the line in the input, counting blank lines.
+    pub line: usize,
+    pub text: String,
+    /// Last syllable with a reading, None if the line has none.
+    pub syllable: Option<String>,
+    /// Final of that syllable, e.g. "oeng".
+    pub rime: Option<String>,
+    /// Rhyme group, "A" for the first, then "B", ...; lines ending in the
+    /// same rime (and tone, by `rhyme_tones`) share a label.
+    pub label: Option<String>,
+}
+
+/// Result of `analyze`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Rhyme {
+    pub lines: Vec<RhymeLine>,
+    /// The labels in order, e.g. "AABA"; "-" for a line without a reading.
+    pub scheme: String,
+}
+
+/// Rhyme group key of a syllable: its final, plus the tone under `tones`.
+/// In `Class`, tones 1 and 4 are level (平) and the rest, including every
+/// checked syllable, oblique (仄).
+fn rhyme_key(syllable: &Syllable, tones: RhymeTones) -> String {
+    let checked = syllable.rime.ends_with(['p', 't', 'k']);
+    match tones {
+        RhymeTones::Ignore => syllable.rime.to_string(),
+        RhymeTones::Class if matches!(syllable.tone, 1 | 4) && !checked => {
+            format!("{} level", syllable.rime)
+        }
+        RhymeTones::Class => format!("{} oblique", syllable.rime),
+        RhymeTones::Exact => format!("{}{}", syllable.rime, syllable.tone),
+    }
+}
+
+/// Spreadsheet-style label for the `n`th rhyme group: A … Z, AA, AB, ...
+fn label(mut n: usize) -> String {
+    let mut out = Vec::new();
+    loop {
+        out.push(b'A' + (n % 26) as u8);
+        if n < 26 {
+            break;
+        }
+        n = n / 26 - 1;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap_or_default()
+}
+
+/// Rhyme scheme of verse or lyrics, one line per input line: each line's
+/// last syllable with a reading is grouped by its final (and tone, by
+/// `tones`). Blank lines are skipped. `segment` annotates one line.
+pub fn analyze(text: &str, tones: RhymeTones, segment: impl Fn(&str) -> Vec<Token>) -> Rhyme {
+    let mut groups: HashMap<String, String> = HashMap::new();
+    let mut lines = Vec::new();
+    let mut scheme = String::new();
+    for (i, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let last = segment(line)
+            .into_iter()
+            .rev()
+            .filter_map(|t| t.reading)
+            .find_map(|r| r.split_whitespace().last().and_then(Syllable::parse));
+        let label = last.map(|s| {
+            let next = label(groups.len());
+            groups.entry(rhyme_key(&s, tones)).or_insert(next).clone()
+        });
+        scheme.push_str(label.as_deref().unwrap_or("-"));
+        lines.push(RhymeLine {
+            line: i,
+            text: line.to_string(),
+            syllable: last.map(|s| s.to_string()),
+            rime: last.map(|s| s.rime.to_string()),
+            label,
+        });
+    }
+    Rhyme { lines, scheme }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(line: &str) -> Vec<Token> {
+        // one token per character, reading from a tiny table
+        line.chars()
+            .map(|c| Token {
+                word: c.to_string(),
+                reading: match c {
+                    '光' => Some("gwong1"),
+                    '霜' => Some("soeng1"),
+                    '月' => Some("jyut6"),
+                    '鄉' => Some("hoeng1"),
+                    '上' => Some("soeng6"),
+                    _ => None,
+                }
+                .map(String::from),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_analyze() {
+        let verse = "床前明月光，\n疑是地上霜。\n\n舉頭望明月，\n低頭思故鄉。\n？";
+        let rhyme = analyze(verse, RhymeTones::Ignore, segment);
+        assert_eq!(rhyme.scheme, "ABCB-");
+        assert_eq!(rhyme.lines[1].syllable.as_deref(), Some("soeng1"));
+        assert_eq!(rhyme.lines[2].line, 3);
+
+        assert_eq!(analyze("霜\n上", RhymeTones::Ignore, segment).scheme, "AA");
+        assert_eq!(analyze("霜\n上", RhymeTones::Class, segment).scheme, "AB");
+        assert_eq!(analyze("霜\n鄉", RhymeTones::Exact, segment).scheme, "AA");
+        assert_eq!(label(25), "Z");
+        assert_eq!(label(26), "AA");
+    }
+}
@@ -0,0 +1,56 @@
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Uncontracted braille letters a–z (Unicode braille patterns).
+const LETTERS: [char; 26] = [
+    '⠁', '⠃', '⠉', '⠙', '⠑', '⠋', '⠛', '⠓', '⠊', '⠚', '⠅', '⠇', '⠍', '⠝', '⠕', '⠏', '⠟', '⠗', '⠎',
+    '⠞', '⠥', '⠧', '⠺', '⠭', '⠽', '⠵',
+];
+
+/// Lower-cell digits 1–6 for the tone: they cannot be mistaken for letters,
+/// so no number sign is needed between syllables.
+const TONES: [char; 6] = ['⠂', '⠆', '⠒', '⠲', '⠢', '⠖'];
+
+/// Blank braille cell, used between words.
+pub const BLANK: char = '\u{2800}';
+
+/// Jyutping in braille cells: the letters of the syllable, uncontracted,
+/// then its tone as a lower-cell digit, e.g. gwong2 → ⠛⠺⠕⠝⠛⠆. Output only.
+pub struct Braille;
+
+impl Romanizer for Braille {
+    fn name(&self) -> &'static str {
+        "braille"
+    }
+
+    fn cased(&self) -> bool {
+        false
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mut out: String = syllable
+            .onset
+            .bytes()
+            .chain(syllable.rime.bytes())
+            .map(|b| LETTERS[(b - b'a') as usize])
+            .collect();
+        if let t @ 1..=6 = syllable.tone {
+            out.push(TONES[t as usize - 1]);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let br = |s| Braille.render(&Syllable::parse(s).unwrap());
+        assert_eq!(br("gwong2"), "⠛⠺⠕⠝⠛⠆");
+        assert_eq!(br("jyut6"), "⠚⠽⠥⠞⠖");
+        assert_eq!(br("m4"), "⠍⠲");
+        assert_eq!(br("si"), "⠎⠊");
+    }
+}
@@ -0,0 +1,94 @@
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Cantonese Pinyin (教院式) initials that differ from Jyutping.
+const INITIALS: [(&str, &str); 2] = [("dz", "z"), ("ts", "c")];
+
+/// Cantonese Pinyin finals that differ from Jyutping.
+const FINALS: [(&str, &str); 7] = [
+    ("a", "aa"),
+    ("oey", "eoi"),
+    ("oen", "eon"),
+    ("oet", "eot"),
+    ("y", "yu"),
+    ("yn", "yun"),
+    ("yt", "yut"),
+];
+
+/// Parse one lowercase Cantonese Pinyin syllable, e.g. "dzoey2" → zeoi2.
+/// The entering tones 7, 8 and 9 become Jyutping 1, 3 and 6.
+pub fn parse(s: &str) -> Option<Syllable> {
+    let (body, tone) = match s.as_bytes().last() {
+        Some(&d @ b'1'..=b'9') => (&s[..s.len() - 1], d - b'0'),
+        _ => (s, 0),
+    };
+    let tone = match tone {
+        7 => 1,
+        8 => 3,
+        9 => 6,
+        t => t,
+    };
+    Syllable::respell(body, tone, &INITIALS, &FINALS)
+}
+
+/// Cantonese Pinyin (教院式), e.g. zeoi2 → dzoey2, sik6 → sik9.
+pub struct CantonesePinyin;
+
+impl Romanizer for CantonesePinyin {
+    fn name(&self) -> &'static str {
+        "cantonese_pinyin"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let onset = respell(&INITIALS, syllable.onset);
+        let rime = respell(&FINALS, syllable.rime);
+        // entering tones: checked syllables use 7, 8, 9 for 1, 3, 6
+        let checked = syllable.rime.ends_with(['p', 't', 'k']);
+        let tone = match (syllable.tone, checked) {
+            (0, _) => String::new(),
+            (1, true) => "7".to_string(),
+            (3, true) => "8".to_string(),
+            (6, true) => "9".to_string(),
+            (t, _) => t.to_string(),
+        };
+        format!("{}{}{}", onset, rime, tone)
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        parse(s)
+    }
+}
+
+/// The spelling in this system of a Jyutping part, via a (system,
+/// Jyutping) table; parts missing from the table are spelled the same.
+pub(super) fn respell<'a>(table: &[(&'a str, &str)], jyutping: &'a str) -> &'a str {
+    table
+        .iter()
+        .find(|&&(_, jp)| jp == jyutping)
+        .map_or(jyutping, |&(spelling, _)| spelling)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jyutping::to_jyutping;
+
+    #[test]
+    fn test_parse() {
+        let convert = |s| to_jyutping(s, parse);
+        assert_eq!(
+            convert("gwong2 dung1 wa2").as_deref(),
+            Ok("gwong2 dung1 waa2")
+        );
+        assert_eq!(
+            convert("dzoey2 tsoen1 jy5").as_deref(),
+            Ok("zeoi2 ceon1 jyu5")
+        );
+        assert_eq!(
+            convert("sik9 jyt9 bat7 gaap8").as_deref(),
+            Ok("sik6 jyut6 bat1 gaap3")
+        );
+        assert_eq!(convert("hoeng1 gong2").as_deref(), Ok("hoeng1 gong2"));
+        assert!(convert("xyz1").is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Chao pitch numerals for Jyutping tones 1–6 (5 high … 1 low).
+const CONTOURS: [&str; 6] = ["55", "25", "33", "21", "23", "22"];
+
+/// Checked syllables (-p/-t/-k) are short, so their level tones 1, 3 and 6
+/// take a single numeral.
+const CHECKED: [&str; 6] = ["5", "25", "3", "21", "23", "2"];
+
+/// Chao contour of a syllable's tone, e.g. 55 for si1 and 5 for sik1; None
+/// without a tone digit.
+pub fn contour(syllable: &Syllable) -> Option<&'static str> {
+    let table = if syllable.rime.ends_with(['p', 't', 'k']) {
+        &CHECKED
+    } else {
+        &CONTOURS
+    };
+    match syllable.tone {
+        t @ 1..=6 => Some(table[t as usize - 1]),
+        _ => None,
+    }
+}
+
+/// Chao tone letter for a pitch numeral.
+fn letter(digit: char) -> char {
+    match digit {
+        '5' => '˥',
+        '4' => '˦',
+        '3' => '˧',
+        '2' => '˨',
+        _ => '˩',
+    }
+}
+
+/// Jyutping with the tone as Chao numerals, for phonetics teaching: si1 →
+/// si55, sik1 → sik5. Output only.
+pub struct ChaoNumerals;
+
+/// Jyutping with the tone as Chao tone letters: si1 → si˥˥, sik1 → sik˥.
+/// Output only.
+pub struct ChaoLetters;
+
+impl Romanizer for ChaoNumerals {
+    fn name(&self) -> &'static str {
+        "chao"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mut out = format!("{}{}", syllable.onset, syllable.rime);
+        out.push_str(contour(syllable).unwrap_or(""));
+        out
+    }
+}
+
+impl Romanizer for ChaoLetters {
+    fn name(&self) -> &'static str {
+        "chao_letters"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mut out = format!("{}{}", syllable.onset, syllable.rime);
+        out.extend(contour(syllable).unwrap_or("").chars().map(letter));
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let syllables = [
+            "si1", "si2", "si3", "si4", "si5", "si6", "sik1", "sik6", "m4", "si",
+        ];
+        let render = |r: &dyn Romanizer| -> Vec<String> {
+            let parse = |s| Syllable::parse(s).unwrap();
+            syllables.iter().map(|s| r.render(&parse(s))).collect()
+        };
+        assert_eq!(
+            render(&ChaoNumerals),
+            [
+                "si55", "si25", "si33", "si21", "si23", "si22", "sik5", "sik2", "m21", "si"
+            ]
+        );
+        assert_eq!(
+            render(&ChaoLetters),
+            [
+                "si˥˥", "si˨˥", "si˧˧", "si˨˩", "si˨˧", "si˨˨", "sik˥", "sik˨", "m˨˩", "si"
+            ]
+        );
+    }
+}
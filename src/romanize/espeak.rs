@@ -0,0 +1,77 @@
+use super::Romanizer;
+use super::ipa::Ipa;
+use crate::jyutping::Syllable;
+
+/// Kirshenbaum (ASCII-IPA) spelling of one IPA symbol, as accepted by
+/// eSpeak NG's phoneme input; None if the symbol is spelled the same.
+fn kirshenbaum(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'ʰ' => "<h>",
+        'ʷ' => "<w>",
+        'ː' => ":",
+        // Kirshenbaum has no ɐ; ʌ is the nearest vowel it names
+        'ɐ' => "V",
+        'ɛ' => "E",
+        'ɔ' => "O",
+        'ŋ' => "N",
+        'œ' => "W",
+        'ɵ' => "@.",
+        '\u{0329}' | '\u{030D}' => "-", // syllabic nasal
+        '\u{031A}' => "",               // unreleased stops are not marked
+        // Chao tone letters become tone digits
+        '˥' => "5",
+        '˦' => "4",
+        '˧' => "3",
+        '˨' => "2",
+        '˩' => "1",
+        _ => return None,
+    })
+}
+
+/// Kirshenbaum-style phoneme strings for eSpeak NG front ends, with the
+/// tone as Chao digits: gwong2 → k<w>O:N35. Output only.
+pub struct Espeak;
+
+impl Romanizer for Espeak {
+    fn name(&self) -> &'static str {
+        "espeak"
+    }
+
+    fn cased(&self) -> bool {
+        false
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mut out = String::new();
+        for ch in Ipa.render(syllable).chars() {
+            match kirshenbaum(ch) {
+                Some(s) => out.push_str(s),
+                None => out.push(ch),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let es = |s| Espeak.render(&Syllable::parse(s).unwrap());
+        assert_eq!(es("gwong2"), "k<w>O:N35");
+        assert_eq!(es("ceoi4"), "ts<h>@.y21");
+        assert_eq!(es("sap6"), "sVp2");
+        assert_eq!(es("hoeng1"), "hW:N5");
+        assert_eq!(es("m4"), "m-21");
+
+        for rime in crate::jyutping::FINALS {
+            for onset in ["", "kw", "c"] {
+                if let Some(s) = Syllable::parse(&format!("{}{}4", onset, rime)) {
+                    assert!(Espeak.render(&s).is_ascii(), "{}", s);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Jyutping final → Guangdong Romanization final. Long aa is written a and
+/// short a is written e; stops are written b, d, g.
+fn rime(rime: &str) -> &str {
+    match rime {
+        "aa" => "a",
+        "aai" => "ai",
+        "aau" => "ao",
+        "aap" => "ab",
+        "aat" => "ad",
+        "aak" => "ag",
+        "a" => "e",
+        "ai" => "ei",
+        "au" => "eo",
+        "am" => "em",
+        "an" => "en",
+        "ang" => "eng",
+        "ap" => "eb",
+        "at" => "ed",
+        "ak" => "eg",
+        "e" => "é",
+        "ei" => "éi",
+        "eu" => "éu",
+        "em" => "ém",
+        "eng" => "éng",
+        "ep" => "éb",
+        "ek" => "ég",
+        "ip" => "ib",
+        "it" => "id",
+        "ik" => "ig",
+        "ot" => "od",
+        "ok" => "og",
+        "ut" => "ud",
+        "uk" => "ug",
+        "oe" => "ê",
+        "oeng" => "êng",
+        "oet" => "êd",
+        "oek" => "êg",
+        "eoi" => "êu",
+        "eon" => "ên",
+        "eot" => "êd",
+        "yu" => "ü",
+        "yun" => "ün",
+        "yut" => "üd",
+        // aam, aan, aang and the i, o, u finals are spelled as in Jyutping
+        rime => rime,
+    }
+}
+
+/// Guangdong Romanization (廣州話拼音方案, 1960), used in mainland
+/// dictionaries and textbooks, e.g. zyu6 → ju6, hoeng1 → hêng1. Output only.
+pub struct Guangdong;
+
+impl Romanizer for Guangdong {
+    fn name(&self) -> &'static str {
+        "guangdong"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let rime = rime(syllable.rime);
+        // z, c, s are written j, q, x before i and ü; Jyutping j is y
+        let palatal = rime.starts_with(['i', 'ü']);
+        let onset = match (syllable.onset, palatal) {
+            ("z", true) => "j",
+            ("c", true) => "q",
+            ("s", true) => "x",
+            ("j", _) => "y",
+            (onset, _) => onset,
+        };
+        // as in pinyin, ü drops its dots after y, j, q, x
+        let rime = match onset {
+            "y" | "j" | "q" | "x" => rime.replacen('ü', "u", 1),
+            _ => rime.to_string(),
+        };
+        match syllable.tone {
+            0 => format!("{}{}", onset, rime),
+            t => format!("{}{}{}", onset, rime, t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let gd = |s| Guangdong.render(&Syllable::parse(s).unwrap());
+        assert_eq!(gd("gwong2"), "gwong2");
+        assert_eq!(gd("gaau3"), "gao3");
+        assert_eq!(gd("sap6"), "seb6");
+        assert_eq!(gd("hoeng1"), "hêng1");
+        assert_eq!(gd("ceoi4"), "cêu4");
+        assert_eq!(gd("zi1"), "ji1");
+        assert_eq!(gd("syu1"), "xu1");
+        assert_eq!(gd("jyut6"), "yud6");
+        assert_eq!(gd("lyun6"), "lün6");
+        assert_eq!(gd("se3"), "sé3");
+    }
+}
@@ -0,0 +1,112 @@
+use super::cantonese_pinyin::respell;
+use super::{Case, Romanizer};
+use crate::jyutping::Syllable;
+
+/// HK Government initials that differ from Jyutping; z and c are handled
+/// in `onset`.
+const INITIALS: [(&str, &str); 5] = [("p", "b"), ("t", "d"), ("k", "g"), ("kw", "gw"), ("y", "j")];
+
+/// HK Government finals that differ from Jyutping: no long/short vowel
+/// distinction, and the spellings of the older English-based schemes.
+const FINALS: [(&str, &str); 14] = [
+    ("a", "aa"),
+    ("ai", "aai"),
+    ("au", "aau"),
+    ("am", "aam"),
+    ("an", "aan"),
+    ("ang", "aang"),
+    ("ap", "aap"),
+    ("at", "aat"),
+    ("ak", "aak"),
+    ("o", "ou"),
+    ("eung", "oeng"),
+    ("euk", "oek"),
+    ("ui", "eoi"),
+    ("un", "eon"),
+];
+
+/// Rimes after which z and c are spelled "ts" (Tse, Tsang, Tsui, Tsim,
+/// Tsuen) rather than "ch" (Chan, Cheung, Chiu, Chow, Chun).
+const TS_RIMES: [&str; 7] = ["e", "eoi", "ai", "ang", "ak", "yun", "yut"];
+
+/// The spelling of the initial, which depends on the rime for z, c and s.
+fn onset(syllable: &Syllable) -> &'static str {
+    let rime = syllable.rime;
+    match syllable.onset {
+        "z" | "c" if TS_RIMES.contains(&rime) || rime.starts_with('i') && rime != "iu" => "ts",
+        "z" | "c" => "ch",
+        // Sha Tin, Shek O, Sheung Shui, Shing Mun, Shu
+        "s" if rime.starts_with("aa")
+            || rime.starts_with('e')
+            || rime.starts_with("oe")
+            || matches!(rime, "ing" | "ik" | "yu") =>
+        {
+            "sh"
+        }
+        onset => respell(&INITIALS, onset),
+    }
+}
+
+/// The spelling of the final: yu loses its y (Chu, Shu; j is already y,
+/// Yu), and yun, yut are "uen", "uet" (Tsuen Wan, Yuen Long). Bare i is
+/// "ze" after s (Sze) and "z" after z and c (Tsz).
+fn rime(syllable: &Syllable) -> &'static str {
+    match (syllable.onset, syllable.rime) {
+        ("s", "i") => "ze",
+        ("z" | "c", "i") => "z",
+        (_, "yu") => "u",
+        (_, "yun") => "uen",
+        (_, "yut") => "uet",
+        (_, rime) => respell(&FINALS, rime),
+    }
+}
+
+/// The Hong Kong Government romanization of names, as on identity cards,
+/// street signs and maps: can4 → Chan, zoeng1 → Cheung, saa1 tin4 → Sha
+/// Tin. Output only: it has no tones and merges many Jyutping finals, and
+/// it is a set of conventions rather than a system, so a few official
+/// spellings differ (將軍澳 Tseung Kwan O, not Cheung). Syllables are
+/// capitalized, as names are.
+pub struct HkGovernment;
+
+impl Romanizer for HkGovernment {
+    fn name(&self) -> &'static str {
+        "hk_government"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        Case::Title.apply(&format!("{}{}", onset(syllable), rime(syllable)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::romanize::romanize;
+
+    #[test]
+    fn test_render() {
+        let spell = |reading| romanize(reading, &HkGovernment).join(" ");
+        let cases = [
+            ("can4 daai6 man4", "Chan Tai Man"),
+            ("zoeng1 hok6 jau5", "Cheung Hok Yau"),
+            ("ze6 zeng6 fung1", "Tse Cheng Fung"),
+            ("zang1 ceoi4 zau1", "Tsang Tsui Chau"),
+            ("ng4 ziu6 wong4", "Ng Chiu Wong"),
+            ("saa1 tin4", "Sha Tin"),
+            ("soeng6 seoi2", "Sheung Shui"),
+            ("cyun4 waan1", "Tsuen Wan"),
+            ("zim1 saa1 zeoi2", "Tsim Sha Tsui"),
+            ("gwun1 tong4", "Kwun Tong"),
+            ("cing1 ji1", "Tsing Yi"),
+            ("jyun4 long5", "Yuen Long"),
+            ("gou1 si1 zi2", "Ko Sze Tsz"),
+            ("zyu1 gei1 lei5", "Chu Kei Lei"),
+        ];
+        for (reading, expected) in cases {
+            assert_eq!(spell(reading), expected, "{}", reading);
+        }
+        // capitals in the input still carry over
+        assert_eq!(spell("CAN4"), "CHAN");
+    }
+}
@@ -0,0 +1,133 @@
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Jyutping initial → IPA.
+fn onset(onset: &str) -> &'static str {
+    match onset {
+        "b" => "p",
+        "p" => "pʰ",
+        "m" => "m",
+        "f" => "f",
+        "d" => "t",
+        "t" => "tʰ",
+        "n" => "n",
+        "l" => "l",
+        "g" => "k",
+        "k" => "kʰ",
+        "ng" => "ŋ",
+        "h" => "h",
+        "gw" => "kʷ",
+        "kw" => "kʷʰ",
+        "w" => "w",
+        "z" => "ts",
+        "c" => "tsʰ",
+        "s" => "s",
+        "j" => "j",
+        _ => "",
+    }
+}
+
+/// Jyutping final → IPA, with unreleased stops marked.
+fn rime(rime: &str) -> &'static str {
+    match rime {
+        "aa" => "aː",
+        "aai" => "aːi",
+        "aau" => "aːu",
+        "aam" => "aːm",
+        "aan" => "aːn",
+        "aang" => "aːŋ",
+        "aap" => "aːp̚",
+        "aat" => "aːt̚",
+        "aak" => "aːk̚",
+        "a" => "ɐ",
+        "ai" => "ɐi",
+        "au" => "ɐu",
+        "am" => "ɐm",
+        "an" => "ɐn",
+        "ang" => "ɐŋ",
+        "ap" => "ɐp̚",
+        "at" => "ɐt̚",
+        "ak" => "ɐk̚",
+        "e" => "ɛː",
+        "ei" => "ei",
+        "eu" => "ɛːu",
+        "em" => "ɛːm",
+        "eng" => "ɛːŋ",
+        "ep" => "ɛːp̚",
+        "ek" => "ɛːk̚",
+        "i" => "iː",
+        "iu" => "iːu",
+        "im" => "iːm",
+        "in" => "iːn",
+        "ing" => "eŋ",
+        "ip" => "iːp̚",
+        "it" => "iːt̚",
+        "ik" => "ek̚",
+        "o" => "ɔː",
+        "oi" => "ɔːy",
+        "ou" => "ou",
+        "on" => "ɔːn",
+        "ong" => "ɔːŋ",
+        "ot" => "ɔːt̚",
+        "ok" => "ɔːk̚",
+        "u" => "uː",
+        "ui" => "uːy",
+        "un" => "uːn",
+        "ung" => "oŋ",
+        "ut" => "uːt̚",
+        "uk" => "ok̚",
+        "oe" => "œː",
+        "oeng" => "œːŋ",
+        "oet" => "œːt̚",
+        "oek" => "œːk̚",
+        "eoi" => "ɵy",
+        "eon" => "ɵn",
+        "eot" => "ɵt̚",
+        "yu" => "yː",
+        "yun" => "yːn",
+        "yut" => "yːt̚",
+        "m" => "m̩",
+        "ng" => "ŋ̍",
+        _ => "",
+    }
+}
+
+/// Tone letters for Jyutping tones 1–6 (55, 35, 33, 21, 13, 22).
+const TONES: [&str; 6] = ["˥", "˧˥", "˧", "˨˩", "˩˧", "˨"];
+
+/// Broad IPA transcription with Chao tone letters, e.g. gwong2 → kʷɔːŋ˧˥.
+/// Output only.
+pub struct Ipa;
+
+impl Romanizer for Ipa {
+    fn name(&self) -> &'static str {
+        "ipa"
+    }
+
+    fn cased(&self) -> bool {
+        false
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let tone = match syllable.tone {
+            t @ 1..=6 => TONES[t as usize - 1],
+            _ => "",
+        };
+        format!("{}{}{}", onset(syllable.onset), rime(syllable.rime), tone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let ipa = |s| Ipa.render(&Syllable::parse(s).unwrap());
+        assert_eq!(ipa("gwong2"), "kʷɔːŋ˧˥");
+        assert_eq!(ipa("sik6"), "sek̚˨");
+        assert_eq!(ipa("ceoi4"), "tsʰɵy˨˩");
+        assert_eq!(ipa("ng5"), "ŋ̍˩˧");
+        assert_eq!(ipa("jyut"), "jyːt̚");
+    }
+}
@@ -0,0 +1,81 @@
+use unicode_normalization::UnicodeNormalization;
+
+use super::Romanizer;
+use crate::jyutping::Syllable;
+
+/// Combining marks for tones 1–6: level tones get a bar (high above, mid
+/// none, low below), rising tones a rising stroke (high acute, low caron),
+/// the falling tone a grave.
+const MARKS: [Option<char>; 6] = [
+    Some('\u{0304}'), // 1 high level   ā
+    Some('\u{0301}'), // 2 high rising  á
+    None,             // 3 mid level    a
+    Some('\u{0300}'), // 4 low falling  à
+    Some('\u{030C}'), // 5 low rising   ǎ
+    Some('\u{0331}'), // 6 low level    a̱
+];
+
+/// Jyutping spelled with tone marks instead of digits, as in some modern
+/// textbooks: gwong2 dung1 waa2 → gwóng dūng wáa. The mark goes on the first
+/// vowel letter of the final, or on the nasal of syllabic m and ng.
+pub struct JyutpingDiacritics;
+
+impl Romanizer for JyutpingDiacritics {
+    fn name(&self) -> &'static str {
+        "jyutping_diacritics"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mark = match syllable.tone {
+            t @ 1..=6 => MARKS[t as usize - 1],
+            _ => None,
+        };
+        let mut out = String::from(syllable.onset);
+        let at = syllable.rime.find(['a', 'e', 'i', 'o', 'u']).unwrap_or(0);
+        for (i, ch) in syllable.rime.char_indices() {
+            out.push(ch);
+            if i == at {
+                out.extend(mark);
+            }
+        }
+        out.nfc().collect()
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        let decomposed: String = s.nfd().collect();
+        let tone = MARKS
+            .iter()
+            .position(|&m| m.is_some_and(|m| decomposed.contains(m)))
+            .map_or(3, |i| i as u8 + 1);
+        let base: String = decomposed
+            .chars()
+            .filter(|c| !MARKS.contains(&Some(*c)))
+            .collect();
+        Syllable::parse(&format!("{}{}", base, tone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let marked = |s| JyutpingDiacritics.render(&Syllable::parse(s).unwrap());
+        assert_eq!(marked("gwong2"), "gwóng");
+        assert_eq!(marked("dung1"), "dūng");
+        assert_eq!(marked("waa2"), "wáa");
+        assert_eq!(marked("si3"), "si");
+        assert_eq!(marked("ceoi4"), "cèoi");
+        assert_eq!(marked("ngo5"), "ngǒ");
+        assert_eq!(marked("jyut6"), "jyu̱t");
+        assert_eq!(marked("m4"), "m̀");
+        assert_eq!(marked("ng5"), "ňg");
+
+        for s in ["gwong2", "ceoi4", "jyut6", "si3", "ng5", "m4"] {
+            let syllable = Syllable::parse(s).unwrap();
+            let back = JyutpingDiacritics.parse(&JyutpingDiacritics.render(&syllable));
+            assert_eq!(back, Some(syllable));
+        }
+    }
+}
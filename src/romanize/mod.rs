@@ -0,0 +1,239 @@
+//! Romanization systems. Every system converts to and from the Jyutping
+//! `Syllable` model, so adding a scheme means one new `Romanizer` and one
+//! entry in `ROMANIZERS`.
+
+pub mod braille;
+pub mod cantonese_pinyin;
+pub mod chao;
+pub mod espeak;
+pub mod guangdong;
+pub mod hk_government;
+pub mod ipa;
+pub mod jyutping_diacritics;
+pub mod sidney_lau;
+pub mod xsampa;
+pub mod yale;
+
+use crate::jyutping::Syllable;
+use crate::utils::is_alpha_char;
+
+/// One romanization system.
+pub trait Romanizer: Sync {
+    /// Name used to select the system in options, e.g. "yale".
+    fn name(&self) -> &'static str;
+
+    /// Spell one syllable in this system.
+    fn render(&self, syllable: &Syllable) -> String;
+
+    /// Read one lowercase syllable of this system back into Jyutping. None
+    /// if it is not a syllable of the system, or the system is output-only.
+    fn parse(&self, _s: &str) -> Option<Syllable> {
+        None
+    }
+
+    /// Whether capitals in the input carry over to this system's spelling.
+    /// False for phonetic notations, where case is meaningful (X-SAMPA) or
+    /// undefined (IPA, braille).
+    fn cased(&self) -> bool {
+        true
+    }
+}
+
+/// Capitalization of a romanized syllable, kept through conversion so that
+/// proper nouns like "Gwong2 dung1" stay capitalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Case {
+    Lower,
+    /// First letter only: "Gwong2"
+    Title,
+    /// Every letter: "GWONG2"; a lone capital ("M4") counts as `Title`
+    Upper,
+}
+
+impl Case {
+    pub fn of(s: &str) -> Case {
+        let mut letters = s.chars().filter(|c| c.is_alphabetic());
+        match letters.next() {
+            Some(first) if first.is_uppercase() => {
+                let mut rest = letters.peekable();
+                if rest.peek().is_some() && rest.all(char::is_uppercase) {
+                    Case::Upper
+                } else {
+                    Case::Title
+                }
+            }
+            _ => Case::Lower,
+        }
+    }
+
+    /// Apply this case to a lowercase spelling.
+    pub fn apply(self, s: &str) -> String {
+        match self {
+            Case::Lower => s.to_string(),
+            Case::Upper => s.to_uppercase(),
+            Case::Title => {
+                let mut chars = s.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+        }
+    }
+}
+
+/// Jyutping itself, the canonical form.
+pub struct Jyutping;
+
+impl Romanizer for Jyutping {
+    fn name(&self) -> &'static str {
+        "jyutping"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        syllable.to_string()
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        Syllable::parse(s)
+    }
+}
+
+/// Every registered system.
+pub static ROMANIZERS: [&dyn Romanizer; 15] = [
+    &Jyutping,
+    &jyutping_diacritics::JyutpingDiacritics,
+    &yale::Yale,
+    &yale::YaleNumeric,
+    &yale::YalePlain,
+    &cantonese_pinyin::CantonesePinyin,
+    &sidney_lau::SidneyLau,
+    &guangdong::Guangdong,
+    &hk_government::HkGovernment,
+    &ipa::Ipa,
+    &xsampa::XSampa,
+    &espeak::Espeak,
+    &braille::Braille,
+    &chao::ChaoNumerals,
+    &chao::ChaoLetters,
+];
+
+/// The system called `name`, if registered.
+pub fn by_name(name: &str) -> Option<&'static dyn Romanizer> {
+    ROMANIZERS.iter().copied().find(|r| r.name() == name)
+}
+
+/// Spell a space-separated Jyutping reading in `system`, one entry per
+/// syllable. Syllables that are not valid Jyutping are kept as they are;
+/// capitalized syllables stay capitalized in cased systems.
+pub fn romanize(reading: &str, system: &dyn Romanizer) -> Vec<String> {
+    reading
+        .split_whitespace()
+        .map(|s| match Syllable::parse(&s.to_lowercase()) {
+            Some(syllable) if system.cased() => Case::of(s).apply(&system.render(&syllable)),
+            Some(syllable) => system.render(&syllable),
+            None => s.to_string(),
+        })
+        .collect()
+}
+
+/// Spell romanized running text in `system`: like `romanize`, but anything
+/// between syllables (spaces, punctuation, quotes) is copied as it is, e.g.
+/// "nei5 hou2, 'aa3 ming4'!" → "néih hóu, 'a mìhng'!" in Yale.
+pub fn romanize_text(text: &str, system: &dyn Romanizer) -> String {
+    let spelled: Result<String, std::convert::Infallible> =
+        map_syllables(text, |s| Ok(romanize(s, system).concat()));
+    spelled.unwrap_or_else(|never| match never {})
+}
+
+/// Rewrite each syllable-like run of `text` (letters, digits and combining
+/// marks) with `f`, copying the text between runs unchanged. Stops at the
+/// first error.
+pub fn map_syllables<E>(
+    text: &str,
+    mut f: impl FnMut(&str) -> Result<String, E>,
+) -> Result<String, E> {
+    let is_syllable_char = |c: char| is_alpha_char(c) || ('\u{0300}'..='\u{036F}').contains(&c);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !is_syllable_char(c))
+            .unwrap_or(rest.len());
+        if end > 0 {
+            out.push_str(&f(&rest[..end])?);
+            rest = &rest[end..];
+        } else {
+            let gap = rest.find(is_syllable_char).unwrap_or(rest.len());
+            out.push_str(&rest[..gap]);
+            rest = &rest[gap..];
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry() {
+        let names: Vec<_> = ROMANIZERS.iter().map(|r| r.name()).collect();
+        assert_eq!(
+            names,
+            [
+                "jyutping",
+                "jyutping_diacritics",
+                "yale",
+                "yale_numeric",
+                "yale_plain",
+                "cantonese_pinyin",
+                "sidney_lau",
+                "guangdong",
+                "hk_government",
+                "ipa",
+                "xsampa",
+                "espeak",
+                "braille",
+                "chao",
+                "chao_letters"
+            ]
+        );
+        assert!(by_name("wade_giles").is_none());
+
+        let reading = "zoeng1 ceoi4 jyu4 sik6";
+        let spell = |name| romanize(reading, by_name(name).unwrap()).join(" ");
+        assert_eq!(spell("jyutping"), reading);
+        assert_eq!(spell("jyutping_diacritics"), "zōeng cèoi jyù si̱k");
+        assert_eq!(spell("yale"), "jēung chèuih yùh sihk");
+        assert_eq!(spell("yale_numeric"), "jeung1 cheui4 yu4 sik6");
+        assert_eq!(spell("yale_plain"), "jeung cheuih yuh sihk");
+        assert_eq!(spell("cantonese_pinyin"), "dzoeng1 tsoey4 jy4 sik9");
+        assert_eq!(spell("sidney_lau"), "jeung1 chui4 yue4 sik6");
+        assert_eq!(spell("guangdong"), "zêng1 cêu4 yu4 xig6");
+        assert_eq!(spell("hk_government"), "Cheung Tsui Yu Shik");
+        assert_eq!(spell("chao"), "zoeng55 ceoi21 jyu21 sik2");
+
+        // capitals carry over, except into phonetic notations
+        let yale = romanize("Gwong2 dung1 WAA2", by_name("yale").unwrap());
+        assert_eq!(yale, ["Gwóng", "dūng", "WÁ"]);
+        assert_eq!(romanize("Si1", by_name("xsampa").unwrap()), ["si:_T"]);
+        assert_eq!(Case::of("M4"), Case::Title);
+
+        // punctuation and unknown words pass through running text
+        assert_eq!(
+            romanize_text("nei5 hou2, 'aa3 ming4'! OK?", by_name("yale").unwrap()),
+            "néih hóu, 'a mìhng'! OK?"
+        );
+
+        // every system with a parser reads its own output back
+        for system in ROMANIZERS {
+            for s in reading.split_whitespace() {
+                let syllable = Syllable::parse(s).unwrap();
+                if let Some(back) = system.parse(&system.render(&syllable)) {
+                    assert_eq!(back, syllable, "{}", system.name());
+                }
+            }
+        }
+    }
+}
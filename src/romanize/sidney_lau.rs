@@ -0,0 +1,90 @@
+use super::Romanizer;
+use super::cantonese_pinyin::respell;
+use crate::jyutping::Syllable;
+
+/// Sidney Lau initials that differ from Jyutping.
+const INITIALS: [(&str, &str); 3] = [("ch", "c"), ("j", "z"), ("y", "j")];
+
+/// Sidney Lau finals that differ from Jyutping.
+const FINALS: [(&str, &str); 18] = [
+    ("a", "aa"),
+    ("ee", "i"),
+    ("ze", "i"),
+    ("oh", "o"),
+    ("o", "ou"),
+    ("oo", "u"),
+    ("ooi", "ui"),
+    ("oon", "un"),
+    ("oot", "ut"),
+    ("euh", "oe"),
+    ("eung", "oeng"),
+    ("euk", "oek"),
+    ("ui", "eoi"),
+    ("un", "eon"),
+    ("ut", "eot"),
+    ("ue", "yu"),
+    ("uen", "yun"),
+    ("uet", "yut"),
+];
+
+/// Parse one lowercase Sidney Lau syllable, e.g. "sze1" → si1, "hui3" →
+/// heoi3. A `°` (high falling tone 1) counts as tone 1.
+pub fn parse(s: &str) -> Option<Syllable> {
+    let s = s.trim_end_matches('°');
+    let (body, tone) = match s.as_bytes().last() {
+        Some(&d @ b'1'..=b'6') => (&s[..s.len() - 1], d - b'0'),
+        _ => (s, 0),
+    };
+    Syllable::respell(body, tone, &INITIALS, &FINALS)
+}
+
+/// Sidney Lau romanization, e.g. si1 → sze1, heoi3 → hui3.
+pub struct SidneyLau;
+
+impl Romanizer for SidneyLau {
+    fn name(&self) -> &'static str {
+        "sidney_lau"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let onset = respell(&INITIALS, syllable.onset);
+        // bare i is "ze" after s (sze) and "ee" elsewhere (yee, jee)
+        let rime = match (syllable.onset, syllable.rime) {
+            ("s", "i") => "ze",
+            (_, rime) => respell(&FINALS, rime),
+        };
+        match syllable.tone {
+            0 => format!("{}{}", onset, rime),
+            t => format!("{}{}{}", onset, rime, t),
+        }
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        parse(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jyutping::to_jyutping;
+
+    #[test]
+    fn test_parse() {
+        let convert = |s| to_jyutping(s, parse);
+        assert_eq!(
+            convert("ngoh5 hai6 hok6 saang1").as_deref(),
+            Ok("ngo5 hai6 hok6 saang1")
+        );
+        assert_eq!(
+            convert("sze1 foo1 hui3 sue1").as_deref(),
+            Ok("si1 fu1 heoi3 syu1")
+        );
+        assert_eq!(
+            convert("yue4 yuet6 cheung4 jee2").as_deref(),
+            Ok("jyu4 jyut6 coeng4 zi2")
+        );
+        assert_eq!(convert("ho2 wooi6 sun3").as_deref(), Ok("hou2 wui6 seon3"));
+        assert!(convert("xyz1").is_err());
+    }
+}
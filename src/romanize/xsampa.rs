@@ -0,0 +1,77 @@
+use super::Romanizer;
+use super::ipa::Ipa;
+use crate::jyutping::Syllable;
+
+/// X-SAMPA for one IPA symbol, or None if the symbol is ASCII and spelled
+/// the same.
+fn xsampa(ch: char) -> Option<&'static str> {
+    Some(match ch {
+        'ʰ' => "_h",
+        'ʷ' => "_w",
+        'ː' => ":",
+        'ɐ' => "6",
+        'ɛ' => "E",
+        'ɔ' => "O",
+        'ŋ' => "N",
+        'œ' => "9",
+        'ɵ' => "8",
+        '\u{031A}' => "_}",             // unreleased stop
+        '\u{0329}' | '\u{030D}' => "=", // syllabic nasal
+        // Chao tone letters
+        '˥' => "_T",
+        '˦' => "_H",
+        '˧' => "_M",
+        '˨' => "_L",
+        '˩' => "_B",
+        _ => return None,
+    })
+}
+
+/// ASCII X-SAMPA transcription for speech tools, a direct respelling of the
+/// IPA output: gwong2 → k_wO:N_M_T. Output only.
+pub struct XSampa;
+
+impl Romanizer for XSampa {
+    fn name(&self) -> &'static str {
+        "xsampa"
+    }
+
+    fn cased(&self) -> bool {
+        false
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        let mut out = String::new();
+        for ch in Ipa.render(syllable).chars() {
+            match xsampa(ch) {
+                Some(s) => out.push_str(s),
+                None => out.push(ch),
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let xs = |s| XSampa.render(&Syllable::parse(s).unwrap());
+        assert_eq!(xs("gwong2"), "k_wO:N_M_T");
+        assert_eq!(xs("ceoi4"), "ts_h8y_L_B");
+        assert_eq!(xs("sap6"), "s6p_}_L");
+        assert_eq!(xs("hoeng1"), "h9:N_T");
+        assert_eq!(xs("ng5"), "N=_B_M");
+
+        // every output is plain ASCII
+        for rime in crate::jyutping::FINALS {
+            for onset in ["", "kw", "c"] {
+                if let Some(s) = Syllable::parse(&format!("{}{}2", onset, rime)) {
+                    assert!(XSampa.render(&s).is_ascii(), "{}", s);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,517 @@
+use unicode_normalization::UnicodeNormalization;
+
+use super::{Case, Romanizer, map_syllables};
+use crate::jyutping::Syllable;
+
+/// How Yale marks tones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    /// Tone numbers: "keui5"
+    Numeric,
+    /// Diacritics and the low-register h: "kéuih"
+    Diacritics,
+    /// Diacritics, with tone 1 on open and nasal syllables written as the
+    /// high falling variant (grave, "sì") instead of high level ("sī"), as
+    /// in classic Yale textbooks. Checked syllables stay level ("sīk").
+    HighFalling,
+    /// No tone marks at all, for filenames, URLs and indexes: "keuih", or
+    /// "keui" without the low-register h.
+    Plain { register: bool },
+}
+
+/// Convert a Jyutping string (may contain multiple syllables separated by spaces)
+/// to Yale romanization with tone numbers (e.g. "keoi5" → "keui5")
+/// or with Yale diacritics (e.g. "keoi5" → "kéuih").
+/// Punctuation and words that are not Jyutping are copied as they are:
+/// "nei5 hou2, OK?" → "néih hóu, OK?"
+pub fn jyutping_to_yale(jyutping: &str, diacritics: bool) -> Option<String> {
+    let style = if diacritics {
+        Style::Diacritics
+    } else {
+        Style::Numeric
+    };
+    if jyutping.trim().is_empty() {
+        return None;
+    }
+
+    let converted: Result<String, std::convert::Infallible> = map_syllables(jyutping, |s| {
+        Ok(convert_syllable(s, style).unwrap_or_else(|| s.to_string()))
+    });
+    let converted = converted.unwrap_or_else(|never| match never {});
+
+    // normalize to NFC so combining diacritics collapse to precomposed chars
+    // e.g. "i" + U+0304 → "ī" as a single codepoint
+    Some(converted.nfc().collect())
+}
+
+/// Like `jyutping_to_yale`, but fails on the first word that is not
+/// Jyutping instead of copying it; empty input gives "".
+pub fn try_jyutping_to_yale(jyutping: &str, diacritics: bool) -> Result<String, String> {
+    let style = if diacritics {
+        Style::Diacritics
+    } else {
+        Style::Numeric
+    };
+    let converted = map_syllables(jyutping, |s| {
+        convert_syllable(s, style).ok_or_else(|| format!("not Jyutping: {:?}", s))
+    })?;
+    Ok(converted.nfc().collect())
+}
+
+fn convert_syllable(syllable: &str, style: Style) -> Option<String> {
+    // convert lowercase, then restore capitals ("Gwong2" → "Gwóng")
+    let case = Case::of(syllable);
+    let syllable = syllable.to_lowercase();
+
+    // split tone number off the end
+    let (body, tone) = split_tone(&syllable)?;
+
+    // convert initial
+    let (initial, rest) = convert_initial(body);
+
+    // convert final (vowel + coda)
+    let mut final_part = convert_final(rest);
+
+    // bare "aa" (no coda) → "a" in Yale
+    if final_part == "aa" {
+        final_part = "a".to_string();
+    }
+
+    let yale = match style {
+        Style::Numeric => format!("{}{}{}", initial, final_part, tone),
+        style => apply_diacritic(initial, &final_part, tone, style),
+    };
+    Some(case.apply(&yale))
+}
+
+/// Returns (body_without_tone, tone_number)
+fn split_tone(s: &str) -> Option<(&str, u8)> {
+    let last = s.chars().last()?;
+    if last.is_ascii_digit() {
+        let tone = last.to_digit(10)? as u8;
+        Some((&s[..s.len() - 1], tone))
+    } else {
+        None
+    }
+}
+
+/// Returns (yale_initial, remaining_final)
+fn convert_initial(body: &str) -> (&str, &str) {
+    // order matters — check longer initials first
+    // jyu, jyun, jyut are written yu, yun, yut (not yyu)
+    if let Some(rest) = body.strip_prefix("jy") { return ("y", rest); }
+    if let Some(rest) = body.strip_prefix("gw") { return ("gw", rest); }
+    if let Some(rest) = body.strip_prefix("kw") { return ("kw", rest); }
+    if let Some(rest) = body.strip_prefix("ng") { return ("ng", rest); }
+    if let Some(rest) = body.strip_prefix('z')  { return ("j",  rest); }
+    if let Some(rest) = body.strip_prefix('c')  { return ("ch", rest); }
+    if let Some(rest) = body.strip_prefix('j')  { return ("y",  rest); }
+    // initials identical in both systems: b p m f d t n l g k h s w
+    for i in ["b","p","m","f","d","t","n","l","g","k","h","s","w"] {
+        if let Some(rest) = body.strip_prefix(i) {
+            return (i, rest);
+        }
+    }
+    ("", body)  // no initial (vowel-initial syllable)
+}
+
+/// Convert Jyutping final to Yale final
+fn convert_final(fin: &str) -> String {
+    fin
+        .replace("eoi",  "eui")   // eoi  → eui
+        .replace("oeng", "eung")  // oeng → eung
+        .replace("oek",  "euk")   // oek  → euk
+        .replace("oe",   "eu")    // oe   → eu
+        .replace("eo",   "eu")    // eo   → eu
+        // all aa finals (aam, aan, aang, aap, aat, aak, aai, aau) stay as-is
+        // bare "aa" is handled separately in convert_syllable
+}
+
+/// Split final into (nucleus, coda)
+/// coda = trailing consonant: ng, p, t, k, m, n
+/// trailing glides i, u are part of the nucleus
+fn split_nucleus_coda(fin: &str) -> (&str, &str) {
+    for coda in ["ng", "p", "t", "k", "m", "n"] {
+        if let Some(nucleus) = fin.strip_suffix(coda) {
+            return (nucleus, coda);
+        }
+    }
+    (fin, "")
+}
+
+/// Apply Yale diacritic tones
+/// High register (1-3): diacritic on first vowel, no h
+/// Low register (4-6):  diacritic on first vowel + h after nucleus, before coda
+/// Tone 1: macron ā   Tone 4: grave + h àh
+/// Tone 2: acute á    Tone 5: acute + h áh
+/// Tone 3: no mark    Tone 6: no mark + h
+/// `Style::HighFalling`: unchecked tone 1 takes a grave without h: sì
+/// `Style::Plain`: no marks, h only if `register`
+fn apply_diacritic(initial: &str, fin: &str, tone: u8, style: Style) -> String {
+    let vowels = ['a', 'e', 'i', 'o', 'u'];
+    let low_register = match style {
+        Style::Plain { register } => register && tone >= 4,
+        _ => tone >= 4,
+    };
+    let checked = fin.ends_with(['p', 't', 'k']);
+
+    let diacritic: Option<char> = match tone {
+        _ if matches!(style, Style::Plain { .. }) => None,
+        1 if style == Style::HighFalling && !checked => Some('\u{0300}'),  // grave  à (high falling)
+        1 => Some('\u{0304}'),  // macron  ā
+        2 => Some('\u{0301}'),  // acute   á
+        3 => None,              // no mark — mid level tone
+        4 => Some('\u{0300}'),  // grave   à (low falling)
+        5 => Some('\u{0301}'),  // acute   á (low rising)
+        6 => None,              // no mark (low level)
+        _ => None,
+    };
+
+    let (nucleus, coda) = split_nucleus_coda(fin);
+
+    // place diacritic on first vowel of nucleus
+    let mut result = String::from(initial);
+    let mut marked = false;
+    for ch in nucleus.chars() {
+        result.push(ch);
+        if !marked && vowels.contains(&ch) {
+            if let Some(d) = diacritic {
+                result.push(d);
+            }
+            marked = true;
+        }
+    }
+
+    // syllabic nasals (m4 → m̀h, ng5 → ńgh) carry the mark on the nasal
+    if !marked
+        && fin.is_empty()
+        && let (Some(d), Some(first)) = (diacritic, initial.chars().next())
+    {
+        result = format!("{}{}{}", first, d, &initial[first.len_utf8()..]);
+    }
+
+    // h goes after entire nucleus, before coda
+    if low_register {
+        result.push('h');
+    }
+
+    result.push_str(coda);
+    result
+}
+
+/// Stands in `jyutping_to_yale_vec` output for a syllable that is not
+/// Jyutping, so that the output stays aligned with the reading.
+pub const UNCONVERTED: &str = "?";
+
+/// Returns one Yale syllable per Jyutping syllable, matching pycantonese output.
+/// e.g. "nei5 hou2 aa3" → ["néih", "hóu", "a"]. Syllables that do not
+/// convert give `UNCONVERTED`, never a shorter vector.
+pub fn jyutping_to_yale_vec(jyutping: &str, style: Style) -> Option<Vec<String>> {
+    let syllables: Vec<&str> = jyutping.split_whitespace().collect();
+    if syllables.is_empty() {
+        return None;
+    }
+
+    let converted = syllables
+        .iter()
+        .map(|s| {
+            convert_syllable(s, style)
+                .map_or_else(|| UNCONVERTED.to_string(), |s| s.nfc().collect())
+        })
+        .collect();
+    Some(converted)
+}
+
+/// Yale with tone diacritics and the low-register h, e.g. hok6 → hohk.
+pub struct Yale;
+
+/// Yale with tone numbers, e.g. keoi5 → keui5.
+pub struct YaleNumeric;
+
+/// `Yale` with the high falling tone 1 (`Style::HighFalling`), selected by
+/// the `high_falling` option rather than by name.
+pub struct YaleHighFalling;
+
+/// Yale without tone marks, e.g. keoi5 → keuih. Output only: the tone is lost.
+pub struct YalePlain;
+
+/// `YalePlain` without the low-register h (keoi5 → keui), selected by the
+/// `register_h` option rather than by name.
+pub struct YalePlainNoH;
+
+impl Romanizer for Yale {
+    fn name(&self) -> &'static str {
+        "yale"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        render(syllable, Style::Diacritics)
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        parse(s)
+    }
+}
+
+impl Romanizer for YaleNumeric {
+    fn name(&self) -> &'static str {
+        "yale_numeric"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        render(syllable, Style::Numeric)
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        parse(s)
+    }
+}
+
+impl Romanizer for YaleHighFalling {
+    fn name(&self) -> &'static str {
+        "yale"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        render(syllable, Style::HighFalling)
+    }
+
+    fn parse(&self, s: &str) -> Option<Syllable> {
+        parse(s)
+    }
+}
+
+impl Romanizer for YalePlain {
+    fn name(&self) -> &'static str {
+        "yale_plain"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        render(syllable, Style::Plain { register: true })
+    }
+}
+
+impl Romanizer for YalePlainNoH {
+    fn name(&self) -> &'static str {
+        "yale_plain"
+    }
+
+    fn render(&self, syllable: &Syllable) -> String {
+        render(syllable, Style::Plain { register: false })
+    }
+}
+
+fn render(syllable: &Syllable, style: Style) -> String {
+    let jyutping = syllable.to_string();
+    match convert_syllable(&jyutping, style) {
+        Some(yale) => yale.nfc().collect(),
+        // no tone digit to convert
+        None => jyutping,
+    }
+}
+
+/// Yale initials that differ from Jyutping.
+const INITIALS: [(&str, &str); 3] = [("ch", "c"), ("j", "z"), ("y", "j")];
+
+/// Yale finals that differ from Jyutping.
+const FINALS: [(&str, &str); 7] = [
+    ("a", "aa"),
+    ("eui", "eoi"),
+    ("eun", "eon"),
+    ("eut", "eot"),
+    ("eung", "oeng"),
+    ("euk", "oek"),
+    ("eu", "oe"),
+];
+
+/// Parse one lowercase Yale syllable back into Jyutping, written either with
+/// tone numbers ("hok6") or with diacritics and the low-register h ("hohk",
+/// "sāang", "yùhn").
+pub fn parse(s: &str) -> Option<Syllable> {
+    let decomposed: String = s.nfd().collect();
+    let mark = decomposed
+        .chars()
+        .find(|c| matches!(c, '\u{0300}' | '\u{0301}' | '\u{0304}'));
+    let base: String = decomposed
+        .chars()
+        .filter(|c| !('\u{0300}'..='\u{036F}').contains(c))
+        .collect();
+
+    let (body, tone) = match base.as_bytes().last() {
+        Some(&d @ b'1'..=b'6') => (base[..base.len() - 1].to_string(), d - b'0'),
+        _ => {
+            let (body, low) = strip_register_h(&base);
+            let tone = match (mark, low) {
+                (Some('\u{0304}'), _) => 1,
+                // grave without h is the high falling variant of tone 1
+                (Some('\u{0300}'), false) => 1,
+                (Some('\u{0300}'), true) => 4,
+                (Some(_), false) => 2,
+                (Some(_), true) => 5,
+                (None, false) => 3,
+                (None, true) => 6,
+            };
+            (body, tone)
+        }
+    };
+
+    // yu, yun, yut stand for Jyutping jyu, jyun, jyut
+    if let Some(rest @ ("u" | "un" | "ut")) = body.strip_prefix('y') {
+        return Syllable::parse(&format!("jy{}{}", rest, tone));
+    }
+    Syllable::respell(&body, tone, &INITIALS, &FINALS)
+}
+
+/// Remove the h that marks Yale's low register: the one right after the
+/// vowel nucleus ("haih", "hohk"), or the final h of a syllabic nasal
+/// ("ńgh", "m̀h"). Returns the rest and whether an h was removed.
+fn strip_register_h(base: &str) -> (String, bool) {
+    let vowels = ['a', 'e', 'i', 'o', 'u'];
+    let chars: Vec<char> = base.chars().collect();
+    let after_vowel = (1..chars.len()).find(|&i| chars[i] == 'h' && vowels.contains(&chars[i - 1]));
+    let nasal =
+        !chars.iter().any(|c| vowels.contains(c)) && chars.len() > 1 && chars.last() == Some(&'h');
+    let index = match (after_vowel, nasal) {
+        (Some(i), _) => i,
+        (None, true) => chars.len() - 1,
+        (None, false) => return (base.to_string(), false),
+    };
+    let mut chars = chars;
+    chars.remove(index);
+    (chars.into_iter().collect(), true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_yale_numeric() {
+        // initials
+        assert_eq!(jyutping_to_yale("zi1",  false), Some("ji1".into()));
+        assert_eq!(jyutping_to_yale("ci1",  false), Some("chi1".into()));
+        assert_eq!(jyutping_to_yale("ji1",  false), Some("yi1".into()));
+        // finals
+        assert_eq!(jyutping_to_yale("keoi5", false), Some("keui5".into()));
+        assert_eq!(jyutping_to_yale("heoi3", false), Some("heui3".into()));
+        // bare aa → a
+        assert_eq!(jyutping_to_yale("aa3",  false), Some("a3".into()));
+        // aa finals stay intact
+        assert_eq!(jyutping_to_yale("saan1", false), Some("saan1".into()));
+        assert_eq!(jyutping_to_yale("baak3", false), Some("baak3".into()));
+        assert_eq!(jyutping_to_yale("haam4", false), Some("haam4".into()));
+        // jyu → yu
+        assert_eq!(
+            jyutping_to_yale("jyut6 jyu4", false),
+            Some("yut6 yu4".into())
+        );
+        // multi-syllable
+        assert_eq!(
+            jyutping_to_yale("gwong2 dung1 waa2", false),
+            Some("gwong2 dung1 wa2".into())
+        );
+    }
+
+    #[test]
+    fn test_yale_diacritics() {
+        // tone 3: no mark
+        assert_eq!(jyutping_to_yale("si3",   true), Some("si".into()));
+        assert_eq!(jyutping_to_yale("heoi3", true), Some("heui".into()));
+
+        // tone 1: macron
+        assert_eq!(jyutping_to_yale("si1",   true), Some("sī".into()));
+        assert_eq!(jyutping_to_yale("jat1",  true), Some("yāt".into()));
+
+        // tone 2: acute
+        assert_eq!(jyutping_to_yale("hou2",  true), Some("hóu".into()));
+
+        // tone 4: grave + h
+        assert_eq!(jyutping_to_yale("haam4", true), Some("hàahm".into()));
+
+        // tone 5: acute + h after nucleus
+        assert_eq!(jyutping_to_yale("ngo5",  true), Some("ngóh".into()));
+
+        // tone 6: no mark + h after nucleus
+        assert_eq!(jyutping_to_yale("hai6",  true), Some("haih".into()));
+        assert_eq!(jyutping_to_yale("hok6",  true), Some("hohk".into()));
+        assert_eq!(jyutping_to_yale("sap6",  true), Some("sahp".into()));
+
+        // syllabic nasals
+        assert_eq!(jyutping_to_yale("m4",    true), Some("m̀h".into()));
+
+        // punctuation and other words pass through
+        assert_eq!(
+            jyutping_to_yale("nei5 hou2, \"OK\"?", true),
+            Some("néih hóu, \"OK\"?".into())
+        );
+
+        // capitals are kept
+        assert_eq!(
+            jyutping_to_yale("Gwong2 dung1", true),
+            Some("Gwóng dūng".into())
+        );
+        assert_eq!(jyutping_to_yale("HOENG1", false), Some("HEUNG1".into()));
+
+        // high falling tone 1, except on checked syllables
+        let falling = |jp| jyutping_to_yale_vec(jp, Style::HighFalling);
+        assert_eq!(
+            falling("si1 sik1 saan1"),
+            Some(vec!["sì".into(), "sīk".into(), "sàan".into()])
+        );
+
+        // one entry per syllable, even for syllables that do not convert
+        let vec = |jp| jyutping_to_yale_vec(jp, Style::Diacritics);
+        assert_eq!(
+            vec("nei5 OK hou2"),
+            Some(vec!["néih".into(), "?".into(), "hóu".into()])
+        );
+        assert_eq!(vec("OK"), Some(vec![UNCONVERTED.into()]));
+        assert_eq!(vec(" "), None);
+
+        // plain, with and without the low-register h
+        let plain =
+            |jp, register| jyutping_to_yale_vec(jp, Style::Plain { register }).map(|v| v.join(" "));
+        assert_eq!(
+            plain("hoeng1 gong2 jyu4 ng5", true),
+            Some("heung gong yuh ngh".into())
+        );
+        assert_eq!(
+            plain("hoeng1 gong2 jyu4 ng5", false),
+            Some("heung gong yu ng".into())
+        );
+        assert_eq!(jyutping_to_yale("ng5", true), Some("ńgh".into()));
+
+        // aa finals with diacritics
+        assert_eq!(jyutping_to_yale("saan1", true), Some("sāan".into()));
+        assert_eq!(jyutping_to_yale("baak3", true), Some("baak".into()));
+    }
+
+    #[test]
+    fn test_parse() {
+        use crate::jyutping::to_jyutping;
+        let convert = |s: &str| to_jyutping(s, parse);
+        assert_eq!(convert("gwóng dūng wá").as_deref(), Ok("gwong2 dung1 waa2"));
+        assert_eq!(
+            convert("hohk sāang haih yùhn").as_deref(),
+            Ok("hok6 saang1 hai6 jyun4")
+        );
+        assert_eq!(convert("kéuih heui ńgh").as_deref(), Ok("keoi5 heoi3 ng5"));
+        assert_eq!(convert("keui5 yu4 ji1").as_deref(), Ok("keoi5 jyu4 zi1"));
+        assert_eq!(convert("sì sàam").as_deref(), Ok("si1 saam1"));
+        assert_eq!(convert("Hēung Góng").as_deref(), Ok("Hoeng1 Gong2"));
+        assert_eq!(convert("Néih hóu, a!").as_deref(), Ok("Nei5 hou2, aa3!"));
+        assert!(convert("xyz").is_err());
+
+        // round trip through Yale
+        for jp in [
+            "nei5 hou2 aa3",
+            "hoeng1 gong2 jan4",
+            "jyut6 jyu5",
+            "m4 goi1",
+            "ceot1 seon3",
+        ] {
+            let yale = jyutping_to_yale(jp, true).unwrap();
+            assert_eq!(convert(&yale).as_deref(), Ok(jp), "{}", yale);
+        }
+    }
+}
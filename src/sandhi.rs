@@ -0,0 +1,179 @@
+use crate::jyutping::{tone, tones};
+use crate::options::SandhiRule;
+use crate::token::Token;
+use crate::utils::is_cjk;
+
+/// Kinship terms read with changed tones when doubled: 爸爸 baa4 baa1,
+/// 妹妹 mui4 mui2.
+const KINSHIP: &str = "爸媽哥姐弟妹爺嫲婆公伯叔姑姨舅嬸";
+
+/// The suffix of reduplicated adjectives: 紅紅哋, 慢慢地.
+const DEI: &str = "哋地";
+
+/// One syllable of the text: its token, its place in the token's reading,
+/// its character and its tone as read.
+struct Unit {
+    token: usize,
+    index: usize,
+    ch: char,
+    tone: u8,
+    /// Ends in -p, -t or -k: checked syllables keep their tone.
+    checked: bool,
+}
+
+/// The syllables of `tokens` in runs that a rule may span: tokens of CJK
+/// characters read one syllable each. Anything else (punctuation, Latin
+/// words, tokens without a reading) ends a run.
+fn runs(tokens: &[Token]) -> Vec<Vec<Unit>> {
+    let mut runs = vec![Vec::new()];
+    for (i, t) in tokens.iter().enumerate() {
+        let syllables: Vec<&str> = t
+            .reading
+            .as_deref()
+            .map(|r| r.split_whitespace().collect())
+            .unwrap_or_default();
+        let chars: Vec<char> = t.word.chars().collect();
+        if chars.is_empty() || chars.len() != syllables.len() || !chars.iter().all(|&c| is_cjk(c)) {
+            runs.push(Vec::new());
+            continue;
+        }
+        let run = runs.last_mut().expect("runs is never empty");
+        for (index, (&ch, syllable)) in chars.iter().zip(syllables).enumerate() {
+            let initial_and_final = syllable.trim_end_matches(|c: char| c.is_ascii_digit());
+            run.push(Unit {
+                token: i,
+                index,
+                ch,
+                tone: tone(syllable),
+                checked: initial_and_final.ends_with(['p', 't', 'k']),
+            });
+        }
+    }
+    runs
+}
+
+/// Apply the tone sandhi `rules` (the `sandhi` option): tokens a rule
+/// changes get the tones of their reading as `underlying_tones` and the
+/// tones as spoken as `surface_tones`. The reading itself is left as the
+/// dictionary gives it. Readings the dictionary already gives with the
+/// changed tones (婆婆 po4 po2) need no change and get neither.
+///
+/// | Rule            | Pattern                        | e.g.                     |
+/// | --------------- | ------------------------------ | ------------------------ |
+/// | `kinship`       | doubled kinship term: 21 + 55  | 哥哥 go1 go1 → 4 1       |
+/// |                 | or 21 + 35                     | 弟弟 dai6 dai6 → 4 2     |
+/// | `familiar`      | 阿 + name: low tone → 35       | 阿陳 aa3 can4 → 3 2      |
+/// | `reduplication` | AA哋 adjective: A and 哋 → 35  | 慢慢哋 maan6 ×2 dei6 → 6 2 2 |
+///
+/// The second syllable of a doubled term keeps a high level tone (tone 1,
+/// 55) and otherwise takes the changed rising tone (tone 2, 35). Checked
+/// syllables (-p, -t, -k) keep their tones.
+pub fn apply(tokens: &mut [Token], rules: &[SandhiRule]) {
+    let mut surface: Vec<Option<Vec<u8>>> = vec![None; tokens.len()];
+    let mut change = |unit: &Unit, to: u8| {
+        if unit.checked || unit.tone == 0 || unit.tone == to {
+            return;
+        }
+        let reading = tokens[unit.token].reading.as_deref().unwrap_or_default();
+        surface[unit.token].get_or_insert_with(|| tones(reading))[unit.index] = to;
+    };
+    let rising_unless_high = |unit: &Unit| if unit.tone == 1 { 1 } else { 2 };
+    for run in runs(tokens) {
+        for (i, a) in run.iter().enumerate() {
+            let Some(b) = run.get(i + 1) else {
+                break;
+            };
+            let after = run.get(i + 2);
+            let doubled = b.ch == a.ch
+                && (i == 0 || run[i - 1].ch != a.ch)
+                && after.is_none_or(|c| c.ch != a.ch);
+            for rule in rules {
+                match rule {
+                    SandhiRule::Kinship if doubled && KINSHIP.contains(a.ch) => {
+                        change(a, 4);
+                        change(b, rising_unless_high(b));
+                    }
+                    // 阿 and a name of one character, as one token or two
+                    SandhiRule::Familiar
+                        if a.ch == '阿'
+                            && a.index == 0
+                            && after.is_none_or(|c| c.token != b.token)
+                            && !KINSHIP.contains(b.ch)
+                            && (4..=6).contains(&b.tone) =>
+                    {
+                        change(b, 2);
+                    }
+                    SandhiRule::Reduplication if doubled => {
+                        if let Some(c) = after.filter(|c| DEI.contains(c.ch)) {
+                            change(b, rising_unless_high(b));
+                            change(c, 2);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    for (t, surface) in tokens.iter_mut().zip(surface) {
+        if let Some(surface) = surface {
+            t.underlying_tones = t.reading.as_deref().map(tones);
+            t.surface_tones = Some(surface);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn surface(words: &[(&str, &str)], rules: &[SandhiRule]) -> Vec<Option<Vec<u8>>> {
+        let mut tokens: Vec<Token> = words
+            .iter()
+            .map(|&(word, reading)| Token {
+                word: word.into(),
+                reading: (!reading.is_empty()).then(|| reading.into()),
+                ..Default::default()
+            })
+            .collect();
+        apply(&mut tokens, rules);
+        tokens.into_iter().map(|t| t.surface_tones).collect()
+    }
+
+    #[test]
+    fn test_apply() {
+        use SandhiRule::*;
+        let all = [Kinship, Familiar, Reduplication];
+        assert_eq!(surface(&[("哥哥", "go1 go1")], &all), [Some(vec![4, 1])]);
+        assert_eq!(surface(&[("弟弟", "dai6 dai6")], &all), [Some(vec![4, 2])]);
+        // already read with the changed tones
+        assert_eq!(surface(&[("婆婆", "po4 po2")], &all), [None]);
+        assert_eq!(
+            surface(&[("阿", "aa3"), ("陳", "can4"), ("話", "waa6")], &all),
+            [None, Some(vec![2]), None]
+        );
+        // not a name: 陳 starts a longer word
+        assert_eq!(
+            surface(&[("阿", "aa3"), ("陳生", "can4 saang1")], &all),
+            [None, None]
+        );
+        assert_eq!(
+            surface(&[("慢慢", "maan6 maan6"), ("哋", "dei6")], &all),
+            [Some(vec![6, 2]), Some(vec![2])]
+        );
+        // the rules are off unless listed
+        assert_eq!(surface(&[("哥哥", "go1 go1")], &[Familiar]), [None]);
+        // punctuation ends a run; checked syllables keep their tones
+        assert_eq!(
+            surface(
+                &[
+                    ("阿", "aa3"),
+                    ("，", ""),
+                    ("白白", "baak6 baak6"),
+                    ("地", "dei6")
+                ],
+                &all
+            ),
+            [None, None, None, Some(vec![2])]
+        );
+    }
+}
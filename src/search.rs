@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+
+use crate::jyutping::{INITIALS, syllabify};
+use crate::trie::{Trie, TrieNode};
+
+/// Options for `search`, passed as a JSON object; every field is optional.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SearchOptions {
+    /// Also ignore aspiration: p/b, t/d, k/g, kw/gw and c/z match.
+    pub aspiration: bool,
+    /// Also ignore the eo/oe distinction (Yale eu): ceon matches coen.
+    pub rounding: bool,
+    /// Most results returned.
+    pub limit: usize,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        SearchOptions {
+            aspiration: false,
+            rounding: false,
+            limit: 20,
+        }
+    }
+}
+
+/// One dictionary word found by `search`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Hit {
+    pub word: String,
+    /// The reading that matched.
+    pub jyutping: String,
+    /// Corpus frequency (freq.txt), 0 if unknown.
+    pub freq: i64,
+}
+
+/// Search key of a reading or query: its letters without tones or spaces,
+/// so "nei5 hou2" and "neihou" agree, with the distinctions `options`
+/// ignores folded away.
+fn key(text: &str, options: &SearchOptions) -> String {
+    let mut key = String::new();
+    for word in text.split_whitespace() {
+        let mut word: String = word.chars().filter(|c| !c.is_ascii_digit()).collect();
+        if options.rounding {
+            word = word.replace("eo", "oe");
+        }
+        if !options.aspiration {
+            key.push_str(&word);
+            continue;
+        }
+        for syllable in syllabify(&word).unwrap_or_else(|| vec![word.as_str()]) {
+            let onset = INITIALS
+                .iter()
+                .find(|&&i| syllable.starts_with(i))
+                .map_or("", |i| i);
+            key.push_str(match onset {
+                "p" => "b",
+                "t" => "d",
+                "k" => "g",
+                "kw" => "gw",
+                "c" => "z",
+                onset => onset,
+            });
+            key.push_str(&syllable[onset.len()..]);
+        }
+    }
+    key
+}
+
+/// Dictionary words whose reading matches `query` ignoring tones (and, by
+/// `options`, aspiration or eo/oe), most frequent first, then shortest.
+/// "si" finds 時, 事, 試, ...; "nei hou" and "neihou" find 你好.
+pub fn search(trie: &Trie, query: &str, options: &SearchOptions) -> Vec<Hit> {
+    let query = key(&query.to_lowercase(), options);
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let mut hits = Vec::new();
+    collect(
+        trie,
+        trie.root(),
+        &mut String::new(),
+        &query,
+        options,
+        &mut hits,
+    );
+    hits.sort_by(|a, b| {
+        b.freq
+            .cmp(&a.freq)
+            .then(a.word.chars().count().cmp(&b.word.chars().count()))
+            .then_with(|| a.word.cmp(&b.word))
+    });
+    hits.truncate(options.limit);
+    hits
+}
+
+fn collect(
+    trie: &Trie,
+    node: &TrieNode,
+    word: &mut String,
+    query: &str,
+    options: &SearchOptions,
+    hits: &mut Vec<Hit>,
+) {
+    let matched = node.readings().find(|r| key(r, options) == query);
+    if let Some(reading) = matched {
+        hits.push(Hit {
+            word: word.clone(),
+            jyutping: reading,
+            freq: node.freq,
+        });
+    }
+    for (ch, child) in trie.children(node) {
+        word.push(ch);
+        collect(trie, child, word, query, options, hits);
+        word.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search() {
+        let mut trie = Trie::default();
+        for (word, reading, freq) in [
+            ("時", "si4", 50),
+            ("事", "si6", 80),
+            ("詩", "si1", 10),
+            ("你好", "nei5 hou2", 30),
+            ("長", "coeng4", 20),
+            ("張", "zoeng1", 40),
+            ("詩人", "si1 jan4", 5),
+        ] {
+            trie.insert(word, reading);
+            trie.insert_freq(word, freq);
+        }
+        let words = |query: &str, options: &str| -> Vec<String> {
+            let options = serde_json::from_str(options).unwrap();
+            search(&trie, query, &options)
+                .into_iter()
+                .map(|h| h.word)
+                .collect()
+        };
+
+        assert_eq!(words("si", "{}"), ["事", "時", "詩"]);
+        assert_eq!(words("SI2", r#"{"limit": 1}"#), ["事"]);
+        assert_eq!(words("neihou", "{}"), ["你好"]);
+        assert_eq!(words("nei hou", "{}"), ["你好"]);
+        assert_eq!(words("zoeng", "{}"), ["張"]);
+        assert_eq!(words("zoeng", r#"{"aspiration": true}"#), ["張", "長"]);
+        assert_eq!(words("zeong", r#"{"rounding": true}"#), ["張"]);
+        assert!(words("", "{}").is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+use std::ops::Range;
+
+/// True for punctuation that ends a sentence.
+fn is_terminator(ch: char) -> bool {
+    matches!(ch, '。' | '！' | '？' | '…' | '‥' | '!' | '?' | '\n')
+}
+
+/// True for closing quotes and brackets, which stay with the sentence they
+/// close: 「你好！」 is one sentence, not "「你好！" + "」".
+fn is_closer(ch: char) -> bool {
+    matches!(
+        ch,
+        '」' | '』'
+            | '”'
+            | '’'
+            | '）'
+            | '】'
+            | '》'
+            | '〉'
+            | '〕'
+            | '〗'
+            | '］'
+            | '｝'
+            | ')'
+            | ']'
+            | '}'
+    )
+}
+
+/// True for the ASCII quotes, which open as well as close: after a
+/// terminator they close the sentence only if the text goes on with
+/// whitespace, more closing punctuation or nothing at all, so that in
+/// 佢話。"你好" the second sentence keeps its opening quote.
+fn is_ascii_quote(ch: char) -> bool {
+    matches!(ch, '"' | '\'')
+}
+
+/// Whether an ASCII quote followed by `rest` closes a sentence.
+fn ends_quote(rest: &str) -> bool {
+    rest.chars()
+        .next()
+        .is_none_or(|c| c.is_whitespace() || is_terminator(c) || is_closer(c) || is_ascii_quote(c))
+}
+
+/// Byte ranges of the sentences in `text`. A sentence runs up to and
+/// including its run of terminators and closers (so "好！？」" stays
+/// together), and the ranges cover the whole text: concatenating them gives
+/// `text` back.
+pub fn sentence_spans(text: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        if !is_terminator(ch) {
+            continue;
+        }
+        while chars
+            .next_if(|&(i, c)| {
+                is_terminator(c)
+                    || is_closer(c)
+                    || (is_ascii_quote(c) && ends_quote(&text[i + 1..]))
+            })
+            .is_some()
+        {}
+        let end = chars.peek().map_or(text.len(), |&(i, _)| i);
+        spans.push(start..end);
+        start = end;
+    }
+    if start < text.len() {
+        spans.push(start..text.len());
+    }
+    spans
+}
+
+/// Group whole sentences into chunks of about `max_chars` characters.
+/// A single sentence longer than that becomes a chunk of its own.
+pub fn chunks(text: &str, max_chars: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    for span in sentence_spans(text) {
+        let span_len = text[span.clone()].chars().count();
+        if len > 0 && len + span_len > max_chars {
+            chunks.push(&text[start..span.start]);
+            start = span.start;
+            len = 0;
+        }
+        len += span_len;
+    }
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_spans() {
+        let text = "你好！？我係學生。\n冇嘢";
+        let sentences: Vec<_> = sentence_spans(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(sentences, ["你好！？", "我係學生。\n", "冇嘢"]);
+        assert!(sentence_spans("").is_empty());
+
+        // ellipses, closing quotes and brackets stay with their sentence
+        let text = "「你去邊呀？」佢問。我唔知……（真係）";
+        let sentences: Vec<_> = sentence_spans(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(
+            sentences,
+            ["「你去邊呀？」", "佢問。", "我唔知……", "（真係）"]
+        );
+
+        // an ASCII quote after a terminator closes only before a space or
+        // the end; otherwise it opens the next sentence
+        let text = "他說。\"你好\"佢話！\" 好！'";
+        let sentences: Vec<_> = sentence_spans(text).into_iter().map(|r| &text[r]).collect();
+        assert_eq!(sentences, ["他說。", "\"你好\"佢話！\"", " 好！'"]);
+    }
+
+    #[test]
+    fn test_chunks() {
+        let text = "一二。三四。五六七八九十。";
+        assert_eq!(chunks(text, 6), ["一二。三四。", "五六七八九十。"]);
+        assert_eq!(chunks(text, 100), [text]);
+        // never splits inside a sentence
+        assert_eq!(chunks(text, 1), ["一二。", "三四。", "五六七八九十。"]);
+        assert_eq!(chunks(text, 6).concat(), text);
+    }
+}
@@ -1,9 +1,243 @@
 use serde::Serialize;
+use std::collections::BTreeMap;
+use unicode_segmentation::UnicodeSegmentation;
 
-#[derive(Debug, Serialize, Clone)]
+use crate::lang::Lang;
+
+#[derive(Debug, Serialize, Clone, Default)]
 pub struct Token {
+    /// The token's text, exactly as in the input: the words of a
+    /// segmentation, concatenated, give the input back byte for byte (less
+    /// the readings written in it, with the `inline_readings` option).
     pub word: String,
     #[serde(rename = "jyutping")]
     pub reading: Option<String>,
+    /// One Yale syllable per syllable of the reading, in step with
+    /// `jyutping_syllables`: a syllable that does not convert is
+    /// `romanize::yale::UNCONVERTED` rather than dropped.
     pub yale: Option<Vec<String>>,
+    /// The reading split into syllables, for zipping with `yale` and the
+    /// characters of the word; None without a reading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jyutping_syllables: Option<Vec<String>>,
+    /// Corpus frequency of the matched entry; only output when requested
+    /// (`freq` option), None for tokens without a reading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub freq: Option<i64>,
+    /// The reading's weight in percent, on characters and on words whose
+    /// readings are weighted in the data; only output when requested
+    /// (`weights` option).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u8>,
+    /// The reading's share of the weights of all the entry's readings
+    /// (0–1), when it has more than one; output with `weight`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<f64>,
+    /// Every reading of the matched entry, most common first (`jyutping`
+    /// is the first unless overridden), when it has more than one; only
+    /// output when requested (`readings` option).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readings: Option<Vec<String>>,
+    /// Set by the `rare_below` option on tokens with a reading.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rare: Option<bool>,
+    /// The token's language, set by the `lang` option (see lang.rs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    /// Set to true by the `particles` option on sentence-final particles.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub particle: Option<bool>,
+    /// Set to true by the `guess_readings` option on characters whose
+    /// reading was guessed from their phonetic component (see guess.rs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guessed: Option<bool>,
+    /// Set to true on words of the names tier (data/names.tsv, or loaded
+    /// with `load_dictionary(b"names", ...)`); their romanizations are
+    /// capitalized.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proper_noun: Option<bool>,
+    /// The classifiers (measure words) of a noun, most common first: 本 for
+    /// 書, 架 and 部 for 車; only output when requested (`classifiers`
+    /// option).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classifiers: Option<Vec<String>>,
+    /// Set to true by `known_mode: "flag"` on words in `known_words`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub known: Option<bool>,
+    /// Set to true on tokens whose reading the `cloze` option withheld.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<bool>,
+    /// Tone number (1–6, 0 if unmarked) of each syllable, set by the
+    /// `tones` option; see jyutping.rs for the mapping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tones: Option<Vec<u8>>,
+    /// Tone numbers of the reading, set with `surface_tones` on tokens a
+    /// `sandhi` rule changed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub underlying_tones: Option<Vec<u8>>,
+    /// Tone numbers as spoken after the `sandhi` rules, e.g. `[4, 1]` for
+    /// 哥哥 go1 go1; see sandhi.rs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub surface_tones: Option<Vec<u8>>,
+    /// Kangxi radical number (1–214, 0 if unknown) of each character, set
+    /// by the `strokes` option where the radical-stroke data has any of
+    /// them; see unihan.rs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub radicals: Option<Vec<u8>>,
+    /// Total stroke count (0 if unknown) of each character, set with
+    /// `radicals`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub strokes: Option<Vec<u8>>,
+    /// The reading in the system chosen by the `romanization` option, one
+    /// entry per syllable; absent for Jyutping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub romanized: Option<Vec<String>>,
+    /// Set on links, kept whole rather than cut as text (see links.rs), and
+    /// on emoji (see emoji.rs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Category>,
+}
+
+/// What kind of link a token is.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    /// A URL or email address.
+    Url,
+    /// An @mention or hashtag.
+    Mention,
+    /// An emoji, ZWJ sequences and skin tones included.
+    Emoji,
+}
+
+/// The text `tokens` were segmented from: their words, concatenated.
+pub fn detokenize(tokens: &[Token]) -> String {
+    tokens.iter().map(|t| t.word.as_str()).collect()
+}
+
+/// Set the reading of the character at each offset in `readings` (the
+/// `readings_at` option): the whole reading of a one-character token, or
+/// the matching syllable of a word read one syllable per character. Other
+/// tokens are left as they are. Overridden tokens lose their weights.
+pub fn set_readings_at(tokens: &mut [Token], readings: &BTreeMap<usize, String>) {
+    let mut start = 0;
+    for token in tokens {
+        let len = token.word.chars().count();
+        for (&offset, reading) in readings.range(start..start + len) {
+            let reading = reading.trim();
+            if len == 1 {
+                token.reading = Some(reading.to_string());
+            } else if let Some(current) = &token.reading {
+                let mut syllables: Vec<&str> = current.split(' ').collect();
+                if syllables.len() != len {
+                    continue;
+                }
+                syllables[offset - start] = reading;
+                token.reading = Some(syllables.join(" "));
+            } else {
+                continue;
+            }
+            token.weight = None;
+            token.confidence = None;
+        }
+        start += len;
+    }
+}
+
+/// Merge each run of whitespace tokens into one (the `merge_whitespace`
+/// option), whose word is the run exactly as in the input: "\n", "\n",
+/// "  " become "\n\n  ". The text still round-trips.
+pub fn merge_whitespace(tokens: Vec<Token>) -> Vec<Token> {
+    let blank = |t: &Token| {
+        t.reading.is_none() && !t.word.is_empty() && t.word.chars().all(char::is_whitespace)
+    };
+    let mut out: Vec<Token> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match out.last_mut() {
+            Some(last) if blank(last) && blank(&token) => last.word.push_str(&token.word),
+            _ => out.push(token),
+        }
+    }
+    out
+}
+
+/// Split words read one syllable per grapheme into a token per character
+/// (`"ruby": "char"`, mono ruby): 學生 → 學 hok6, 生 saang1. Each keeps its
+/// syllable, Yale syllable, radical and stroke count; the rest (freq,
+/// flags, classifiers, ...) is the word's, copied, except for the word's
+/// other `readings`. Other tokens stay whole.
+pub fn split_by_char(tokens: Vec<Token>) -> Vec<Token> {
+    let mut out = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let graphemes: Vec<&str> = token.word.graphemes(true).collect();
+        let syllables: Vec<&str> = token
+            .reading
+            .as_deref()
+            .map(|r| r.split_whitespace().collect())
+            .unwrap_or_default();
+        if graphemes.len() < 2 || graphemes.len() != syllables.len() {
+            out.push(token);
+            continue;
+        }
+        let nth = |v: &Option<Vec<u8>>, i| match v {
+            Some(v) if v.len() == graphemes.len() => Some(vec![v[i]]),
+            _ => None,
+        };
+        for (i, (g, s)) in graphemes.iter().zip(&syllables).enumerate() {
+            out.push(Token {
+                word: g.to_string(),
+                reading: Some(s.to_string()),
+                yale: token.yale.as_ref().map(|y| vec![y[i].clone()]),
+                jyutping_syllables: Some(vec![s.to_string()]),
+                radicals: nth(&token.radicals, i),
+                strokes: nth(&token.strokes, i),
+                readings: None,
+                ..token.clone()
+            });
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_whitespace() {
+        let tokens = ["好", " ", "\t", "\n", "學", "\u{3000}", "。", " "]
+            .into_iter()
+            .map(|word| Token {
+                word: word.into(),
+                ..Default::default()
+            })
+            .collect();
+        let tokens = merge_whitespace(tokens);
+        let words: Vec<_> = tokens.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, ["好", " \t\n", "學", "\u{3000}", "。", " "]);
+    }
+
+    #[test]
+    fn test_split_by_char() {
+        let token = |word: &str, reading: Option<&str>| Token {
+            word: word.into(),
+            reading: reading.map(Into::into),
+            yale: reading.map(|r| r.split(' ').map(|s| s.to_uppercase()).collect()),
+            freq: Some(7),
+            ..Default::default()
+        };
+        let tokens = split_by_char(vec![
+            token("學生", Some("hok6 saang1")),
+            token("，", None),
+            token("OK", Some("ou1 kei1")),
+            token("part-time", Some("paat1 taai1")),
+        ]);
+        let words: Vec<_> = tokens.iter().map(|t| t.word.as_str()).collect();
+        assert_eq!(words, ["學", "生", "，", "O", "K", "part-time"]);
+        assert_eq!(tokens[1].reading.as_deref(), Some("saang1"));
+        assert_eq!(tokens[1].yale, Some(vec!["SAANG1".into()]));
+        assert_eq!(tokens[1].jyutping_syllables, Some(vec!["saang1".into()]));
+        assert_eq!(tokens[1].freq, Some(7));
+        assert_eq!(detokenize(&tokens), "學生，OKpart-time");
+    }
 }
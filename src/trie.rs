@@ -1,22 +1,570 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{UnicodeNormalization, is_nfc, is_nfkc};
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::token::Token;
-use crate::utils::{is_alpha_char, is_connector};
-use std::collections::HashMap;
+#[cfg(feature = "aho-corasick")]
+use crate::automaton::Automaton;
+use crate::bigram::Bigrams;
+use crate::emoji::{self, EmojiReadings};
+use crate::interner;
+use crate::links;
+use crate::options::{Normalization, Scoring};
+use crate::token::{Category, Token, detokenize};
+use crate::unihan::RadicalStrokes;
+use crate::utils::{CONNECTORS, ends_run, fold_ascii, fold_width, is_alpha_char, is_cjk};
+use crate::variants;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
 
-#[derive(Deserialize)]
 pub struct TrieNode {
-    pub children: HashMap<char, TrieNode>,
-    pub readings: Vec<String>,
+    /// Child node indices in `Trie::nodes`, sorted by char; see
+    /// `Trie::child`. Most nodes have only a few children, so a sorted list
+    /// is smaller than a map and as fast to search.
+    children: Vec<(char, u32)>,
+    /// The readings, most common first, as interned syllables (see
+    /// interner.rs); empty if the node is not an entry. Use `readings()`.
+    pub readings: Vec<u32>,
+    /// The weight of each reading from the data files, in percent (100
+    /// where the line gives none), in the same order; empty for entries
+    /// without weights. Every character has them, words only if a line of
+    /// words.tsv or lettered.tsv weights one of their readings.
+    pub weights: Vec<u8>,
+    pub freq: i64,
+}
+
+impl TrieNode {
+    pub fn new() -> Self {
+        TrieNode {
+            children: Vec::new(),
+            readings: Vec::new(),
+            weights: Vec::new(),
+            freq: 0,
+        }
+    }
+
+    /// The weight of the first reading, if the entry has weights.
+    pub fn weight(&self) -> Option<u8> {
+        self.weights.first().copied()
+    }
+
+    /// How sure the first reading is, when there are others to choose
+    /// from: its share of the total weight (an equal share if every weight
+    /// is 0).
+    pub fn confidence(&self) -> Option<f64> {
+        if self.weights.len() < 2 {
+            return None;
+        }
+        let total: u32 = self.weights.iter().map(|&w| w as u32).sum();
+        Some(if total == 0 {
+            1.0 / self.weights.len() as f64
+        } else {
+            self.weights[0] as f64 / total as f64
+        })
+    }
+
+    /// The readings, most common first.
+    pub fn readings(&self) -> impl Iterator<Item = String> + '_ {
+        interner::decode(&self.readings)
+    }
+
+    /// Index of the child for exactly `ch`.
+    fn child_id(&self, ch: char) -> Option<u32> {
+        let i = self.children.binary_search_by_key(&ch, |&(c, _)| c).ok()?;
+        Some(self.children[i].1)
+    }
+}
+
+/// A token the segmenter may pick: `chars[start..end]` for the `end` it is
+/// listed under.
+#[derive(Clone, Copy)]
+struct Edge {
+    start: usize,
+    source: Source,
+}
+
+/// Where a candidate token comes from. Readings are only looked up for the
+/// tokens that win.
+#[derive(Clone, Copy)]
+enum Source {
+    /// The single-cluster fallback. It takes the cluster's reading if there
+    /// is one (`cluster_node`) but scores as a token without an entry.
+    Cluster,
+    /// A dictionary entry with this frequency.
+    Entry(i64),
+    /// An alpha run with no entry.
+    Alpha,
+    /// A URL, email address, mention or hashtag, kept whole (see
+    /// links.rs).
+    Link(Category),
+}
+
+/// What the segmenter may make one token of, beyond the dictionary's
+/// entries (see `segment_with`).
+#[derive(Clone, Copy, Debug)]
+pub struct Matching<'a> {
+    /// Longest dictionary word, in chars, to match; None for no limit.
+    pub max_word_length: Option<usize>,
+    /// The characters an alpha run may have inside it, `CONNECTORS` by
+    /// default; of these, `+` and `#` may also end one (see `ends_run`).
+    pub connectors: &'a str,
+}
+
+impl Default for Matching<'_> {
+    fn default() -> Self {
+        Matching {
+            max_word_length: None,
+            connectors: CONNECTORS,
+        }
+    }
+}
+
+/// Buffers reused across calls by the DP.
+#[derive(Default)]
+struct Buffers {
+    /// See `walk`.
+    matches: Vec<Vec<(usize, i64)>>,
+    /// See `edges`.
+    edges: Vec<Vec<Edge>>,
+    /// dp[i] = best (token count, score) for the first i chars.
+    dp: Vec<(usize, i64)>,
+    /// track[i] = the last token of that split.
+    track: Vec<Edge>,
+}
+
+/// Scratch space for `segment_with`: the input and lookup text, and the DP
+/// buffers.
+#[derive(Default)]
+struct Workspace {
+    original: Vec<char>,
+    lookup: Vec<char>,
+    boundary: Vec<bool>,
+    to_original: Vec<usize>,
+    buffers: Buffers,
+}
+
+impl Workspace {
+    /// Fill in `text` and its lookup form under `normalization`.
+    fn load(&mut self, text: &str, normalization: Normalization) {
+        let Workspace {
+            original,
+            lookup,
+            boundary,
+            to_original,
+            ..
+        } = self;
+        original.clear();
+        original.extend(text.chars());
+        let already_normal = match normalization {
+            Normalization::None => true,
+            Normalization::Nfc => is_nfc(text),
+            Normalization::Nfkc => is_nfkc(text),
+        };
+
+        // the lookup text, its cluster boundaries, and for each of its char
+        // offsets the offset in the original
+        lookup.clear();
+        boundary.clear();
+        to_original.clear();
+        let mut offset = 0;
+        for cluster in text.graphemes(true) {
+            let len = lookup.len();
+            match normalization {
+                _ if already_normal => lookup.extend(cluster.chars()),
+                Normalization::Nfkc => lookup.extend(cluster.nfkc()),
+                _ => lookup.extend(cluster.nfc()),
+            }
+            if lookup.len() == len {
+                lookup.extend(cluster.chars());
+            }
+            boundary.push(true);
+            boundary.extend(std::iter::repeat_n(false, lookup.len() - len - 1));
+            to_original.extend(std::iter::repeat_n(offset, lookup.len() - len));
+            offset += cluster.chars().count();
+        }
+        boundary.push(true);
+        to_original.push(offset);
+    }
+}
+
+/// A dictionary entry found in a text by `Trie::lattice`, whether or not
+/// segmentation would pick it.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Match {
+    /// Character offsets in the text.
+    pub start: usize,
+    pub end: usize,
+    pub word: String,
+    /// The entry's most common reading.
+    #[serde(rename = "jyutping")]
+    pub reading: String,
     pub freq: i64,
 }
 
+/// Most candidate segmentations `Trie::explain` enumerates, and most it
+/// lists, so that a long span cannot blow up.
+const EXPLAIN_MAX_PATHS: usize = 1000;
+const EXPLAIN_SHOWN_PATHS: usize = 10;
+
+/// How `Trie::explain` segmented a span, for "wrong segmentation" reports.
+#[derive(Serialize, Debug)]
+pub struct Explanation {
+    pub scoring: Scoring,
+    /// From each start, the longest run of text the trie had nodes for,
+    /// entry or not: how far the dictionary walk got there.
+    pub explored: Vec<Explored>,
+    /// The entries found, as in `lattice`, with their frequencies.
+    pub matches: Vec<Match>,
+    /// Candidate segmentations, best first under the scoring; the chosen
+    /// one comes first.
+    pub paths: Vec<ScoredPath>,
+    /// Why the chosen path beat the next one.
+    pub reason: String,
+    /// Whether the span had more than `EXPLAIN_MAX_PATHS` segmentations,
+    /// so that only the first ones were ranked: `paths` and `reason` then
+    /// compare the chosen path with the best of those, which need not be
+    /// the real runner-up.
+    pub truncated: bool,
+}
+
+/// One start of the dictionary walk in `Explanation::explored`.
+#[derive(Serialize, Debug)]
+pub struct Explored {
+    pub start: usize,
+    pub path: String,
+}
+
+/// One candidate segmentation in `Explanation::paths`.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ScoredPath {
+    pub words: Vec<String>,
+    pub tokens: usize,
+    /// The second field the DP ranks by (see `Trie::gain`): the total
+    /// frequency under "fewest", the squared lengths under "longest", and
+    /// so on.
+    pub score: i64,
+    pub chosen: bool,
+}
+
+thread_local! {
+    /// Kept between calls, so that annotating many short strings (batches,
+    /// Typst show rules) does not allocate every buffer afresh each time.
+    static WORKSPACE: RefCell<Workspace> = RefCell::new(Workspace::default());
+}
+
+/// Run `f` with this thread's workspace.
+fn with_workspace<R>(f: impl FnOnce(&mut Workspace) -> R) -> R {
+    WORKSPACE.with(|cell| match cell.try_borrow_mut() {
+        Ok(mut workspace) => f(&mut workspace),
+        // only if segmentation were ever re-entered on this thread
+        Err(_) => f(&mut Workspace::default()),
+    })
+}
+
+/// Empty `rows` and size it to `len` rows, keeping each row's capacity.
+fn reset_rows<T>(rows: &mut Vec<Vec<T>>, len: usize) {
+    rows.truncate(len);
+    rows.iter_mut().for_each(Vec::clear);
+    rows.resize_with(len, Vec::new);
+}
+
+/// A node as build.rs writes it. The nodes are stored breadth first, so a
+/// node's children are the nodes right after those of the nodes before it,
+/// and only their chars are listed, in char order (as `TrieNode::children`
+/// keeps them).
 #[derive(Deserialize)]
+struct StoredNode {
+    children: Vec<char>,
+    #[serde(deserialize_with = "interner::deserialize")]
+    readings: Vec<u32>,
+    weights: Vec<u8>,
+    freq: i64,
+}
+
+#[derive(Deserialize)]
+struct StoredTrie {
+    nodes: Vec<StoredNode>,
+    max_len: HashMap<char, usize>,
+    names: Vec<String>,
+    classifiers: Vec<(String, Vec<String>)>,
+    radical_strokes: Vec<(char, RadicalStrokes)>,
+    ids: Vec<(char, String)>,
+    phonetics: Vec<(char, String)>,
+}
+
+#[derive(Deserialize)]
+#[serde(from = "StoredTrie")]
 pub struct Trie {
-    pub root: TrieNode,
+    /// Every node, in one arena: `nodes[0]` is the root, and children link
+    /// by index.
+    nodes: Vec<TrieNode>,
+    /// Word pair counts for `Scoring::Bigram`, loaded at runtime with
+    /// `load_bigrams` (lib.rs).
+    pub bigrams: Bigrams,
+    /// Emoji readings, loaded at runtime with `load_dictionary` (lib.rs),
+    /// for emoji tokens without an entry.
+    pub emoji: EmojiReadings,
+    /// Entries of the names tier (names.tsv, and `load_dictionary(b"names",
+    /// ...)` in lib.rs), whose tokens are flagged as proper nouns.
+    pub names: HashSet<String>,
+    /// Classifiers of nouns (classifiers.tsv): 書 → 本, 車 → 架, 部.
+    pub classifiers: HashMap<String, Vec<String>>,
+    /// Radical and stroke count of characters, from Unihan at build time or
+    /// `load_dictionary(b"unihan", ...)` (lib.rs); see unihan.rs.
+    pub radical_strokes: HashMap<char, RadicalStrokes>,
+    /// Ideographic description sequences of characters (data/ids.txt at
+    /// build time, or `load_dictionary(b"ids", ...)`), for `decompose`.
+    pub ids: HashMap<char, String>,
+    /// Series readings of phonetic components (phonetics.tsv), for
+    /// `guess_readings`; see guess.rs.
+    pub phonetics: HashMap<char, String>,
+    /// Length in chars of the longest entry under each first character,
+    /// recorded when the trie is built and kept up to date by `insert`.
+    max_len: HashMap<char, usize>,
+    /// Built on first use and dropped by `insert`.
+    #[cfg(feature = "aho-corasick")]
+    automaton: OnceLock<Option<Automaton>>,
+    /// Sum of all entry frequencies, for `Scoring::Unigram`; computed on
+    /// first use. `insert` leaves it alone, as runtime entries have
+    /// frequency 0, but `insert_freq` and `replace_freqs` drop it.
+    total_freq: OnceLock<i64>,
+}
+
+/// An empty trie.
+impl Default for Trie {
+    fn default() -> Self {
+        Trie {
+            nodes: vec![TrieNode::new()],
+            bigrams: Bigrams::default(),
+            emoji: EmojiReadings::default(),
+            names: HashSet::new(),
+            classifiers: HashMap::new(),
+            radical_strokes: HashMap::new(),
+            ids: HashMap::new(),
+            phonetics: HashMap::new(),
+            max_len: HashMap::new(),
+            #[cfg(feature = "aho-corasick")]
+            automaton: OnceLock::new(),
+            total_freq: OnceLock::new(),
+        }
+    }
+}
+
+impl From<StoredTrie> for Trie {
+    fn from(stored: StoredTrie) -> Self {
+        let mut next = 1;
+        let nodes = stored
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let children = node
+                    .children
+                    .into_iter()
+                    .map(|ch| {
+                        next += 1;
+                        (ch, next - 1)
+                    })
+                    .collect();
+                TrieNode {
+                    children,
+                    readings: node.readings,
+                    weights: node.weights,
+                    freq: node.freq,
+                }
+            })
+            .collect();
+        Trie {
+            nodes,
+            max_len: stored.max_len,
+            names: stored.names.into_iter().collect(),
+            classifiers: stored.classifiers.into_iter().collect(),
+            radical_strokes: stored.radical_strokes.into_iter().collect(),
+            ids: stored.ids.into_iter().collect(),
+            phonetics: stored.phonetics.into_iter().collect(),
+            ..Trie::default()
+        }
+    }
 }
 
 impl Trie {
+    /// `total_freq` if `scoring` needs it, else 0.
+    fn total_freq_for(&self, scoring: Scoring) -> i64 {
+        match scoring {
+            Scoring::Unigram => self.total_freq(),
+            _ => 0,
+        }
+    }
+
+    fn total_freq(&self) -> i64 {
+        *self
+            .total_freq
+            .get_or_init(|| self.nodes.iter().map(|node| node.freq).sum())
+    }
+
+    /// The root node, for the empty word.
+    pub fn root(&self) -> &TrieNode {
+        &self.nodes[0]
+    }
+
+    /// Child of `node` for `ch`, falling back to the ASCII form of a
+    /// full-width character or a typographic apostrophe or hyphen, so
+    /// "ＡＢ膠" and "３％" find "AB膠" and "%" and "part‑time" (U+2011) finds
+    /// "part-time", and then to the other forms of a variant character, so
+    /// "裡面" finds "裏面" (see variants.rs). The original is tried first
+    /// because some entries (笑左，笑埋右) are keyed full-width.
+    pub fn child(&self, node: &TrieNode, ch: char) -> Option<&TrieNode> {
+        let id = node
+            .child_id(ch)
+            .or_else(|| {
+                let folded = fold_ascii(ch);
+                if folded == ch {
+                    None
+                } else {
+                    node.child_id(folded)
+                }
+            })
+            .or_else(|| variants::others(ch).find_map(|other| node.child_id(other)))?;
+        Some(&self.nodes[id as usize])
+    }
+
+    /// Children of `node` with their chars, in char order.
+    pub fn children<'a>(
+        &'a self,
+        node: &'a TrieNode,
+    ) -> impl Iterator<Item = (char, &'a TrieNode)> + 'a {
+        node.children
+            .iter()
+            .map(|&(ch, id)| (ch, &self.nodes[id as usize]))
+    }
+
+    /// Length in chars of the longest entry that can start with `ch`,
+    /// including entries keyed by its ASCII form or another variant form
+    /// (see `child`).
+    fn max_len(&self, ch: char) -> usize {
+        let len = |c| self.max_len.get(&c).copied().unwrap_or(0);
+        let own = len(ch).max(len(fold_ascii(ch)));
+        variants::others(ch).map(len).fold(own, usize::max)
+    }
+
+    /// The radical and the stroke count of each character of `word`, 0
+    /// where unknown; None if no character of it is known.
+    pub fn radicals_and_strokes(&self, word: &str) -> Option<(Vec<u8>, Vec<u8>)> {
+        let known: Vec<RadicalStrokes> = word
+            .chars()
+            .map(|ch| self.radical_strokes.get(&ch).copied().unwrap_or_default())
+            .collect();
+        if known.iter().all(|&entry| entry == (0, 0)) {
+            return None;
+        }
+        Some(known.into_iter().unzip())
+    }
+
+    /// Add a proper noun at runtime: `insert`, and record it in `names`.
+    pub fn insert_name(&mut self, word: &str, reading: &str) {
+        if word.is_empty() || reading.is_empty() {
+            return;
+        }
+        self.insert(word, reading);
+        self.names.insert(word.to_string());
+    }
+
+    /// Add a reading at runtime (see `load_dictionary` in lib.rs).
+    /// The reading is appended after any bundled readings, so it only wins
+    /// for words the bundled data does not cover.
+    pub fn insert(&mut self, word: &str, reading: &str) {
+        if word.is_empty() || reading.is_empty() {
+            return;
+        }
+        #[cfg(feature = "aho-corasick")]
+        {
+            self.automaton = OnceLock::new();
+        }
+        if let Some(first) = word.chars().next() {
+            let len = self.max_len.entry(first).or_insert(0);
+            *len = (*len).max(word.chars().count());
+        }
+        let node = self.node_mut(word);
+        if !node.readings().any(|r| r == reading) {
+            interner::encode(reading, &mut node.readings);
+            // runtime readings rank last, like CEDICT ones at build time
+            if !node.weights.is_empty() {
+                node.weights.push(0);
+            }
+        }
+    }
+
+    /// Node for `word`, appending any missing nodes to the arena.
+    fn node_mut(&mut self, word: &str) -> &mut TrieNode {
+        let mut id = 0;
+        for ch in word.chars() {
+            let children = &self.nodes[id].children;
+            id = match children.binary_search_by_key(&ch, |&(c, _)| c) {
+                Ok(i) => children[i].1 as usize,
+                Err(i) => {
+                    let child = self.nodes.len();
+                    self.nodes[id].children.insert(i, (ch, child as u32));
+                    self.nodes.push(TrieNode::new());
+                    child
+                }
+            };
+        }
+        &mut self.nodes[id]
+    }
+
+    /// Set the corpus frequency of `word`, if the dictionary has it as
+    /// written; returns whether it did.
+    pub fn insert_freq(&mut self, word: &str, freq: i64) -> bool {
+        let mut id = 0;
+        for ch in word.chars() {
+            match self.nodes[id].child_id(ch) {
+                Some(child) => id = child as usize,
+                None => return false,
+            }
+        }
+        let node = &mut self.nodes[id];
+        if id == 0 || node.readings.is_empty() {
+            return false;
+        }
+        node.freq = freq;
+        self.total_freq = OnceLock::new();
+        true
+    }
+
+    /// Replace the corpus frequencies (freq.txt) with those in `table`:
+    /// each listed word gets its count, every other word 0, so that counts
+    /// from a different corpus are not mixed with the bundled ones. Returns
+    /// the number of words set.
+    pub fn replace_freqs(&mut self, table: &[(String, i64)]) -> usize {
+        for node in &mut self.nodes {
+            node.freq = 0;
+        }
+        self.total_freq = OnceLock::new();
+        let mut count = 0;
+        for (word, freq) in table {
+            if self.insert_freq(word, *freq) {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// A small trie of per-call readings (the `overrides` option), for
+    /// `segment_with` to consult before this one; None if there are none.
+    /// Words this trie has keep their frequency.
+    pub fn overlay(&self, entries: &BTreeMap<String, String>) -> Option<Trie> {
+        if entries.is_empty() {
+            return None;
+        }
+        let mut overlay = Trie::default();
+        for (word, reading) in entries {
+            overlay.insert(word, reading.trim());
+            if let Some(node) = self.lookup(&word.chars().collect::<Vec<_>>()) {
+                overlay.node_mut(word).freq = node.freq;
+            }
+        }
+        Some(overlay)
+    }
+
     /// Segment text into tokens using trie + dynamic programming.
     ///
     /// dp[i] = (token_count, total_freq) for the best segmentation of the
@@ -32,6 +580,12 @@ impl Trie {
     ///         freq(學生)=71278 >> freq(好學)=2847 → "好"+"學生" wins
     ///   → reconstruct: track[3]=(1,"學生"), track[1]=(0,"好") → ["好","學生"]
     ///
+    /// This is the default `Scoring::Fewest`; `segment_with` can also rank
+    /// by word length, by frequency first, or by unigram log probability
+    /// (Viterbi over freq.txt) with no token-count term at all (see `gain`),
+    /// optionally conditioned on the previous word (`segment_bigram`), or
+    /// skip the DP for greedy maximum matching (`forward`, `backward`).
+    ///
     /// Tokenisation rules for non-CJK characters:
     ///
     /// 1. ALPHA RUNS — a contiguous span where every character is either:
@@ -39,12 +593,14 @@ impl Trie {
     ///      like é since Rust's `is_alphanumeric()` covers all Unicode letters), or
     ///    - an intra-word connector (hyphen `-`, underscore `_`, apostrophe `'`)
     ///      that is surrounded by alphanumeric chars on both sides
+    ///
     ///    is merged into one token. This handles:
-    ///      "package"    → one token (no dict entry needed)
-    ///      "café"       → one token (é is alphanumeric)
-    ///      "part-time"  → one token if in lettered dict; otherwise hyphen splits it
-    ///      "rust_canto" → one token
-    ///      "i'm"        → one token
+    ///    - "package"    → one token (no dict entry needed)
+    ///    - "café"       → one token (é is alphanumeric)
+    ///    - "part-time"  → one token if in lettered dict; otherwise hyphen splits it
+    ///    - "rust_canto" → one token
+    ///    - "i'm"        → one token
+    ///
     ///    The trie walk always runs first. If the trie finds a reading for the span
     ///    (e.g. "ge" → "ge3", "café" → "kat6 fei1"), that reading is used. The
     ///    alpha-run fallback only fires when the trie has no entry, giving reading=None.
@@ -53,114 +609,959 @@ impl Trie {
     ///    - Whitespace (space, tab, newline) → each becomes its own token, no reading
     ///    - Punctuation and symbols, including `%` → each becomes its own token;
     ///      the trie is checked for a reading (e.g. "%" → "pat6 sen1")
+    ///
     ///    This ensures "3%" splits into "3" (alpha run) + "%" (standalone), so that
     ///    the Cantonese reading of "%" can be displayed independently.
+    ///
+    /// 3. FULL-WIDTH FORMS — full-width Latin letters, digits and symbols
+    ///    (ＡＢＣ１２３％－) are looked up as their ASCII equivalents, so they
+    ///    hit lettered entries and connect alpha runs, while `Token::word`
+    ///    keeps the original text.
+    ///
+    /// 4. GRAPHEME CLUSTERS — token boundaries only fall between extended
+    ///    grapheme clusters, so an emoji ZWJ sequence (👨‍👩‍👧), a flag (🇭🇰) or a
+    ///    letter plus combining marks is one unit: the standalone fallback
+    ///    covers a whole cluster, and alpha runs are judged by the first char
+    ///    of each cluster.
+    ///
+    /// 5. NORMALIZATION — input is NFC-normalized before lookup (see
+    ///    `segment_with`), so decomposed text pasted from PDFs still hits the
+    ///    dictionary. Token words are always slices of the original text.
+    ///
+    /// 6. ROUND TRIP — the tokens cover the input in order, so concatenating
+    ///    their words gives it back byte for byte, whitespace, control
+    ///    characters and all (see `detokenize`).
+    ///
+    /// 7. LINKS — URLs, email addresses, @mentions and hashtags are found
+    ///    before anything else (see links.rs) and kept whole, with a
+    ///    `category` and no reading, instead of being cut at every dot and
+    ///    slash.
+    ///
+    /// 8. EMOJI — a single-cluster token that is an emoji gets the
+    ///    `category` "emoji", and, if the trie has no reading for it, the
+    ///    one loaded into `emoji` (see emoji.rs).
     pub fn segment(&self, text: &str) -> Vec<Token> {
-        let chars: Vec<char> = text.chars().collect();
-        let n = chars.len();
+        self.segment_with(
+            text,
+            Normalization::Nfc,
+            Scoring::Fewest,
+            Matching::default(),
+            None,
+        )
+    }
 
-        let mut dp: Vec<(usize, i64)> = vec![(usize::MAX, 0); n + 1];
-        let mut track: Vec<(usize, Option<String>)> = vec![(0, None); n + 1];
-        dp[0] = (0, 0);
+    /// `segment` with an explicit normalization form and scoring policy,
+    /// matching dictionary words of at most `matching.max_word_length`
+    /// chars if set, and alpha runs joined by `matching.connectors`.
+    /// Each grapheme cluster is normalized on its own, so cluster boundaries
+    /// in the normalized text map back to the original; a cluster that
+    /// expands to several (NFKC "㍿" → "株式会社") can only be matched as a
+    /// whole.
+    ///
+    /// Entries of `overlay` (see `overlay`) are matched along with this
+    /// trie's, and their readings win where both have the word.
+    pub fn segment_with(
+        &self,
+        text: &str,
+        normalization: Normalization,
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) -> Vec<Token> {
+        let tokens = with_workspace(|workspace| {
+            self.segment_in(workspace, text, normalization, scoring, matching, overlay)
+        });
+        debug_assert_eq!(detokenize(&tokens), text, "tokens must cover the input");
+        tokens
+    }
 
-        for end in 1..=n {
-            // --- single-character fallback ---
-            // Covers whitespace, punctuation, symbols, and any character with no
-            // better multi-char match. Checks the trie for a reading so that
-            // single-char lettered entries like "%" → "pat6 sen1" are not lost.
-            if dp[end - 1].0 != usize::MAX {
-                let single_reading = self
-                    .root
-                    .children
-                    .get(&chars[end - 1])
-                    .and_then(|n| n.readings.first().cloned());
-                let cost = (dp[end - 1].0 + 1, dp[end - 1].1);
-                if Self::better(&cost, &dp[end]) {
-                    dp[end] = cost;
-                    track[end] = (end - 1, single_reading);
+    /// `segment_with` using the buffers in `workspace`.
+    fn segment_in(
+        &self,
+        workspace: &mut Workspace,
+        text: &str,
+        normalization: Normalization,
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) -> Vec<Token> {
+        workspace.load(text, normalization);
+        let Workspace {
+            original,
+            lookup,
+            boundary,
+            to_original,
+            buffers,
+        } = workspace;
+
+        self.segment_chars(buffers, lookup, boundary, scoring, matching, overlay)
+            .into_iter()
+            .map(|(start, end, matched, category)| {
+                let (start, end) = (to_original[start], to_original[end]);
+                let word = &original[start..end];
+                let reading = match category {
+                    Some(Category::Emoji) if matched.is_none() => {
+                        self.emoji.get(word).map(str::to_string)
+                    }
+                    _ => matched.and_then(|node| node.readings().next()),
+                };
+                let word: String = word.iter().collect();
+                let proper_noun = matched.is_some() && self.names.contains(&word);
+                let classifiers = matched.and_then(|_| self.classifiers.get(&word)).cloned();
+                Token {
+                    proper_noun: proper_noun.then_some(true),
+                    classifiers,
+                    word,
+                    reading,
+                    freq: matched.map(|node| node.freq),
+                    weight: matched.and_then(TrieNode::weight),
+                    confidence: matched.and_then(TrieNode::confidence),
+                    readings: matched
+                        .map(|node| node.readings().collect::<Vec<_>>())
+                        .filter(|readings| readings.len() > 1),
+                    category,
+                    ..Default::default() // yale filled in by annotate() in lib.rs
+                }
+            })
+            .collect()
+    }
+
+    /// Where `segment` would split `text`, as character offsets: 0, the end
+    /// of each token, in order. No readings are looked up, so this is the
+    /// fast path for hosts that only need the tokenization.
+    pub fn boundaries(&self, text: &str) -> Vec<usize> {
+        with_workspace(|workspace| {
+            workspace.load(text, Normalization::Nfc);
+            let Workspace {
+                lookup,
+                boundary,
+                to_original,
+                buffers,
+                ..
+            } = workspace;
+            let spans = self.spans(
+                buffers,
+                lookup,
+                boundary,
+                Scoring::Fewest,
+                Matching::default(),
+                None,
+            );
+            std::iter::once(0)
+                .chain(spans.into_iter().map(|(_, end, _)| to_original[end]))
+                .collect()
+        })
+    }
+
+    /// Every dictionary entry in `text` (NFC-normalized for lookup, as by
+    /// `segment`), by start and then end, without choosing between
+    /// overlapping ones: the candidates the segmenter decides among, minus
+    /// its fallbacks for text without an entry.
+    pub fn lattice(&self, text: &str) -> Vec<Match> {
+        let mut workspace = Workspace::default();
+        workspace.load(text, Normalization::Nfc);
+        let Workspace {
+            original,
+            lookup,
+            boundary,
+            to_original,
+            buffers,
+        } = &mut workspace;
+        #[cfg(not(feature = "aho-corasick"))]
+        self.walk(&mut buffers.matches, lookup, boundary, None);
+        #[cfg(feature = "aho-corasick")]
+        self.scan(&mut buffers.matches, lookup, boundary, None);
+
+        let mut lattice = Vec::new();
+        for (start, found) in buffers.matches.iter().enumerate() {
+            for &(end, freq) in found {
+                let Some(node) = self.lookup(&lookup[start..end]) else {
+                    continue;
+                };
+                let (start, end) = (to_original[start], to_original[end]);
+                lattice.push(Match {
+                    start,
+                    end,
+                    word: original[start..end].iter().collect(),
+                    reading: node.readings().next().unwrap_or_default(),
+                    freq,
+                });
+            }
+        }
+        lattice
+    }
+
+    /// Explain the segmentation of `text` (best kept short, a phrase or a
+    /// sentence): how far the dictionary walk got from each start, the
+    /// entries it matched, the candidate segmentations with their scores,
+    /// and why the chosen one won. Links get no special treatment.
+    ///
+    /// Candidates are enumerated depth first and only the first
+    /// `EXPLAIN_MAX_PATHS` are ranked; past that, `truncated` is set and the
+    /// reason says so, as the runner-up it names may not be the real one.
+    pub fn explain(
+        &self,
+        text: &str,
+        normalization: Normalization,
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) -> Explanation {
+        let mut workspace = Workspace::default();
+        workspace.load(text, normalization);
+        let Workspace {
+            original,
+            lookup,
+            boundary,
+            to_original,
+            buffers,
+        } = &mut workspace;
+        let n = lookup.len();
+        let chosen: Vec<(usize, usize)> = self
+            .piece_spans(buffers, lookup, boundary, scoring, matching, overlay)
+            .into_iter()
+            .map(|(start, end, _)| (start, end))
+            .collect();
+        // `piece_spans` filled the edges; list them by start
+        let mut from: Vec<Vec<(usize, i64)>> = vec![Vec::new(); n + 1];
+        let mut matches = Vec::new();
+        for (end, edges) in buffers.edges.iter().enumerate() {
+            for edge in edges {
+                let freq = match edge.source {
+                    Source::Entry(freq) => freq,
+                    _ => 0,
+                };
+                from[edge.start].push((end, freq));
+                if !matches!(edge.source, Source::Entry(_)) {
+                    continue;
                 }
+                let span = &lookup[edge.start..end];
+                let Some(node) = overlay
+                    .and_then(|o| o.lookup(span))
+                    .or_else(|| self.lookup(span))
+                else {
+                    continue;
+                };
+                let (start, end) = (to_original[edge.start], to_original[end]);
+                matches.push(Match {
+                    start,
+                    end,
+                    word: original[start..end].iter().collect(),
+                    reading: node.readings().next().unwrap_or_default(),
+                    freq,
+                });
+            }
+        }
+        matches.sort_by_key(|m| (m.start, m.end));
+        matches.dedup();
+        // one edge per span, the entry rather than the bare cluster
+        for edges in &mut from {
+            edges.sort_by_key(|&(end, freq)| (end, std::cmp::Reverse(freq)));
+            edges.dedup_by_key(|&mut (end, _)| end);
+        }
+
+        let explored = (0..n)
+            .filter(|&start| boundary[start])
+            .filter_map(|start| {
+                let mut node = self.root();
+                let mut end = start;
+                while let Some(child) = lookup.get(end).and_then(|&ch| self.child(node, ch)) {
+                    node = child;
+                    end += 1;
+                }
+                (end > start).then(|| Explored {
+                    start: to_original[start],
+                    path: original[to_original[start]..to_original[end]]
+                        .iter()
+                        .collect(),
+                })
+            })
+            .collect();
+
+        let total = self.total_freq_for(scoring);
+        let score = |spans: &[(usize, usize, i64)], chosen: bool| ScoredPath {
+            words: spans
+                .iter()
+                .map(|&(s, e, _)| original[to_original[s]..to_original[e]].iter().collect())
+                .collect(),
+            tokens: spans.len(),
+            score: spans
+                .iter()
+                .map(|&(s, e, freq)| Self::gain(scoring, freq, e - s, total))
+                .sum(),
+            chosen,
+        };
+        let freq_of = |start: usize, end: usize| {
+            from[start]
+                .iter()
+                .find(|&&(e, _)| e == end)
+                .map_or(0, |&(_, freq)| freq)
+        };
+        let chosen: Vec<(usize, usize, i64)> = chosen
+            .into_iter()
+            .map(|(s, e)| (s, e, freq_of(s, e)))
+            .collect();
+        let mut paths = Vec::new();
+        let mut truncated = false;
+        let mut stack = vec![(0, Vec::new())];
+        while let Some((pos, spans)) = stack.pop() {
+            if paths.len() == EXPLAIN_MAX_PATHS {
+                truncated = true;
+                break;
+            }
+            if pos == n {
+                if spans != chosen {
+                    paths.push(score(&spans, false));
+                }
+                continue;
+            }
+            for &(end, freq) in &from[pos] {
+                let mut next = spans.clone();
+                next.push((pos, end, freq));
+                stack.push((end, next));
+            }
+        }
+        let key = |p: &ScoredPath| (p.tokens, p.score);
+        paths.sort_by(|a, b| {
+            if Self::better(scoring, &key(a), &key(b)) {
+                std::cmp::Ordering::Less
+            } else if Self::better(scoring, &key(b), &key(a)) {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        });
+        paths.dedup_by(|a, b| a.words == b.words);
+        paths.truncate(EXPLAIN_SHOWN_PATHS - 1);
+        let chosen = score(&chosen, true);
+        let mut reason = Self::reason(scoring, &chosen, paths.first());
+        if truncated {
+            reason.push_str(&format!(
+                " (among the first {EXPLAIN_MAX_PATHS} candidate segmentations only)"
+            ));
+        }
+        paths.insert(0, chosen);
+        Explanation {
+            scoring,
+            explored,
+            matches,
+            paths,
+            reason,
+            truncated,
+        }
+    }
+
+    /// Why `chosen` beat `next` under `scoring`, in words.
+    fn reason(scoring: Scoring, chosen: &ScoredPath, next: Option<&ScoredPath>) -> String {
+        let Some(next) = next else {
+            return "the only segmentation".to_string();
+        };
+        match scoring {
+            Scoring::Forward => {
+                return "forward maximum matching: the longest entry from the start".to_string();
+            }
+            Scoring::Backward => {
+                return "backward maximum matching: the longest entry from the end".to_string();
+            }
+            Scoring::Bigram => {
+                return "bigram scoring: the likeliest path given word pair counts".to_string();
+            }
+            _ => {}
+        }
+        let by_score = matches!(scoring, Scoring::Frequency | Scoring::Unigram);
+        if by_score && chosen.score != next.score {
+            format!("a higher score: {} against {}", chosen.score, next.score)
+        } else if chosen.tokens == next.tokens && chosen.score != next.score {
+            format!(
+                "as many tokens ({}), and a higher score: {} against {}",
+                chosen.tokens, chosen.score, next.score
+            )
+        } else if chosen.tokens != next.tokens {
+            format!("fewer tokens: {} against {}", chosen.tokens, next.tokens)
+        } else {
+            format!(
+                "a tie with the next path ({} tokens, score {}): the earlier candidate wins",
+                chosen.tokens, chosen.score
+            )
+        }
+    }
+
+    /// DP core of `segment`: the best segmentation of `chars` as
+    /// `(start, end, matched node, category)` spans, cutting only where
+    /// `boundary` is true. The node is None for tokens without a reading,
+    /// the category for tokens that are neither links nor emoji.
+    fn segment_chars<'a>(
+        &'a self,
+        buffers: &mut Buffers,
+        chars: &[char],
+        boundary: &[bool],
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&'a Trie>,
+    ) -> Vec<(usize, usize, Option<&'a TrieNode>, Option<Category>)> {
+        // look up the readings of the winning tokens, in the overlay first
+        self.spans(buffers, chars, boundary, scoring, matching, overlay)
+            .into_iter()
+            .map(|(start, end, source)| {
+                let span = &chars[start..end];
+                let matched = match source {
+                    Source::Cluster => overlay
+                        .and_then(|o| o.cluster_node(span))
+                        .or_else(|| self.cluster_node(span)),
+                    Source::Entry(_) => overlay
+                        .and_then(|o| o.lookup(span))
+                        .or_else(|| self.lookup(span)),
+                    Source::Alpha | Source::Link(_) => None,
+                };
+                let category = match source {
+                    Source::Link(category) => Some(category),
+                    Source::Cluster if emoji::is_emoji(span) => Some(Category::Emoji),
+                    _ => None,
+                };
+                (start, end, matched, category)
+            })
+            .collect()
+    }
+
+    /// The winning tokens of `segment_chars`, as `(start, end, source)`
+    /// spans. Links (see links.rs) are tokens of their own; the text
+    /// between them is segmented piece by piece.
+    fn spans(
+        &self,
+        buffers: &mut Buffers,
+        chars: &[char],
+        boundary: &[bool],
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) -> Vec<(usize, usize, Source)> {
+        let links = links::find(chars, boundary);
+        if links.is_empty() {
+            return self.piece_spans(buffers, chars, boundary, scoring, matching, overlay);
+        }
+        let mut spans = Vec::new();
+        let mut piece = |spans: &mut Vec<_>, from: usize, to: usize| {
+            if from < to {
+                let found = self.piece_spans(
+                    buffers,
+                    &chars[from..to],
+                    &boundary[from..=to],
+                    scoring,
+                    matching,
+                    overlay,
+                );
+                spans.extend(
+                    found
+                        .into_iter()
+                        .map(|(s, e, source)| (from + s, from + e, source)),
+                );
+            }
+        };
+        let mut pos = 0;
+        for (start, end, category) in links {
+            piece(&mut spans, pos, start);
+            spans.push((start, end, Source::Link(category)));
+            pos = end;
+        }
+        piece(&mut spans, pos, chars.len());
+        spans
+    }
+
+    /// `spans` for a piece of text without links.
+    fn piece_spans(
+        &self,
+        buffers: &mut Buffers,
+        chars: &[char],
+        boundary: &[bool],
+        scoring: Scoring,
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) -> Vec<(usize, usize, Source)> {
+        self.edges(buffers, chars, boundary, matching, overlay);
+        match scoring {
+            Scoring::Bigram => self.segment_bigram(chars, &buffers.edges),
+            Scoring::Forward => Self::forward(&buffers.edges, chars.len()),
+            Scoring::Backward => Self::backward(&buffers.edges, chars.len()),
+            _ => Self::best_path(buffers, chars.len(), scoring, self.total_freq_for(scoring)),
+        }
+    }
+
+    /// `Scoring::Forward`, forward maximum matching: from the start of the
+    /// text, take the longest candidate token and carry on after it. Of two
+    /// candidates for the same span the later listed wins, so a dictionary
+    /// entry beats the bare cluster.
+    fn forward(edges: &[Vec<Edge>], n: usize) -> Vec<(usize, usize, Source)> {
+        // longest[start] = (end, source) of the longest token from start
+        let mut longest: Vec<Option<(usize, Source)>> = vec![None; n + 1];
+        for (end, candidates) in edges.iter().enumerate() {
+            for edge in candidates {
+                longest[edge.start] = Some((end, edge.source));
             }
+        }
 
-            // --- multi-character spans ---
-            for start in (0..end).rev() {
+        let mut spans = Vec::new();
+        let mut curr = 0;
+        while curr < n {
+            let Some((end, source)) = longest[curr] else {
+                break;
+            };
+            spans.push((curr, end, source));
+            curr = end;
+        }
+        spans
+    }
+
+    /// `Scoring::Backward`, backward maximum matching: `forward` from the
+    /// end of the text, taking the longest token that ends where the last
+    /// one started. `edges[end]` lists the farthest start last.
+    fn backward(edges: &[Vec<Edge>], n: usize) -> Vec<(usize, usize, Source)> {
+        let mut spans = Vec::new();
+        let mut curr = n;
+        while curr > 0 {
+            let Some(edge) = edges[curr].last() else {
+                break;
+            };
+            spans.push((edge.start, curr, edge.source));
+            curr = edge.start;
+        }
+        spans.reverse();
+        spans
+    }
+
+    /// The DP over `buffers.edges` for the other scorings, as
+    /// `(start, end, source)` spans.
+    fn best_path(
+        buffers: &mut Buffers,
+        n: usize,
+        scoring: Scoring,
+        total: i64,
+    ) -> Vec<(usize, usize, Source)> {
+        let Buffers {
+            edges, dp, track, ..
+        } = buffers;
+
+        // track[end] = the winning token ending at end
+        dp.clear();
+        dp.resize(n + 1, (usize::MAX, 0));
+        track.clear();
+        track.resize(
+            n + 1,
+            Edge {
+                start: 0,
+                source: Source::Alpha,
+            },
+        );
+        dp[0] = (0, 0);
+
+        for end in 1..=n {
+            for edge in &edges[end] {
+                let start = edge.start;
                 if dp[start].0 == usize::MAX {
                     continue;
                 }
+                let freq = match edge.source {
+                    Source::Entry(freq) => freq,
+                    _ => 0,
+                };
+                let cost = (
+                    dp[start].0 + 1,
+                    dp[start].1 + Self::gain(scoring, freq, end - start, total),
+                );
+                if Self::better(scoring, &cost, &dp[end]) {
+                    dp[end] = cost;
+                    track[end] = *edge;
+                }
+            }
+        }
 
-                // TRIE WALK: look up chars[start..end] in the trie.
-                // Matches CJK words (words.tsv), mixed Latin+CJK entries (AB膠,
-                // Hap唔Happy呀), hyphenated entries (chok-cheat, part-time), and
-                // any other lettered dict entries that carry a Jyutping reading.
-                // trie_matched is set as soon as a reading is found at end-1,
-                // regardless of whether that reading wins dp[end], so that the
-                // alpha-run fallback below stays silent for known words.
-                let mut node = &self.root;
-                let mut trie_matched = false;
-                for j in start..end {
-                    let ch = chars[j];
-                    match node.children.get(&ch) {
-                        None => break,
-                        Some(child) => {
-                            node = child;
-                            if j == end - 1 && !node.readings.is_empty() {
-                                trie_matched = true;
-                                let cost = (dp[start].0 + 1, dp[start].1 + node.freq);
-                                if Self::better(&cost, &dp[end]) {
-                                    dp[end] = cost;
-                                    track[end] = (start, Some(node.readings[0].clone()));
-                                }
-                            }
-                        }
+        // reconstruct token spans by following track[] backwards
+        let mut spans = Vec::new();
+        let mut curr = n;
+        while curr > 0 {
+            let edge = track[curr];
+            spans.push((edge.start, curr, edge.source));
+            curr = edge.start;
+        }
+        spans.reverse();
+        spans
+    }
+
+    /// Fill `buffers.edges` with the candidate tokens of `chars`:
+    /// `edges[end]` lists every token that may end at `end`, in the order
+    /// the DP tries them (ties go to the first): the single-cluster
+    /// fallback, then spans from the nearest start to the farthest.
+    ///
+    /// Each start is scanned forward once, and the scan stops as soon as
+    /// the trie has no child for the next char (or the next cluster cannot
+    /// continue an alpha run), so the work is O(n·L) for words of at most
+    /// L chars rather than a fresh lookup for every (start, end) pair. L is
+    /// the longest entry under the start's first char (`max_len`), capped
+    /// by `matching.max_word_length`.
+    fn edges(
+        &self,
+        buffers: &mut Buffers,
+        chars: &[char],
+        boundary: &[bool],
+        matching: Matching,
+        overlay: Option<&Trie>,
+    ) {
+        let n = chars.len();
+        let Buffers { matches, edges, .. } = buffers;
+        reset_rows(edges, n + 1);
+
+        // --- single-cluster fallback ---
+        // Covers whitespace, punctuation, symbols, emoji, and any character
+        // with no better multi-char match. The trie is checked for a reading
+        // once the token wins (see `cluster_node`), so that single-char
+        // lettered entries like "%" → "pat6 sen1" are not lost. No token
+        // starts or ends inside a cluster.
+        let mut cluster_start = 0;
+        for end in 1..=n {
+            if boundary[end] {
+                edges[end].push(Edge {
+                    start: cluster_start,
+                    source: Source::Cluster,
+                });
+                cluster_start = end;
+            }
+        }
+
+        // --- multi-character spans ---
+        // TRIE MATCHES: CJK words (words.tsv), mixed Latin+CJK entries (AB膠,
+        // Hap唔Happy呀), hyphenated entries (chok-cheat, part-time), and any
+        // other lettered dict entries that carry a Jyutping reading. They are
+        // listed whether or not they win, so that the alpha-run fallback
+        // below stays silent for known words.
+        #[cfg(not(feature = "aho-corasick"))]
+        self.walk(matches, chars, boundary, matching.max_word_length);
+        #[cfg(feature = "aho-corasick")]
+        self.scan(matches, chars, boundary, matching.max_word_length);
+        if let Some(overlay) = overlay {
+            overlay.add_matches(matches, chars, boundary, matching.max_word_length);
+        }
+
+        // Starts go right to left, so each edges[end] lists nearer starts
+        // first.
+        for start in (0..n).rev() {
+            if !boundary[start] {
+                continue;
+            }
+            for &(end, freq) in &matches[start] {
+                edges[end].push(Edge {
+                    start,
+                    source: Source::Entry(freq),
+                });
+            }
+
+            // ALPHA RUN fallback: chars[start..end] is an alpha run if every
+            // cluster starts with a non-CJK alphanumeric or a connector, and
+            // the first and last clusters start with an alphanumeric (no
+            // leading or trailing connectors, but for the `ends_run` ones
+            // of C++ and C#). Combining marks inside a
+            // cluster ride along with their base letter. Fires only when the
+            // trie has no entry for the span, so words with dict readings
+            // (e.g. "ge" → "ge3") are never silently downgraded to
+            // reading=None.
+            if !is_alpha_char(fold_width(chars[start])) {
+                continue;
+            }
+            let mut ends_alpha = false;
+            for p in start..n {
+                if boundary[p] {
+                    // a curly apostrophe or hyphen variant joins as `'`
+                    // or `-` does, unless listed itself
+                    let wide = fold_width(chars[p]);
+                    let head = fold_ascii(wide);
+                    let connector =
+                        matching.connectors.contains(head) || matching.connectors.contains(wide);
+                    if !is_alpha_char(head) && !connector {
+                        break;
                     }
+                    ends_alpha = is_alpha_char(head) || (connector && ends_run(head));
                 }
+                let end = p + 1;
+                let known = matches[start].iter().any(|&(e, _)| e == end);
+                if boundary[end] && ends_alpha && !known {
+                    edges[end].push(Edge {
+                        start,
+                        source: Source::Alpha,
+                    });
+                }
+            }
+        }
+    }
 
-                // Determine whether chars[start..end] qualifies as an alpha run:
-                // every character must be a non-CJK alphanumeric or a connector,
-                // and the first and last characters must be alphanumeric (no leading
-                // or trailing connectors).
-                let span_is_alpha_run = {
-                    let span = &chars[start..end];
-                    span.iter().all(|&c| is_alpha_char(c) || is_connector(c))
-                        && span.first().map(|&c| is_alpha_char(c)).unwrap_or(false)
-                        && span.last().map(|&c| is_alpha_char(c)).unwrap_or(false)
-                };
+    /// Fill `matches` with the dictionary entries in `chars` by start:
+    /// `matches[start]` lists `(end, freq)` for each entry
+    /// `chars[start..end]` between cluster boundaries, nearest end first, at
+    /// most `max_word_length` chars long. Each start is walked forward once,
+    /// stopping as soon as the trie has no child for the next char or the
+    /// entry would be longer than any under the first char (`max_len`).
+    fn walk(
+        &self,
+        matches: &mut Vec<Vec<(usize, i64)>>,
+        chars: &[char],
+        boundary: &[bool],
+        max_word_length: Option<usize>,
+    ) {
+        reset_rows(matches, chars.len() + 1);
+        for (start, found) in matches.iter_mut().enumerate().take(chars.len()) {
+            if boundary[start] {
+                self.walk_from(found, chars, boundary, start, max_word_length);
+            }
+        }
+    }
 
-                // ALPHA RUN fallback — fires only when the trie has no entry for
-                // this span, ensuring that words with dict readings (e.g. "ge" → "ge3")
-                // are never silently downgraded to reading=None.
-                if !trie_matched && span_is_alpha_run {
-                    let cost = (dp[start].0 + 1, dp[start].1);
-                    if Self::better(&cost, &dp[end]) {
-                        dp[end] = cost;
-                        track[end] = (start, None);
-                    }
+    /// `walk` for one start.
+    fn walk_from(
+        &self,
+        found: &mut Vec<(usize, i64)>,
+        chars: &[char],
+        boundary: &[bool],
+        start: usize,
+        max_word_length: Option<usize>,
+    ) {
+        let limit = self
+            .max_len(chars[start])
+            .min(max_word_length.unwrap_or(usize::MAX));
+        let window = &chars[start..chars.len().min(start.saturating_add(limit))];
+        let mut node = self.root();
+        for (j, &ch) in window.iter().enumerate() {
+            let Some(child) = self.child(node, ch) else {
+                break;
+            };
+            node = child;
+            if boundary[start + j + 1] && !node.readings.is_empty() {
+                found.push((start + j + 1, node.freq));
+            }
+        }
+        // a variant may lead on to entries keyed by its canonical form even
+        // where the variant itself is an entry (裡 and 裡頭 → 裏頭)
+        if window.iter().all(|&ch| variants::canonical(ch) == ch) {
+            return;
+        }
+        let mut node = self.root();
+        for (j, &ch) in window.iter().enumerate() {
+            let Some(child) = self.child(node, variants::canonical(ch)) else {
+                break;
+            };
+            node = child;
+            let end = start + j + 1;
+            if boundary[end] && !node.readings.is_empty() && !found.iter().any(|&(e, _)| e == end) {
+                found.push((end, node.freq));
+            }
+        }
+        found.sort_by_key(|&(end, _)| end);
+    }
+
+    /// Add this overlay's entries to `matches` as filled in by `walk`,
+    /// keeping each start's ends in order and listed once.
+    fn add_matches(
+        &self,
+        matches: &mut [Vec<(usize, i64)>],
+        chars: &[char],
+        boundary: &[bool],
+        max_word_length: Option<usize>,
+    ) {
+        let mut extra = Vec::new();
+        for (start, found) in matches.iter_mut().enumerate().take(chars.len()) {
+            if !boundary[start] {
+                continue;
+            }
+            extra.clear();
+            self.walk_from(&mut extra, chars, boundary, start, max_word_length);
+            for &(end, freq) in &extra {
+                if let Err(i) = found.binary_search_by_key(&end, |&(e, _)| e) {
+                    found.insert(i, (end, freq));
                 }
             }
         }
+    }
 
-        // reconstruct token sequence by following track[] backwards
-        let mut tokens = Vec::new();
+    /// `walk` with the `aho-corasick` feature: all candidate entries are
+    /// found in a single scan of the text, then looked up in the trie, so
+    /// the result is the same. Falls back to `walk` if the automaton could
+    /// not be built.
+    #[cfg(feature = "aho-corasick")]
+    fn scan(
+        &self,
+        matches: &mut Vec<Vec<(usize, i64)>>,
+        chars: &[char],
+        boundary: &[bool],
+        max_word_length: Option<usize>,
+    ) {
+        let automaton = self.automaton.get_or_init(|| Automaton::new(self));
+        let Some(automaton) = automaton else {
+            return self.walk(matches, chars, boundary, max_word_length);
+        };
+        reset_rows(matches, chars.len() + 1);
+        for (start, end) in automaton.find(chars) {
+            if !boundary[start]
+                || !boundary[end]
+                || end - start > max_word_length.unwrap_or(usize::MAX)
+            {
+                continue;
+            }
+            if let Some(node) = self.lookup(&chars[start..end]) {
+                matches[start].push((end, node.freq));
+            }
+        }
+        for found in matches.iter_mut() {
+            found.sort_by_key(|&(end, _)| end);
+        }
+    }
+
+    /// `Scoring::Bigram`: Viterbi search for the most probable split where
+    /// each word's probability depends on the word before it (see
+    /// `Bigrams::log_prob`). A position can be reached by several last
+    /// words, so the best path is kept per candidate token, not per
+    /// position.
+    fn segment_bigram(&self, chars: &[char], edges: &[Vec<Edge>]) -> Vec<(usize, usize, Source)> {
+        struct State {
+            edge: Edge,
+            word: String,
+            score: f64,
+            // index of the previous token's state in states[edge.start]
+            back: usize,
+        }
+
+        let n = chars.len();
+        let total = self.total_freq().max(0) as f64 + 1.0;
+        let mut states: Vec<Vec<State>> = (0..=n).map(|_| Vec::new()).collect();
+        states[0].push(State {
+            edge: Edge {
+                start: 0,
+                source: Source::Alpha,
+            },
+            word: String::new(),
+            score: 0.0,
+            back: 0,
+        });
+
+        for end in 1..=n {
+            for &edge in &edges[end] {
+                let word: String = chars[edge.start..end].iter().collect();
+                let freq = match edge.source {
+                    Source::Entry(freq) => freq,
+                    _ => 0,
+                };
+                let unigram = (freq.max(0) as f64 + 1.0) / total;
+                let best = states[edge.start]
+                    .iter()
+                    .enumerate()
+                    .map(|(i, prev)| {
+                        (
+                            i,
+                            prev.score + self.bigrams.log_prob(&prev.word, &word, unigram),
+                        )
+                    })
+                    .reduce(|a, b| if b.1 > a.1 { b } else { a });
+                if let Some((back, score)) = best {
+                    states[end].push(State {
+                        edge,
+                        word,
+                        score,
+                        back,
+                    });
+                }
+            }
+        }
+
+        // follow the best final state backwards
+        let mut spans = Vec::new();
         let mut curr = n;
+        let mut index = states[n]
+            .iter()
+            .enumerate()
+            .reduce(|a, b| if b.1.score > a.1.score { b } else { a })
+            .map_or(0, |(i, _)| i);
         while curr > 0 {
-            let (prev, reading) = &track[curr];
-            let word: String = chars[*prev..curr].iter().collect();
-            tokens.push(Token {
-                word,
-                reading: reading.clone(),
-                yale: None, // filled in by annotate() in lib.rs after segmentation
-            });
-            curr = *prev;
-        }
-        tokens.reverse();
-        tokens
+            let state = &states[curr][index];
+            spans.push((state.edge.start, curr, state.edge.source));
+            index = state.back;
+            curr = state.edge.start;
+        }
+        spans.reverse();
+        spans
     }
 
-    /// Fewer tokens wins; on a tie, higher total frequency wins.
-    fn better(candidate: &(usize, i64), current: &(usize, i64)) -> bool {
-        if candidate.0 != current.0 {
-            candidate.0 < current.0
+    /// The entry for `word`, if it has a reading: as written, or else with
+    /// its variant characters in their canonical form (see `walk_from`).
+    fn lookup(&self, word: &[char]) -> Option<&TrieNode> {
+        let entry = |form: fn(char) -> char| {
+            let mut node = self.root();
+            for &ch in word {
+                node = self.child(node, form(ch))?;
+            }
+            Some(node).filter(|n| !n.readings.is_empty())
+        };
+        entry(|ch| ch).or_else(|| entry(variants::canonical))
+    }
+
+    /// Corpus frequency (freq.txt) of `word`, 0 if unknown.
+    pub fn freq(&self, word: &str) -> i64 {
+        let mut node = self.root();
+        for ch in word.chars() {
+            match self.child(node, ch) {
+                Some(child) => node = child,
+                None => return 0,
+            }
+        }
+        node.freq
+    }
+
+    /// Trie node giving the reading for a single grapheme cluster: the entry
+    /// for the whole cluster if there is one, else — for a CJK character
+    /// followed by a variation selector or similar — the base character's.
+    fn cluster_node(&self, cluster: &[char]) -> Option<&TrieNode> {
+        if let Some(node) = self.lookup(cluster) {
+            return Some(node);
+        }
+        let base = *cluster.first()?;
+        if cluster.len() > 1 && is_cjk(base) {
+            self.child(self.root(), base)
+                .filter(|n| !n.readings.is_empty())
         } else {
-            candidate.1 > current.1
+            None
+        }
+    }
+
+    /// What a token of `len` chars adds to the second DP field:
+    /// - `Fewest`: `freq`, the frequency of the token's entry (0 without one)
+    /// - `Longest`: the squared length, so that among splits with equally
+    ///   many tokens the most uneven one, with the longest word, wins
+    /// - `Frequency`: the frequency times the characters beyond the first,
+    ///   so frequent words outweigh rare long ones, but single characters
+    ///   score nothing and can never win by splitting a word apart
+    /// - `Unigram`: the word's log probability ln((freq + 1) / (total + 1))
+    ///   in thousandths, add-one smoothed so that entries missing from
+    ///   freq.txt and tokens without an entry get the lowest probability
+    fn gain(scoring: Scoring, freq: i64, len: usize, total: i64) -> i64 {
+        match scoring {
+            Scoring::Fewest | Scoring::Forward | Scoring::Backward => freq,
+            Scoring::Longest => (len * len) as i64,
+            Scoring::Frequency => freq * (len as i64 - 1),
+            Scoring::Unigram | Scoring::Bigram => {
+                let p = (freq.max(0) as f64 + 1.0) / (total.max(0) as f64 + 1.0);
+                (p.ln() * 1000.0).round() as i64
+            }
+        }
+    }
+
+    /// Fewer tokens wins; on a tie, the higher second field wins. Under
+    /// `Scoring::Frequency` and `Scoring::Unigram` the fields are compared
+    /// the other way round. Anything beats an unreached position.
+    fn better(scoring: Scoring, candidate: &(usize, i64), current: &(usize, i64)) -> bool {
+        if current.0 == usize::MAX {
+            return true;
+        }
+        let fewest = candidate.0 != current.0;
+        match scoring {
+            Scoring::Frequency | Scoring::Unigram if candidate.1 != current.1 => {
+                candidate.1 > current.1
+            }
+            _ if fewest => candidate.0 < current.0,
+            _ => candidate.1 > current.1,
         }
     }
 }
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+/// A character's Kangxi radical (1–214) and total stroke count, 0 where
+/// unknown.
+pub type RadicalStrokes = (u8, u8);
+
+/// Parse a Unihan kCantonese line (Unihan_Readings.txt), e.g.
+/// "U+20BA9\tkCantonese\tjam1 ngam1". Returns the character and its
+/// readings; other fields and comments give None.
+pub fn parse_cantonese_line(line: &str) -> Option<(char, Vec<&str>)> {
+    let mut parts = line.split('\t');
+    let code = parts.next()?.strip_prefix("U+")?;
+    if parts.next()? != "kCantonese" {
+        return None;
+    }
+    let ch = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+    let readings: Vec<&str> = parts.next()?.split_whitespace().collect();
+    if readings.is_empty() {
+        None
+    } else {
+        Some((ch, readings))
+    }
+}
+
+/// Parse a Unihan radical-stroke line (Unihan_IRGSources.txt), e.g.
+/// "U+597D\tkRSUnicode\t38.3" (radical 38 plus 3 strokes) or
+/// "U+597D\tkTotalStrokes\t6". Only the first value counts; the `'` of a
+/// simplified radical ("120'.3") is dropped. Other fields, comments and
+/// malformed lines give None.
+pub fn parse_line(line: &str) -> Option<(char, RadicalStrokes)> {
+    let mut parts = line.split('\t');
+    let code = parts.next()?.strip_prefix("U+")?;
+    let ch = char::from_u32(u32::from_str_radix(code, 16).ok()?)?;
+    let field = parts.next()?;
+    let value = parts.next()?.split_whitespace().next()?;
+    match field {
+        "kRSUnicode" => {
+            let (radical, _) = value.split_once('.')?;
+            let radical = radical.trim_end_matches('\'').parse().ok()?;
+            (1..=214).contains(&radical).then_some((ch, (radical, 0)))
+        }
+        "kTotalStrokes" => Some((ch, (0, value.parse().ok()?))),
+        _ => None,
+    }
+}
+
+/// Add the radicals and stroke counts in Unihan `text` to `table`, both
+/// fields of a character merged into one entry. Returns the number of
+/// lines used.
+pub fn load(table: &mut HashMap<char, RadicalStrokes>, text: &str) -> usize {
+    let mut count = 0;
+    for (ch, (radical, strokes)) in text.lines().filter_map(parse_line) {
+        let entry = table.entry(ch).or_default();
+        if radical > 0 {
+            entry.0 = radical;
+        }
+        if strokes > 0 {
+            entry.1 = strokes;
+        }
+        count += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cantonese_line() {
+        assert_eq!(
+            parse_cantonese_line("U+20BA9\tkCantonese\tjam1 ngam1"),
+            Some(('\u{20BA9}', vec!["jam1", "ngam1"]))
+        );
+        assert_eq!(
+            parse_cantonese_line("U+4E00\tkCantonese\tjat1"),
+            Some(('一', vec!["jat1"]))
+        );
+        // another field, an empty reading, a bad code point, a comment
+        assert_eq!(parse_cantonese_line("U+4E00\tkMandarin\tyī"), None);
+        assert_eq!(parse_cantonese_line("U+4E00\tkCantonese\t"), None);
+        assert_eq!(parse_cantonese_line("U+4E00\tkCantonese\t  "), None);
+        assert_eq!(parse_cantonese_line("U+D800\tkCantonese\tjat1"), None);
+        assert_eq!(parse_cantonese_line("U+XYZ\tkCantonese\tjat1"), None);
+        assert_eq!(parse_cantonese_line("# U+4E00\tkCantonese\tjat1"), None);
+    }
+
+    #[test]
+    fn test_load() {
+        let mut table = HashMap::new();
+        let text = "# comment\n\
+                    U+597D\tkRSUnicode\t38.3\n\
+                    U+597D\tkTotalStrokes\t6\n\
+                    U+7E9F\tkRSUnicode\t120'.3 120.3\n\
+                    U+597D\tkIRG_GSource\tG0-3A43\n\
+                    U+4E00\tkRSUnicode\t999.0\n";
+        assert_eq!(load(&mut table, text), 3);
+        assert_eq!(table[&'好'], (38, 6));
+        assert_eq!(table[&'纟'], (120, 0));
+        assert!(!table.contains_key(&'一'));
+    }
+}
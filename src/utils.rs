@@ -1,5 +1,6 @@
 /// True for CJK ideographs, including extension blocks needed for
-/// rare Cantonese characters like 𠮩 (U+20BA9) and 𠹌 (U+20E4C).
+/// rare Cantonese characters like 𠮩 (U+20BA9) and 𠹌 (U+20E4C), and the
+/// newer extensions F–I where Cantonese-specific characters keep being added.
 pub fn is_cjk(ch: char) -> bool {
     matches!(ch,
         '\u{4E00}'..='\u{9FFF}'     // CJK Unified Ideographs
@@ -8,7 +9,12 @@ pub fn is_cjk(ch: char) -> bool {
         | '\u{2A700}'..='\u{2B73F}' // CJK Extension C
         | '\u{2B740}'..='\u{2B81F}' // CJK Extension D
         | '\u{2B820}'..='\u{2CEAF}' // CJK Extension E
+        | '\u{2CEB0}'..='\u{2EBEF}' // CJK Extension F
+        | '\u{2EBF0}'..='\u{2EE5F}' // CJK Extension I
+        | '\u{30000}'..='\u{3134F}' // CJK Extension G
+        | '\u{31350}'..='\u{323AF}' // CJK Extension H
         | '\u{F900}'..='\u{FAFF}'   // CJK Compatibility Ideographs
+        | '\u{2F800}'..='\u{2FA1F}' // CJK Compatibility Ideographs Supplement
     )
 }
 
@@ -19,10 +25,79 @@ pub fn is_alpha_char(ch: char) -> bool {
     ch.is_alphanumeric() && !is_cjk(ch)
 }
 
-/// True if `ch` is an intra-word connector: hyphen, underscore, or apostrophe.
+/// The default intra-word connectors: hyphen, underscore, and apostrophe
+/// (the `connectors` option replaces them).
 /// Connectors are allowed *inside* an alpha run but not at the start or end.
 /// Examples: "part-time", "rust_canto", "i'm"
 /// Non-examples: "-abc" (leading), "abc-" (trailing), "3%" (% is not a connector)
-pub fn is_connector(ch: char) -> bool {
-    matches!(ch, '-' | '_' | '\'')
+pub const CONNECTORS: &str = "-_'";
+
+/// True if `ch`, when it is a connector, may also end an alpha run: `+`
+/// and `#`, which trail names like "C++" and "C#". Other connectors never
+/// do, so "v1.2." leaves the full stop out even with `.` as a connector.
+pub fn ends_run(ch: char) -> bool {
+    matches!(ch, '+' | '#')
+}
+
+/// Fold a typographic apostrophe (’ ʼ) or hyphen (‐ ‑ ‒ –), as smart
+/// quotes and word processors write them, to its ASCII form; other
+/// characters are returned unchanged.
+pub fn fold_punctuation(ch: char) -> char {
+    match ch {
+        '\u{2019}' | '\u{02BC}' => '\'',
+        '\u{2010}'..='\u{2013}' => '-',
+        _ => ch,
+    }
+}
+
+/// `fold_width`, then `fold_punctuation`: the ASCII form a character of the
+/// text is looked up in when the dictionary has no entry for it as is.
+pub fn fold_ascii(ch: char) -> char {
+    fold_punctuation(fold_width(ch))
+}
+
+/// Fold a full-width form (U+FF01..U+FF5E, e.g. Ａ１％－) to its ASCII
+/// equivalent; other characters are returned unchanged.
+pub fn fold_width(ch: char) -> char {
+    match ch {
+        '\u{FF01}'..='\u{FF5E}' => char::from_u32(ch as u32 - 0xFEE0).unwrap_or(ch),
+        _ => ch,
+    }
+}
+
+/// Terminal column width of `ch`: 2 for East Asian wide and full-width
+/// characters (CJK, kana, Hangul, full-width forms, most emoji), 0 for
+/// combining marks and zero-width joiners, 1 otherwise. An approximation of
+/// UAX #11 that is good enough for aligning interlinear text.
+pub fn char_width(ch: char) -> usize {
+    match ch {
+        '\u{0300}'..='\u{036F}' | '\u{200B}'..='\u{200D}' | '\u{FE00}'..='\u{FE0F}' => 0,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}' => 2,
+        _ => 1,
+    }
+}
+
+/// Terminal column width of `s`, see `char_width`.
+pub fn display_width(s: &str) -> usize {
+    s.chars().map(char_width).sum()
+}
+
+/// `s` with its first letter in upper case, for romanized proper nouns
+/// ("tùhng" → "Tùhng").
+pub fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
 }
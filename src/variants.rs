@@ -0,0 +1,112 @@
+/// Variant characters and the form the dictionary mostly uses for them,
+/// `(variant, canonical)`: 裡 → 裏, 為 → 爲, 群 → 羣. A word in one form
+/// finds entries written in the other (see `Trie::child`). Pairs whose
+/// forms differ in reading or use (着/著, 只/隻) are left out.
+const VARIANTS: [(char, char); 62] = [
+    ('裡', '裏'),
+    ('為', '爲'),
+    ('群', '羣'),
+    ('綫', '線'),
+    ('衞', '衛'),
+    ('峰', '峯'),
+    ('綉', '繡'),
+    ('麵', '麪'),
+    ('眞', '真'),
+    ('敎', '教'),
+    ('淸', '清'),
+    ('温', '溫'),
+    ('説', '說'),
+    ('悦', '悅'),
+    ('税', '稅'),
+    ('脱', '脫'),
+    ('兑', '兌'),
+    ('鈎', '鉤'),
+    ('偽', '僞'),
+    ('户', '戶'),
+    ('内', '內'),
+    ('吿', '告'),
+    ('黄', '黃'),
+    ('晋', '晉'),
+    ('眾', '衆'),
+    ('厠', '廁'),
+    ('啟', '啓'),
+    ('鷄', '雞'),
+    ('歎', '嘆'),
+    ('汙', '污'),
+    ('痴', '癡'),
+    ('床', '牀'),
+    ('彫', '雕'),
+    ('唇', '脣'),
+    ('鑑', '鑒'),
+    ('粧', '妝'),
+    ('擧', '舉'),
+    ('麽', '麼'),
+    ('濕', '溼'),
+    ('滙', '匯'),
+    ('携', '攜'),
+    ('秘', '祕'),
+    ('畧', '略'),
+    ('盃', '杯'),
+    ('册', '冊'),
+    ('柺', '拐'),
+    ('恒', '恆'),
+    ('囱', '囪'),
+    ('鼈', '鱉'),
+    ('蔴', '麻'),
+    ('綳', '繃'),
+    ('銹', '鏽'),
+    ('槪', '概'),
+    ('旣', '既'),
+    ('卽', '即'),
+    ('鄕', '鄉'),
+    ('郞', '郎'),
+    ('靑', '青'),
+    ('凈', '淨'),
+    ('兎', '兔'),
+    ('絶', '絕'),
+    ('幷', '並'),
+];
+
+/// The canonical form of `ch`, or `ch` if it is not a variant.
+pub fn canonical(ch: char) -> char {
+    VARIANTS
+        .iter()
+        .find(|&&(variant, _)| variant == ch)
+        .map_or(ch, |&(_, canonical)| canonical)
+}
+
+/// The other forms of `ch`: its canonical form, if it is a variant, and
+/// the variants of that form.
+pub fn others(ch: char) -> impl Iterator<Item = char> {
+    let canonical = canonical(ch);
+    let variants = VARIANTS
+        .iter()
+        .filter(move |&&(_, c)| c == canonical)
+        .map(|&(variant, _)| variant);
+    std::iter::once(canonical)
+        .chain(variants)
+        .filter(move |&c| c != ch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_others() {
+        assert_eq!(canonical('裡'), '裏');
+        assert_eq!(canonical('裏'), '裏');
+        assert_eq!(others('裡').collect::<String>(), "裏");
+        assert_eq!(others('裏').collect::<String>(), "裡");
+        assert_eq!(others('好').count(), 0);
+        // every variant maps straight to a form that is not itself a variant
+        for (variant, canonical) in VARIANTS {
+            assert_ne!(variant, canonical);
+            assert!(
+                VARIANTS.iter().all(|&(v, _)| v != canonical),
+                "{}",
+                canonical
+            );
+        }
+    }
+}